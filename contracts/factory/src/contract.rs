@@ -0,0 +1,553 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, wasm_execute, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply,
+    Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw_zll_std_factory::{
+    msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
+    response::{
+        ConfigResponse, MarketResponse, MarketsResponse, SeriesResponse, TemplateResponse,
+        TemplatesResponse,
+    },
+    state::{
+        get_config, get_market, get_markets, get_markets_count, get_owner, get_series,
+        get_template_names, may_get_market, may_get_series, may_get_template, next_market_id,
+        next_series_id, remove_template, set_code_ids, set_config, set_market, set_series,
+        set_template, CodeIds, Config, MarketRecord, MarketTemplate, Series,
+    },
+};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use cw_zll_std_market::{
+    response::{LiquidityPoolResponse, MarketPhase, MarketPhaseResponse},
+    state::MarketPhasesInfo,
+};
+use cw_zll_std_utils::{
+    migration::assert_migration,
+    reply::{parse_reply_instantiate_data, MsgInstantiateContractResponse},
+};
+
+use crate::error::ContractError;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw-zll-factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const ADDR_WHILE_INSTANTIATION: &str = "";
+
+/// A `reply` call code ID used for sub-messages.
+const INSTANTIATE_MARKET_REPLY_ID: u64 = 1;
+
+/// The number of markets returned by `GetMarkets` when no limit is given.
+const DEFAULT_MARKETS_LIMIT: u32 = 10;
+
+/// The maximum number of markets returned by `GetMarkets`.
+const MAX_MARKETS_LIMIT: u32 = 30;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    set_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_validate(&msg.owner)?,
+            code_ids: msg.code_ids,
+        },
+    )?;
+
+    Ok(Response::new().add_attribute("method", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // the market operator of a series can roll it too, every other message is owner-only
+    if !matches!(msg, ExecuteMsg::RollSeries { .. }) && info.sender != get_owner(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match msg {
+        ExecuteMsg::SetCodeIds { code_ids } => execute_set_code_ids(deps, code_ids),
+        ExecuteMsg::SetTemplate { name, template } => execute_set_template(deps, name, template),
+        ExecuteMsg::RemoveTemplate { name } => execute_remove_template(deps, name),
+        ExecuteMsg::CreateMarket {
+            template,
+            market_operator,
+            asset_infos,
+            market_phases_info,
+            target_liquidity,
+            minimum_liquidity,
+            collateral_price,
+            oracle,
+            recurring,
+        } => execute_create_market(
+            deps,
+            env,
+            template,
+            market_operator,
+            asset_infos,
+            market_phases_info,
+            target_liquidity,
+            minimum_liquidity,
+            collateral_price,
+            oracle,
+            recurring,
+        ),
+        ExecuteMsg::RollSeries { series_id } => {
+            execute_roll_series(deps, env, info.sender, series_id)
+        }
+    }
+}
+
+fn execute_set_code_ids(deps: DepsMut, code_ids: CodeIds) -> Result<Response, ContractError> {
+    let Config { code_ids, .. } = set_code_ids(deps.storage, code_ids)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "set_code_ids".to_string()),
+        ("market_code_id", code_ids.market.to_string()),
+        (
+            "liquidity_pool_code_id",
+            code_ids.liquidity_pool.to_string(),
+        ),
+        (
+            "liquidity_pool_token_code_id",
+            code_ids.liquidity_pool_token.to_string(),
+        ),
+        ("option_token_code_id", code_ids.option_token.to_string()),
+    ]))
+}
+
+fn execute_set_template(
+    deps: DepsMut,
+    name: String,
+    template: MarketTemplate,
+) -> Result<Response, ContractError> {
+    for addr in [&template.treasury, &template.guardian]
+        .iter()
+        .copied()
+        .flatten()
+    {
+        deps.api.addr_validate(addr)?;
+    }
+
+    set_template(deps.storage, &name, &template)?;
+
+    Ok(Response::new().add_attributes(vec![("method", "set_template"), ("name", &name)]))
+}
+
+fn execute_remove_template(deps: DepsMut, name: String) -> Result<Response, ContractError> {
+    if may_get_template(deps.storage, &name)?.is_none() {
+        return Err(ContractError::TemplateNotFound { name });
+    }
+
+    remove_template(deps.storage, &name);
+
+    Ok(Response::new().add_attributes(vec![("method", "remove_template"), ("name", &name)]))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute_create_market(
+    deps: DepsMut,
+    env: Env,
+    template_name: String,
+    market_operator: String,
+    asset_infos: [AssetInfo; 2],
+    market_phases_info: MarketPhasesInfo,
+    target_liquidity: Option<Uint128>,
+    minimum_liquidity: Option<Uint128>,
+    collateral_price: Option<Decimal>,
+    oracle: Option<String>,
+    recurring: Option<bool>,
+) -> Result<Response, ContractError> {
+    let market_operator = deps.api.addr_validate(&market_operator)?;
+
+    let series_id = if recurring.unwrap_or(false) {
+        let series_id = next_series_id(deps.storage)?;
+
+        // the market ids are added once the markets are instantiated
+        set_series(
+            deps.storage,
+            &Series {
+                id: series_id,
+                template: template_name.clone(),
+                market_operator: market_operator.clone(),
+                asset_infos: asset_infos.clone(),
+                target_liquidity,
+                minimum_liquidity,
+                collateral_price,
+                oracle: oracle.clone(),
+                market_ids: vec![],
+            },
+        )?;
+
+        Some(series_id)
+    } else {
+        None
+    };
+
+    let response = create_market(
+        deps,
+        env,
+        template_name,
+        market_operator,
+        asset_infos,
+        market_phases_info,
+        target_liquidity,
+        minimum_liquidity,
+        collateral_price,
+        oracle,
+        series_id,
+        None,
+    )?;
+
+    Ok(response.add_attribute("method", "create_market"))
+}
+
+fn execute_roll_series(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    series_id: u64,
+) -> Result<Response, ContractError> {
+    let series = may_get_series(deps.storage, series_id)?
+        .ok_or(ContractError::SeriesNotFound { series_id })?;
+
+    if sender != get_owner(deps.storage)? && sender != series.market_operator {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let latest_market_id = *series
+        .market_ids
+        .last()
+        .ok_or(ContractError::SeriesNotReady { series_id })?;
+    let latest_market = get_market(deps.storage, latest_market_id)?;
+
+    if !query_market_phase(deps.as_ref(), &latest_market.market)?.can_roll_over_liquidity() {
+        return Err(ContractError::SeriesNotReady { series_id });
+    }
+
+    // the successor keeps the phase durations of the latest market, starting at the current block
+    let MarketPhasesInfo {
+        market_started_at,
+        lp_phase_ends_at,
+        amm_phase_ends_at,
+        settlement_phase_ends_at,
+    } = latest_market.market_phases_info;
+    let shift = env.block.height - market_started_at;
+
+    let response = create_market(
+        deps,
+        env,
+        series.template,
+        series.market_operator,
+        series.asset_infos,
+        MarketPhasesInfo {
+            market_started_at: market_started_at + shift,
+            lp_phase_ends_at: lp_phase_ends_at + shift,
+            amm_phase_ends_at: amm_phase_ends_at + shift,
+            settlement_phase_ends_at: settlement_phase_ends_at + shift,
+        },
+        series.target_liquidity,
+        series.minimum_liquidity,
+        series.collateral_price,
+        series.oracle,
+        Some(series_id),
+        Some(latest_market.market),
+    )?;
+
+    Ok(response.add_attributes(vec![
+        ("method", "roll_series".to_string()),
+        ("series_id", series_id.to_string()),
+    ]))
+}
+
+/// Instantiates a market from the template and adds it to the registry. The market is added to
+/// the series once it is instantiated, succeeding the `predecessor` market of the series.
+#[allow(clippy::too_many_arguments)]
+fn create_market(
+    deps: DepsMut,
+    env: Env,
+    template_name: String,
+    market_operator: Addr,
+    asset_infos: [AssetInfo; 2],
+    market_phases_info: MarketPhasesInfo,
+    target_liquidity: Option<Uint128>,
+    minimum_liquidity: Option<Uint128>,
+    collateral_price: Option<Decimal>,
+    oracle: Option<String>,
+    series_id: Option<u64>,
+    predecessor: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let template = may_get_template(deps.storage, &template_name)?.ok_or_else(|| {
+        ContractError::TemplateNotFound {
+            name: template_name.clone(),
+        }
+    })?;
+    let Config { owner, code_ids } = get_config(deps.storage)?;
+
+    let market_id = next_market_id(deps.storage)?;
+
+    // the addresses are set once the market is instantiated
+    set_market(
+        deps.storage,
+        &MarketRecord {
+            id: market_id,
+            market: Addr::unchecked(ADDR_WHILE_INSTANTIATION),
+            liquidity_pool: Addr::unchecked(ADDR_WHILE_INSTANTIATION),
+            template: template_name.clone(),
+            asset_infos: asset_infos.clone(),
+            market_phases_info: market_phases_info.clone(),
+            created_at: env.block.height,
+            series_id,
+        },
+    )?;
+
+    let instantiate_market_msg = cw_zll_std_market::msg::InstantiateMsg {
+        market_operator,
+        liquidity_pool_code_id: code_ids.liquidity_pool,
+        liquidity_pool_token_code_id: code_ids.liquidity_pool_token,
+        option_token_code_id: Some(code_ids.option_token),
+        asset_infos,
+        market_phases_info,
+        blocks_per_year: template.blocks_per_year,
+        alpha: template.alpha,
+        target_liquidity,
+        minimum_liquidity,
+        collateral_price,
+        oracle,
+        twap_window: template.twap_window,
+        max_price_age: template.max_price_age,
+        max_price_deviation: template.max_price_deviation,
+        buyback_spread: template.buyback_spread,
+        max_buyback_volume: template.max_buyback_volume,
+        protocol_fee_rate: template.protocol_fee_rate,
+        treasury: template.treasury,
+        guardian: template.guardian,
+        timelock_delay: template.timelock_delay,
+        predecessor: predecessor.map(|predecessor| predecessor.to_string()),
+    };
+
+    // the owner administers the markets, so it can migrate them
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            WasmMsg::Instantiate {
+                admin: Some(owner.to_string()),
+                code_id: code_ids.market,
+                msg: to_binary(&instantiate_market_msg)?,
+                funds: vec![],
+                label: format!("ZLL Market {}", market_id),
+            },
+            INSTANTIATE_MARKET_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            ("market_id", market_id.to_string()),
+            ("template", template_name),
+        ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::GetConfig {} => query_get_config(deps),
+        QueryMsg::GetTemplate { name } => query_get_template(deps, name),
+        QueryMsg::GetTemplates {} => query_get_templates(deps),
+        QueryMsg::GetMarket { market_id } => query_get_market(deps, market_id),
+        QueryMsg::GetMarkets {
+            asset_infos,
+            phase,
+            created_from,
+            created_until,
+            start_after,
+            limit,
+        } => query_get_markets(
+            deps,
+            asset_infos,
+            phase,
+            created_from,
+            created_until,
+            start_after,
+            limit,
+        ),
+        QueryMsg::GetSeries { series_id } => query_get_series(deps, series_id),
+    }
+}
+
+fn query_get_config(deps: Deps) -> Result<Binary, ContractError> {
+    let Config { owner, code_ids } = get_config(deps.storage)?;
+
+    Ok(to_binary(&ConfigResponse { owner, code_ids })?)
+}
+
+fn query_get_template(deps: Deps, name: String) -> Result<Binary, ContractError> {
+    let template = may_get_template(deps.storage, &name)?
+        .ok_or_else(|| ContractError::TemplateNotFound { name: name.clone() })?;
+
+    Ok(to_binary(&TemplateResponse { name, template })?)
+}
+
+fn query_get_templates(deps: Deps) -> Result<Binary, ContractError> {
+    let response = TemplatesResponse {
+        names: get_template_names(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_market(deps: Deps, market_id: u64) -> Result<Binary, ContractError> {
+    let market = may_get_market(deps.storage, market_id)?
+        .ok_or(ContractError::MarketNotFound { market_id })?;
+
+    Ok(to_binary(&MarketResponse { market })?)
+}
+
+fn query_get_series(deps: Deps, series_id: u64) -> Result<Binary, ContractError> {
+    let series = may_get_series(deps.storage, series_id)?
+        .ok_or(ContractError::SeriesNotFound { series_id })?;
+
+    Ok(to_binary(&SeriesResponse { series })?)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn query_get_markets(
+    deps: Deps,
+    asset_infos: Option<[AssetInfo; 2]>,
+    phase: Option<MarketPhase>,
+    created_from: Option<u64>,
+    created_until: Option<u64>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Binary, ContractError> {
+    let limit = limit
+        .unwrap_or(DEFAULT_MARKETS_LIMIT)
+        .min(MAX_MARKETS_LIMIT) as usize;
+
+    let mut markets = vec![];
+
+    for market in get_markets(deps.storage, start_after) {
+        if markets.len() == limit {
+            break;
+        }
+
+        let market = market?;
+
+        let other_asset_infos =
+            matches!(&asset_infos, Some(asset_infos) if &market.asset_infos != asset_infos);
+        let created_before = matches!(created_from, Some(from) if market.created_at < from);
+        let created_after = matches!(created_until, Some(until) if market.created_at > until);
+
+        if other_asset_infos || created_before || created_after {
+            continue;
+        }
+
+        // the phase is only queried for the markets matching the other filters
+        if let Some(phase) = &phase {
+            if &query_market_phase(deps, &market.market)? != phase {
+                continue;
+            }
+        }
+
+        markets.push(market);
+    }
+
+    Ok(to_binary(&MarketsResponse { markets })?)
+}
+
+fn query_market_phase(deps: Deps, market: &Addr) -> StdResult<MarketPhase> {
+    let MarketPhaseResponse { phase } = deps
+        .querier
+        .query_wasm_smart(market, &cw_zll_std_market::msg::QueryMsg::GetMarketPhase {})?;
+
+    Ok(phase)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.result.is_err() {
+        return Err(ContractError::Std(StdError::GenericErr {
+            msg: msg.result.unwrap_err(),
+        }));
+    }
+
+    match msg.id {
+        INSTANTIATE_MARKET_REPLY_ID => reply_on_instantiate_market(deps, env, msg),
+        _ => Err(ContractError::Std(StdError::GenericErr {
+            msg: format!("reply id `{:?}` is invalid", msg.id),
+        })),
+    }
+}
+
+fn reply_on_instantiate_market(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let mut market = get_market(deps.storage, get_markets_count(deps.storage)?)?;
+
+    if market.market != Addr::unchecked(ADDR_WHILE_INSTANTIATION) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let response: MsgInstantiateContractResponse = parse_reply_instantiate_data(msg)?;
+
+    market.market = deps.api.addr_validate(&response.contract_address)?;
+
+    // the market instantiates its pool before it replies to the factory
+    let LiquidityPoolResponse { liquidity_pool } = deps.querier.query_wasm_smart(
+        market.market.clone(),
+        &cw_zll_std_market::msg::QueryMsg::GetLiquidityPool {},
+    )?;
+    market.liquidity_pool = liquidity_pool;
+
+    set_market(deps.storage, &market)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        ("market_addr", market.market.to_string()),
+        ("liquidity_pool_addr", market.liquidity_pool.to_string()),
+    ]);
+
+    if let Some(series_id) = market.series_id {
+        let mut series = get_series(deps.storage, series_id)?;
+
+        // the liquidity escrowed in the predecessor is rolled over into the pool of the new market
+        if let Some(predecessor_id) = series.market_ids.last() {
+            let predecessor = get_market(deps.storage, *predecessor_id)?;
+
+            response = response.add_message(wasm_execute(
+                predecessor.market,
+                &cw_zll_std_market::msg::ExecuteMsg::RolloverLiquidity {
+                    market: market.market.to_string(),
+                },
+                vec![],
+            )?);
+        }
+
+        series.market_ids.push(market.id);
+        set_series(deps.storage, &series)?;
+    }
+
+    Ok(response)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = assert_migration(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "migrate".to_string()),
+        ("previous_version", previous_version.to_string()),
+        ("version", CONTRACT_VERSION.to_string()),
+    ]))
+}
@@ -0,0 +1,30 @@
+use cosmwasm_std::StdError;
+use cw_zll_std_utils::{migration::MigrationError, reply::ParseReplyError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Template `{name}` does not exist")]
+    TemplateNotFound { name: String },
+
+    #[error("Market {market_id} does not exist")]
+    MarketNotFound { market_id: u64 },
+
+    #[error("Series {series_id} does not exist")]
+    SeriesNotFound { series_id: u64 },
+
+    #[error("The latest market of series {series_id} is not settled yet")]
+    SeriesNotReady { series_id: u64 },
+}
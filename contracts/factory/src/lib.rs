@@ -0,0 +1,4 @@
+pub mod contract;
+mod error;
+
+pub use crate::error::ContractError;
@@ -1,2 +1,4 @@
+pub mod contract;
+
 pub use cw20::BalanceResponse;
 pub use cw20_base::*;
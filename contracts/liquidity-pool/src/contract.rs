@@ -1,28 +1,47 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, wasm_execute, wasm_instantiate, Addr, Binary, CosmosMsg, Decimal,
-    Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128,
+    attr, coin, from_binary, to_binary, wasm_execute, Addr, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
-use cw_zll_std_market::response::MarketPhaseResponse;
-use cw_zll_std_utils::reply::{parse_reply_instantiate_data, MsgInstantiateContractResponse};
+use cw_storage_plus::{Bound, U64Key};
+use cw_zll_std_market::{
+    response::{
+        CollateralPriceResponse, CommittedCollateralResponse, IsAllowedResponse,
+        LockedAssetsResponse, MarketPhase, MarketPhaseResponse, OutstandingLoansResponse,
+        PausedActionsResponse, PendingUpdateResponse, TargetLiquidityResponse,
+    },
+    state::{AllowlistRole, PausableAction, PausedActions, PendingUpdate},
+};
+use cw_zll_std_utils::{
+    migration::assert_migration,
+    reply::{parse_reply_instantiate_data, MsgInstantiateContractResponse},
+};
 
 use crate::{
     error::ContractError,
-    state::{Config, CONFIG},
+    state::{
+        Config, Observation, Rollover, CONFIG, OBSERVATIONS, ROLLOVER, ROLLOVER_SHARES,
+        ROLLOVER_SHARES_TOTAL,
+    },
 };
 
 use cw_zll_std_liquidity_pool::{
     ap::{
         asset::{format_lp_token_name, Asset, AssetInfo, PairInfo},
         factory::PairType,
-        pair::{Cw20HookMsg, PoolResponse},
-        querier::query_supply,
+        pair::{CumulativePricesResponse, PoolResponse},
+        querier::{query_supply, query_token_balance},
         U256,
     },
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg, TokenInstantiateMsg},
+    msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, TokenInstantiateMsg},
+    response::{
+        HolderShareResponse, LiquidityProgressResponse, NavPerShareResponse, PremiumEarnedResponse,
+        RolloverSharesResponse, TwapResponse,
+    },
 };
 
 // version info for migration info
@@ -34,6 +53,13 @@ const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
 
 const ADDR_WHILE_INSTANTIATION: &str = "";
 
+/// The amount of LP tokens minted to the pool itself on the first deposit. These tokens are
+/// locked forever, so the share price cannot be inflated by a tiny first deposit.
+pub const MINIMUM_LIQUIDITY_AMOUNT: Uint128 = Uint128::new(1_000);
+
+/// The number of decimal places the cumulative prices are scaled by.
+pub const TWAP_PRECISION: u8 = 6;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -58,17 +84,22 @@ pub fn instantiate(
             pair_type: PairType::Xyk {},
         },
         factory_addr: deps.api.addr_validate(msg.factory_addr.as_ref())?,
+        block_time_last: env.block.time.seconds(),
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+        reserves_last: [Uint128::zero(), Uint128::zero()],
     };
 
     CONFIG.save(deps.storage, &config)?;
 
     let token_name = format_lp_token_name(msg.asset_infos, &deps.querier)?;
 
-    // Create the LP token contract
+    // Create the LP token contract, administered by the market like the pool itself
     let instantiate_token_msg = SubMsg::reply_on_success(
-        wasm_instantiate(
-            msg.token_code_id,
-            &TokenInstantiateMsg {
+        WasmMsg::Instantiate {
+            admin: Some(config.factory_addr.to_string()),
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
                 name: token_name,
                 symbol: "uLP".to_string(),
                 decimals: 6,
@@ -77,10 +108,10 @@ pub fn instantiate(
                     minter: env.contract.address.to_string(),
                     cap: None,
                 }),
-            },
-            vec![],
-            String::from("ZLL LP token"),
-        )?,
+            })?,
+            funds: vec![],
+            label: String::from("ZLL LP token"),
+        },
         INSTANTIATE_TOKEN_REPLY_ID,
     );
 
@@ -97,20 +128,92 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::ProvideLiquidity { assets, .. } => {
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            min_share_out,
+            ..
+        } => {
             assert_deposits_enabled(deps.branch())?;
+            assert_allowlisted(deps.as_ref(), &info.sender)?;
             assert_balanced_assets_ratio(&assets)?;
-            provide_liquidity(deps, env, info, assets)
+            provide_liquidity(deps, env, info, assets, min_share_out)
         }
-        ExecuteMsg::Receive(msg) => {
-            assert_withrawals_enabled(deps.branch())?;
-            receive_cw20(deps, env, info, msg)
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::TransferAssets { assets, recipient } => {
+            transfer_assets(deps, env, info, assets, recipient)
+        }
+        ExecuteMsg::OptOutRollover {} => opt_out_rollover(deps, info),
+        ExecuteMsg::RolloverLiquidity { liquidity_pool } => {
+            rollover_liquidity(deps, env, info, liquidity_pool)
         }
-        _ => Ok(Response::default()),
+        ExecuteMsg::ClaimRolloverShares {} => claim_rollover_shares(deps, env, info),
+        ExecuteMsg::SyncReserves {} => sync_reserves(deps, env),
     }
 }
 
-fn assert_deposits_enabled(deps: DepsMut) -> Result<(), ContractError> {
+/// ## Description
+/// Transfers assets held by the pool on behalf of the market. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **assets** are a [`Vec`] of [`Asset`] type items. These are the assets to transfer.
+///
+/// * **recipient** is an object of type [`String`]. This is the address that receives the assets.
+pub fn transfer_assets(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.factory_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    // Accumulate prices for the reserves before the transfer
+    update_cumulative_prices(deps.storage, &env, &mut config)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    for asset in assets.iter().filter(|a| !a.amount.is_zero()) {
+        if !config
+            .pair_info
+            .asset_infos
+            .iter()
+            .any(|info| info.equal(&asset.info))
+        {
+            return Err(ContractError::AssetMismatch {});
+        }
+
+        messages.push(asset.clone().into_msg(&deps.querier, recipient.clone())?);
+    }
+
+    messages.push(create_sync_reserves_msg(&env)?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "transfer_assets"),
+        attr("recipient", recipient.as_str()),
+        attr(
+            "assets",
+            assets
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    ]))
+}
+
+fn query_market_phase(deps: Deps) -> StdResult<MarketPhase> {
     let config = CONFIG.load(deps.storage)?;
 
     let MarketPhaseResponse { phase } = deps.querier.query_wasm_smart(
@@ -118,10 +221,82 @@ fn assert_deposits_enabled(deps: DepsMut) -> Result<(), ContractError> {
         &cw_zll_std_market::msg::QueryMsg::GetMarketPhase {},
     )?;
 
+    Ok(phase)
+}
+
+fn query_paused_actions(deps: Deps) -> StdResult<PausedActions> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let PausedActionsResponse { paused_actions, .. } = deps.querier.query_wasm_smart(
+        config.factory_addr,
+        &cw_zll_std_market::msg::QueryMsg::GetPausedActions {},
+    )?;
+
+    Ok(paused_actions)
+}
+
+fn query_has_pending_exit_update(deps: Deps) -> StdResult<bool> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let PendingUpdateResponse { pending_update, .. } = deps.querier.query_wasm_smart(
+        config.factory_addr,
+        &cw_zll_std_market::msg::QueryMsg::GetPendingUpdate {},
+    )?;
+
+    Ok(matches!(pending_update, Some(PendingUpdate { update, .. }) if update.lets_lps_exit()))
+}
+
+fn query_committed_collateral(deps: Deps) -> StdResult<Asset> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let CommittedCollateralResponse {
+        committed_collateral,
+    } = deps.querier.query_wasm_smart(
+        config.factory_addr,
+        &cw_zll_std_market::msg::QueryMsg::GetCommittedCollateral {},
+    )?;
+
+    Ok(committed_collateral)
+}
+
+fn assert_not_paused(deps: Deps, action: PausableAction) -> Result<(), ContractError> {
+    if query_paused_actions(deps)?.is_paused(action) {
+        return Err(ContractError::ActionPaused { action });
+    }
+
+    Ok(())
+}
+
+fn assert_deposits_enabled(deps: DepsMut) -> Result<(), ContractError> {
+    let phase = query_market_phase(deps.as_ref())?;
+
     if !phase.can_lp_accept_deposits() {
         return Err(ContractError::Unauthorized {});
     }
 
+    assert_not_paused(deps.as_ref(), PausableAction::Deposit)
+}
+
+/// Checks the market allows the account to provide liquidity, which it does unless the
+/// liquidity provider role is restricted to allowlisted accounts.
+fn assert_allowlisted(deps: Deps, account: &Addr) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let IsAllowedResponse { is_allowed } = deps.querier.query_wasm_smart(
+        config.factory_addr,
+        &cw_zll_std_market::msg::QueryMsg::IsAllowed {
+            role: AllowlistRole::LiquidityProvider,
+            address: account.to_string(),
+        },
+    )?;
+
+    if !is_allowed {
+        return Err(ContractError::NotAllowlisted {
+            role: AllowlistRole::LiquidityProvider,
+            address: account.to_string(),
+        });
+    }
+
     Ok(())
 }
 
@@ -137,19 +312,18 @@ fn assert_balanced_assets_ratio(_assets: &[Asset; 2]) -> Result<(), ContractErro
     Ok(())
 }
 
+/// Checks the market allows withdrawals, which it does in the phases accepting them and while an
+/// update of the phase schedule or the fees queued by the market operator waits to be applied, so
+/// the LPs objecting to the update can exit pro-rata beforehand with the reserves not committed to
+/// the open PUT options.
 fn assert_withrawals_enabled(deps: DepsMut) -> Result<(), ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+    let phase = query_market_phase(deps.as_ref())?;
 
-    let MarketPhaseResponse { phase } = deps.querier.query_wasm_smart(
-        config.factory_addr,
-        &cw_zll_std_market::msg::QueryMsg::GetMarketPhase {},
-    )?;
-
-    if !phase.can_lp_accept_withdrawals() {
+    if !phase.can_lp_accept_withdrawals() && !query_has_pending_exit_update(deps.as_ref())? {
         return Err(ContractError::Unauthorized {});
     }
 
-    Ok(())
+    assert_not_paused(deps.as_ref(), PausableAction::Withdraw)
 }
 
 /// ## Description
@@ -165,12 +339,15 @@ fn assert_withrawals_enabled(deps: DepsMut) -> Result<(), ContractError> {
 ///
 /// * **assets** is an array with two objects of type [`Asset`]. These are the assets available in the pool.
 ///
+/// * **min_share_out** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of LP tokens the provider accepts to receive.
+///
 // NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
 pub fn provide_liquidity(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     assets: [Asset; 2],
+    min_share_out: Option<Uint128>,
 ) -> Result<Response, ContractError> {
     assets[0].info.check(deps.api)?;
     assets[1].info.check(deps.api)?;
@@ -179,7 +356,7 @@ pub fn provide_liquidity(
         asset.assert_sent_native_token_balance(&info)?;
     }
 
-    let config: Config = CONFIG.load(deps.storage)?;
+    let mut config: Config = CONFIG.load(deps.storage)?;
     let mut pools: [Asset; 2] = config
         .pair_info
         .query_pools(&deps.querier, env.contract.address.clone())?;
@@ -224,14 +401,30 @@ pub fn provide_liquidity(
         }
     }
 
+    // Accumulate prices for the reserves before the deposit
+    update_cumulative_prices(deps.storage, &env, &mut config)?;
+
     let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
     let share = if total_share.is_zero() {
         // Initial share = collateral amount
-        Uint128::new(
+        let share = Uint128::new(
             (U256::from(deposits[0].u128()) * U256::from(deposits[1].u128()))
                 .integer_sqrt()
                 .as_u128(),
         )
+        .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
+        .map_err(|_| ContractError::MinimumLiquidityAmount {
+            min_liquidity_amount: MINIMUM_LIQUIDITY_AMOUNT,
+        })?;
+
+        // Mint the minimum liquidity to the pool itself, where it stays locked forever
+        messages.extend(mint_liquidity_token_message(
+            &config,
+            env.contract.address.clone(),
+            MINIMUM_LIQUIDITY_AMOUNT,
+        )?);
+
+        share
     } else {
         // min(1, 2)
         // 1. deposit_0 * (total_share / sqrt(pool_0 * pool_1))
@@ -244,12 +437,26 @@ pub fn provide_liquidity(
         )
     };
 
+    if share.is_zero() {
+        return Err(ContractError::ZeroShare {});
+    }
+
+    if let Some(min_share_out) = min_share_out {
+        if share < min_share_out {
+            return Err(ContractError::MinimumShareOut {
+                share,
+                min_share_out,
+            });
+        }
+    }
+
     // Mint LP tokens for the sender
     messages.extend(mint_liquidity_token_message(
         &config,
         info.sender.clone(),
         share,
     )?);
+    messages.push(create_sync_reserves_msg(&env)?);
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "provide_liquidity"),
@@ -273,22 +480,35 @@ pub fn provide_liquidity(
 ///
 /// * **cw20_msg** is an object of type [`Cw20ReceiveMsg`]. This is the CW20 message that has to be processed.
 pub fn receive_cw20(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => withdraw_liquidity(
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            recipient,
+            min_assets,
+            output_asset,
+        }) => {
+            assert_withrawals_enabled(deps.branch())?;
+            withdraw_liquidity(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                cw20_msg.amount,
+                recipient,
+                min_assets,
+                output_asset,
+            )
+        }
+        Ok(Cw20HookMsg::OptInRollover {}) => opt_in_rollover(
             deps,
-            env,
             info,
             Addr::unchecked(cw20_msg.sender),
             cw20_msg.amount,
         ),
-        Ok(inner_msg) => Err(ContractError::CustomError {
-            val: format!("Msg `{:?}` cannot be handled", inner_msg),
-        }),
         Err(err) => Err(ContractError::Std(err)),
     }
 }
@@ -303,44 +523,111 @@ pub fn receive_cw20(
 ///
 /// * **info** is an object of type [`MessageInfo`].
 ///
-/// * **sender** is an object of type [`Addr`]. This is the address that will receive assets back from the pair contract.
+/// * **sender** is an object of type [`Addr`]. This is the address that sent the LP tokens to the pair contract.
 ///
 /// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+///
+/// * **recipient** is an [`Option`] field of type [`String`]. This is the address that will receive assets back from the pair contract, defaults to the sender.
+///
+/// * **min_assets** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of assets to receive.
+///
+/// * **output_asset** is an [`Option`] field of type [`AssetInfo`]. This is the single asset to receive in the post-settlement phase.
+#[allow(clippy::too_many_arguments)]
 pub fn withdraw_liquidity(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     sender: Addr,
     amount: Uint128,
+    recipient: Option<String>,
+    min_assets: Option<Vec<Asset>>,
+    output_asset: Option<AssetInfo>,
 ) -> Result<Response, ContractError> {
-    let config: Config = CONFIG.load(deps.storage).unwrap();
+    let mut config: Config = CONFIG.load(deps.storage).unwrap();
 
     if info.sender != config.pair_info.liquidity_token {
         return Err(ContractError::Unauthorized {});
     }
 
-    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
-    let refund_assets = get_share_in_assets(&pools, amount, total_share);
-
-    // Update the pool info
-    let messages: Vec<CosmosMsg> = vec![
-        refund_assets[0]
-            .clone()
-            .into_msg(&deps.querier, sender.clone())?,
-        refund_assets[1]
-            .clone()
-            .into_msg(&deps.querier, sender.clone())?,
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => sender.clone(),
+    };
+
+    let (mut pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let market_phase = query_market_phase(deps.as_ref())?;
+
+    // An LP exiting before the market accepts withdrawals, while an update is queued, only shares
+    // the collateral which is not committed to the open PUT options
+    if !market_phase.clone().can_lp_accept_withdrawals() {
+        let committed_collateral = query_committed_collateral(deps.as_ref())?;
+
+        for pool in pools.iter_mut() {
+            if pool.info.equal(&committed_collateral.info) {
+                pool.amount = pool.amount.saturating_sub(committed_collateral.amount);
+            }
+        }
+    }
+
+    let mut refund_assets = get_share_in_assets(&pools, amount, total_share);
+
+    if let Some(output_asset) = output_asset {
+        if market_phase != MarketPhase::PostSettlement {
+            return Err(ContractError::SingleAssetWithdrawalUnavailable {});
+        }
+
+        refund_assets = get_share_in_single_asset(&pools, &refund_assets, &output_asset)?;
+    }
+
+    for min_asset in min_assets.unwrap_or_default() {
+        let refund_amount = refund_assets
+            .iter()
+            .find(|a| a.info.equal(&min_asset.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+
+        if refund_amount < min_asset.amount {
+            return Err(ContractError::MinimumAssetOut {
+                asset: min_asset.info.to_string(),
+                amount: refund_amount,
+                min_amount: min_asset.amount,
+            });
+        }
+    }
+
+    // Accumulate prices for the reserves before the withdrawal
+    update_cumulative_prices(deps.storage, &env, &mut config)?;
+
+    // Let the market record the minimum liquidity check before the payout lowers the reserves
+    let mut messages: Vec<CosmosMsg> = vec![wasm_execute(
+        config.factory_addr.to_string(),
+        &cw_zll_std_market::msg::ExecuteMsg::CheckMinimumLiquidity {},
+        vec![],
+    )?
+    .into()];
+
+    for refund_asset in refund_assets.iter().filter(|a| !a.amount.is_zero()) {
+        messages.push(
+            refund_asset
+                .clone()
+                .into_msg(&deps.querier, recipient.clone())?,
+        );
+    }
+
+    messages.push(
         wasm_execute(
             config.pair_info.liquidity_token.to_string(),
             &Cw20ExecuteMsg::Burn { amount },
             vec![],
         )?
         .into(),
-    ];
+    );
+    messages.push(create_sync_reserves_msg(&env)?);
 
     let attributes = vec![
         attr("action", "withdraw_liquidity"),
         attr("sender", sender.as_str()),
+        attr("recipient", recipient.as_str()),
         attr("withdrawn_share", &amount.to_string()),
         attr(
             "refund_assets",
@@ -353,6 +640,288 @@ pub fn withdraw_liquidity(
         .add_attributes(attributes))
 }
 
+/// ## Description
+/// Escrows LP tokens for the rollover into the successor market. LP tokens can be escrowed until
+/// the market is settled or cancelled. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that sent the LP tokens to the pair contract.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to escrow.
+pub fn opt_in_rollover(
+    deps: DepsMut,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if ROLLOVER.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::RolloverCompleted {});
+    }
+
+    if !query_market_phase(deps.as_ref())?.can_opt_in_rollover() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // LP tokens are transferable, so the holder escrowing them has to be a liquidity provider too
+    assert_allowlisted(deps.as_ref(), &sender)?;
+
+    let shares = ROLLOVER_SHARES.update(deps.storage, &sender, |shares| -> StdResult<_> {
+        Ok(shares.unwrap_or_default().checked_add(amount)?)
+    })?;
+    let total = ROLLOVER_SHARES_TOTAL
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    ROLLOVER_SHARES_TOTAL.save(deps.storage, &total.checked_add(amount)?)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "opt_in_rollover"),
+        attr("sender", sender.as_str()),
+        attr("amount", amount.to_string()),
+        attr("shares", shares.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns the LP tokens the sender escrowed for the rollover. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+pub fn opt_out_rollover(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if ROLLOVER.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::RolloverCompleted {});
+    }
+
+    let shares = ROLLOVER_SHARES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+
+    if shares.is_zero() {
+        return Err(ContractError::NoRolloverShares {});
+    }
+
+    ROLLOVER_SHARES.remove(deps.storage, &info.sender);
+    ROLLOVER_SHARES_TOTAL.update(deps.storage, |total| -> StdResult<_> {
+        Ok(total.checked_sub(shares)?)
+    })?;
+
+    Ok(Response::new()
+        .add_message(wasm_execute(
+            config.pair_info.liquidity_token,
+            &Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: shares,
+            },
+            vec![],
+        )?)
+        .add_attributes(vec![
+            attr("action", "opt_out_rollover"),
+            attr("sender", info.sender.as_str()),
+            attr("amount", shares.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Moves the liquidity of the escrowed LP tokens into the pool of the successor market. The escrowed
+/// LP tokens are burned and their share of the reserves is deposited into the successor pool, which
+/// mints its LP tokens to this pool. A share without one of the assets cannot be deposited, so it
+/// stays escrowed for its holders to opt out. Returns a [`ContractError`] on failure, otherwise returns a
+/// [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **liquidity_pool** is an object of type [`String`]. This is the liquidity pool of the successor market.
+pub fn rollover_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    liquidity_pool: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.factory_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if ROLLOVER.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::RolloverCompleted {});
+    }
+
+    let liquidity_pool = deps.api.addr_validate(&liquidity_pool)?;
+    let successor: PairInfo = deps
+        .querier
+        .query_wasm_smart(liquidity_pool.clone(), &QueryMsg::Pair {})?;
+
+    if successor.asset_infos != config.pair_info.asset_infos {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let shares = ROLLOVER_SHARES_TOTAL
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let assets = get_share_in_assets(&pools, shares, total_share);
+
+    if !shares.is_zero() && assets.iter().any(|asset| asset.amount.is_zero()) {
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "rollover_liquidity"),
+            attr("liquidity_pool", liquidity_pool.as_str()),
+            attr("escrowed_shares", shares.to_string()),
+        ]));
+    }
+
+    ROLLOVER.save(
+        deps.storage,
+        &Rollover {
+            liquidity_pool: liquidity_pool.clone(),
+            liquidity_token: successor.liquidity_token,
+            unclaimed_shares: shares,
+        },
+    )?;
+
+    let mut response = Response::new().add_attributes(vec![
+        attr("action", "rollover_liquidity"),
+        attr("liquidity_pool", liquidity_pool.as_str()),
+        attr("shares", shares.to_string()),
+    ]);
+
+    if shares.is_zero() {
+        return Ok(response);
+    }
+
+    // Accumulate prices for the reserves before the rollover
+    update_cumulative_prices(deps.storage, &env, &mut config)?;
+
+    response = response.add_message(wasm_execute(
+        config.pair_info.liquidity_token.to_string(),
+        &Cw20ExecuteMsg::Burn { amount: shares },
+        vec![],
+    )?);
+
+    let mut funds = vec![];
+
+    for asset in assets.iter() {
+        match &asset.info {
+            AssetInfo::Token { contract_addr } => {
+                response = response.add_message(wasm_execute(
+                    contract_addr,
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: liquidity_pool.to_string(),
+                        amount: asset.amount,
+                        expires: None,
+                    },
+                    vec![],
+                )?);
+            }
+            AssetInfo::NativeToken { denom } => funds.push(coin(asset.amount.u128(), denom)),
+        }
+    }
+
+    Ok(response
+        .add_message(wasm_execute(
+            liquidity_pool,
+            &ExecuteMsg::ProvideLiquidity {
+                assets: [assets[0].clone(), assets[1].clone()],
+                slippage_tolerance: None,
+                auto_stake: None,
+                receiver: None,
+                min_share_out: None,
+            },
+            funds,
+        )?)
+        .add_message(create_sync_reserves_msg(&env)?)
+        .add_attribute("assets", format!("{}, {}", assets[0], assets[1])))
+}
+
+/// ## Description
+/// Sends the sender their share of the LP tokens the successor pool minted for the rolled over
+/// liquidity. Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
+/// specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+pub fn claim_rollover_shares(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut rollover = ROLLOVER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::RolloverPending {})?;
+
+    let shares = ROLLOVER_SHARES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+
+    if shares.is_zero() {
+        return Err(ContractError::NoRolloverShares {});
+    }
+
+    let amount = get_claimable_rollover_shares(deps.as_ref(), &env, &rollover, shares)?;
+
+    ROLLOVER_SHARES.remove(deps.storage, &info.sender);
+    rollover.unclaimed_shares = rollover.unclaimed_shares.checked_sub(shares)?;
+    ROLLOVER.save(deps.storage, &rollover)?;
+
+    Ok(Response::new()
+        .add_message(wasm_execute(
+            rollover.liquidity_token,
+            &Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            },
+            vec![],
+        )?)
+        .add_attributes(vec![
+            attr("action", "claim_rollover_shares"),
+            attr("sender", info.sender.as_str()),
+            attr("shares", shares.to_string()),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Returns the amount of LP tokens of the successor pool that correspond to an amount of escrowed LP tokens.
+fn get_claimable_rollover_shares(
+    deps: Deps,
+    env: &Env,
+    rollover: &Rollover,
+    shares: Uint128,
+) -> StdResult<Uint128> {
+    if rollover.unclaimed_shares.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let balance = query_token_balance(
+        &deps.querier,
+        rollover.liquidity_token.clone(),
+        env.contract.address.clone(),
+    )?;
+
+    Ok(balance.multiply_ratio(shares, rollover.unclaimed_shares))
+}
+
 /// ## Description
 /// Returns the amount of pool assets that correspond to an amount of LP tokens.
 /// ## Params
@@ -380,6 +949,50 @@ pub fn get_share_in_assets(
         .collect()
 }
 
+/// ## Description
+/// Converts a refund of both pool assets into a refund of a single asset. The other asset is
+/// swapped into the output asset against the pool reserves left after the refund, using the
+/// constant product formula. Rounding is in favour of the pool.
+/// ## Params
+/// * **pools** are an array of [`Asset`] type items. These are the assets in the pool.
+///
+/// * **refund_assets** are a slice of [`Asset`] type items. These are the pro-rata amounts of the pool assets to refund.
+///
+/// * **output_asset** is an object of type [`AssetInfo`]. This is the asset to refund.
+pub fn get_share_in_single_asset(
+    pools: &[Asset; 2],
+    refund_assets: &[Asset],
+    output_asset: &AssetInfo,
+) -> Result<Vec<Asset>, ContractError> {
+    let (output_index, input_index) = if pools[0].info.equal(output_asset) {
+        (0, 1)
+    } else if pools[1].info.equal(output_asset) {
+        (1, 0)
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    let input_amount = refund_assets[input_index].amount;
+
+    if input_amount.is_zero() {
+        return Ok(refund_assets.to_vec());
+    }
+
+    // out = (pool_out - refund_out) * refund_in / ((pool_in - refund_in) + refund_in)
+    let swapped_amount = pools[output_index]
+        .amount
+        .checked_sub(refund_assets[output_index].amount)?
+        .multiply_ratio(input_amount, pools[input_index].amount);
+
+    let mut single_asset_refund = refund_assets.to_vec();
+    single_asset_refund[output_index].amount = single_asset_refund[output_index]
+        .amount
+        .checked_add(swapped_amount)?;
+    single_asset_refund[input_index].amount = Uint128::zero();
+
+    Ok(single_asset_refund)
+}
+
 fn mint_liquidity_token_message(
     config: &Config,
     recipient: Addr,
@@ -399,13 +1012,19 @@ fn mint_liquidity_token_message(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Pair {} => to_binary(&query_pair_info(deps)?),
         QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
-        _ => Err(StdError::NotFound {
-            kind: format!("Message {:?} cannot be handled", &msg),
-        }),
+        QueryMsg::LiquidityProgress {} => to_binary(&query_liquidity_progress(deps)?),
+        QueryMsg::NavPerShare {} => to_binary(&query_nav_per_share(deps)?),
+        QueryMsg::HolderShare { address } => to_binary(&query_holder_share(deps, address)?),
+        QueryMsg::PremiumEarned {} => to_binary(&query_premium_earned(deps)?),
+        QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
+        QueryMsg::Twap { window, end } => to_binary(&query_twap(deps, env, window, end)?),
+        QueryMsg::RolloverShares { address } => {
+            to_binary(&query_rollover_shares(deps, env, address)?)
+        }
     }
 }
 
@@ -435,17 +1054,536 @@ pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     Ok(resp)
 }
 
+/// ## Description
+/// Returns the amount of the lending asset collected by the pool together with the target
+/// liquidity configured on the market in an object of type [`LiquidityProgressResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_liquidity_progress(deps: Deps) -> StdResult<LiquidityProgressResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let TargetLiquidityResponse { target_liquidity } = deps.querier.query_wasm_smart(
+        config.factory_addr.clone(),
+        &cw_zll_std_market::msg::QueryMsg::GetTargetLiquidity {},
+    )?;
+
+    // The second asset of the pair is the lending asset of the market
+    let (pools, _) = pool_info(deps, config)?;
+    let liquidity = pools[1].clone();
+
+    let is_target_reached = target_liquidity
+        .as_ref()
+        .map(|target_liquidity| liquidity.amount >= target_liquidity.amount)
+        .unwrap_or(false);
+
+    Ok(LiquidityProgressResponse {
+        liquidity,
+        target_liquidity,
+        is_target_reached,
+    })
+}
+
+/// ## Description
+/// Returns the net asset value of the pool and of a single LP token, valued in the lending asset
+/// with the collateral price provided by the market or implied by the reserves, in an object of
+/// type [`NavPerShareResponse`]. The outstanding loans are part of the value.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_nav_per_share(deps: Deps) -> StdResult<NavPerShareResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let loans = query_outstanding_loans(deps, &config)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+    let collateral_price = query_collateral_price(deps, &config, &pools);
+
+    let mut nav = get_assets_value(&pools, collateral_price)?;
+    nav.amount = nav
+        .amount
+        .checked_add(get_loans_value(&loans, collateral_price))?;
+    let nav_per_share = if total_share.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(nav.amount, total_share)
+    };
+
+    Ok(NavPerShareResponse {
+        nav,
+        total_share,
+        nav_per_share,
+        collateral_price,
+    })
+}
+
+/// ## Description
+/// Returns the holder's share of each reserve and of the outstanding loans, their value in the
+/// lending asset and the holder's part of the premium earned by the pool in an object of type
+/// [`HolderShareResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **address** is an object of type [`String`]. This is the address of the LP token holder.
+pub fn query_holder_share(deps: Deps, address: String) -> StdResult<HolderShareResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let holder = deps.api.addr_validate(&address)?;
+
+    let share = query_token_balance(
+        &deps.querier,
+        config.pair_info.liquidity_token.clone(),
+        holder,
+    )?;
+    let PremiumEarnedResponse { premium, .. } = query_premium_earned(deps)?;
+    let loans = query_outstanding_loans(deps, &config)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+    let collateral_price = query_collateral_price(deps, &config, &pools);
+
+    let assets = get_share_in_assets(&pools, share, total_share);
+    let loans = get_share_in_assets(&loans, share, total_share);
+    let mut nav = get_assets_value(&assets, collateral_price)?;
+    nav.amount = nav
+        .amount
+        .checked_add(get_loans_value(&loans, collateral_price))?;
+    let premium_earned = Asset {
+        amount: if total_share.is_zero() {
+            Uint128::zero()
+        } else {
+            premium.amount.multiply_ratio(share, total_share)
+        },
+        info: premium.info,
+    };
+
+    Ok(HolderShareResponse {
+        share,
+        assets,
+        loans,
+        nav,
+        premium_earned,
+    })
+}
+
+/// ## Description
+/// Returns the premium earned by the pool since the market started in an object of type [`PremiumEarnedResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_premium_earned(deps: Deps) -> StdResult<PremiumEarnedResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let cw_zll_std_market::response::PremiumEarnedResponse { premium } =
+        deps.querier.query_wasm_smart(
+            config.factory_addr.clone(),
+            &cw_zll_std_market::msg::QueryMsg::GetPremiumEarned {},
+        )?;
+
+    let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
+    let premium_per_share = if total_share.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(premium.amount, total_share)
+    };
+
+    Ok(PremiumEarnedResponse {
+        premium,
+        premium_per_share,
+    })
+}
+
+/// ## Description
+/// Returns the price of one unit of the collateral asset in units of the lending asset, as provided by the market.
+/// Falls back to the price implied by the reserves when the market cannot provide the price, so the pool
+/// can be valued in every market.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **pools** are an array of [`Asset`] type items. These are the collateral and the lending asset reserves.
+fn query_collateral_price(deps: Deps, config: &Config, pools: &[Asset; 2]) -> Decimal {
+    deps.querier
+        .query_wasm_smart(
+            config.factory_addr.clone(),
+            &cw_zll_std_market::msg::QueryMsg::GetCollateralPrice {},
+        )
+        .map(|CollateralPriceResponse { price, .. }| price)
+        .unwrap_or_else(|_: StdError| {
+            if pools[0].amount.is_zero() {
+                Decimal::zero()
+            } else {
+                Decimal::from_ratio(pools[1].amount, pools[0].amount)
+            }
+        })
+}
+
+/// ## Description
+/// Returns the value of the collateral asset (first) and the lending asset (second) in units of the lending asset.
+/// ## Params
+/// * **assets** are a slice of [`Asset`] type items. These are the collateral and the lending asset amounts.
+///
+/// * **collateral_price** is an object of type [`Decimal`]. This is the price of one unit of the collateral asset.
+fn get_assets_value(assets: &[Asset], collateral_price: Decimal) -> StdResult<Asset> {
+    Ok(Asset {
+        info: assets[1].info.clone(),
+        amount: (assets[0].amount * collateral_price).checked_add(assets[1].amount)?,
+    })
+}
+
+/// ## Description
+/// Returns the collateral asset pledged for (first) and the lending asset owed by (second) the
+/// outstanding loans of the market.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+fn query_outstanding_loans(deps: Deps, config: &Config) -> StdResult<[Asset; 2]> {
+    let OutstandingLoansResponse {
+        collateral,
+        notional,
+    } = deps.querier.query_wasm_smart(
+        config.factory_addr.clone(),
+        &cw_zll_std_market::msg::QueryMsg::GetOutstandingLoans {},
+    )?;
+
+    Ok([collateral, notional])
+}
+
+/// ## Description
+/// Returns the value of outstanding loans in units of the lending asset. Borrowers only repay the
+/// notional to reclaim collateral worth more, so the loans are worth the lesser of the two.
+/// ## Params
+/// * **loans** are a slice of [`Asset`] type items. These are the pledged collateral and the notional owed.
+///
+/// * **collateral_price** is an object of type [`Decimal`]. This is the price of one unit of the collateral asset.
+fn get_loans_value(loans: &[Asset], collateral_price: Decimal) -> Uint128 {
+    (loans[0].amount * collateral_price).min(loans[1].amount)
+}
+
+/// ## Description
+/// Returns the cumulative prices of the pool assets accumulated up to the current block
+/// in an object of type [`CumulativePricesResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config.clone())?;
+
+    let (price0_cumulative_last, price1_cumulative_last, _) = accumulate_prices(&env, &config)?
+        .unwrap_or((
+            config.price0_cumulative_last,
+            config.price1_cumulative_last,
+            config.block_time_last,
+        ));
+
+    Ok(CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last,
+        price1_cumulative_last,
+    })
+}
+
+/// ## Description
+/// Returns the time-weighted average prices of the pool assets in an object of type [`TwapResponse`].
+/// The prices are averaged from the latest observation made at least `window` seconds before `end`
+/// until `end`, so the reserves recorded after `end` do not affect them.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **window** is an object of type [`u64`]. This is the minimum number of seconds to average the prices over.
+///
+/// * **end** is an [`Option`] of type [`u64`]. This is the time to average the prices until, the current block time by default.
+pub fn query_twap(deps: Deps, env: Env, window: u64, end: Option<u64>) -> StdResult<TwapResponse> {
+    let current_time = env.block.time.seconds();
+    let end = end.unwrap_or(current_time);
+
+    if end > current_time {
+        return Err(StdError::generic_err(format!(
+            "TWAP end ({}) is in the future",
+            end
+        )));
+    }
+
+    if window == 0 || window > end {
+        return Err(StdError::generic_err(format!(
+            "TWAP window ({}s) is invalid",
+            window
+        )));
+    }
+
+    let observation = find_observation_before(deps, end - window)?.ok_or_else(|| {
+        StdError::generic_err(format!(
+            "Not enough price history for a TWAP over {}s",
+            window
+        ))
+    })?;
+
+    let (price0_cumulative_end, price1_cumulative_end) =
+        query_cumulative_prices_at(deps, env, end)?;
+
+    let window = end - observation.timestamp;
+    let denominator = Uint128::from(window) * Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+
+    Ok(TwapResponse {
+        price0_average: Decimal::from_ratio(
+            price0_cumulative_end.wrapping_sub(observation.price0_cumulative),
+            denominator,
+        ),
+        price1_average: Decimal::from_ratio(
+            price1_cumulative_end.wrapping_sub(observation.price1_cumulative),
+            denominator,
+        ),
+        window,
+    })
+}
+
+/// ## Description
+/// Returns the latest observation made at or before the given time.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **time** is an object of type [`u64`].
+fn find_observation_before(deps: Deps, time: u64) -> StdResult<Option<Observation>> {
+    OBSERVATIONS
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive_int(time)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()
+        .map(|observation| observation.map(|(_, observation)| observation))
+}
+
+/// ## Description
+/// Returns the cumulative prices of the pool assets at the given time, which must not be before the
+/// first observation. The reserves only change when an observation is made, so the cumulative
+/// prices grow linearly between two observations.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **time** is an object of type [`u64`]. This must not be after the current block time.
+fn query_cumulative_prices_at(deps: Deps, env: Env, time: u64) -> StdResult<(Uint128, Uint128)> {
+    let previous = find_observation_before(deps, time)?
+        .ok_or_else(|| StdError::generic_err(format!("No price history at {}", time)))?;
+
+    if previous.timestamp == time {
+        return Ok((previous.price0_cumulative, previous.price1_cumulative));
+    }
+
+    let next = match OBSERVATIONS
+        .range(
+            deps.storage,
+            Some(Bound::exclusive_int(time)),
+            None,
+            Order::Ascending,
+        )
+        .next()
+        .transpose()?
+    {
+        Some((_, observation)) => observation,
+        None => {
+            let current_time = env.block.time.seconds();
+            let CumulativePricesResponse {
+                price0_cumulative_last,
+                price1_cumulative_last,
+                ..
+            } = query_cumulative_prices(deps, env)?;
+
+            Observation {
+                timestamp: current_time,
+                price0_cumulative: price0_cumulative_last,
+                price1_cumulative: price1_cumulative_last,
+            }
+        }
+    };
+
+    let elapsed = time - previous.timestamp;
+    let interval = next.timestamp - previous.timestamp;
+
+    Ok((
+        previous.price0_cumulative.wrapping_add(
+            next.price0_cumulative
+                .wrapping_sub(previous.price0_cumulative)
+                .multiply_ratio(elapsed, interval),
+        ),
+        previous.price1_cumulative.wrapping_add(
+            next.price1_cumulative
+                .wrapping_sub(previous.price1_cumulative)
+                .multiply_ratio(elapsed, interval),
+        ),
+    ))
+}
+
+/// ## Description
+/// Accumulates the prices for the reserves recorded at the last update, stores them in the config
+/// and records them as an observation at the current block time.
+/// This has to be called before any change of the pool reserves, which is then recorded by
+/// [`sync_reserves`].
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`]. This is the config to update.
+fn update_cumulative_prices(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &mut Config,
+) -> StdResult<()> {
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, config)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(storage, config)?;
+    }
+
+    OBSERVATIONS.save(
+        storage,
+        U64Key::new(config.block_time_last),
+        &Observation {
+            timestamp: config.block_time_last,
+            price0_cumulative: config.price0_cumulative_last,
+            price1_cumulative: config.price1_cumulative_last,
+        },
+    )
+}
+
+/// ## Description
+/// Accumulates the prices for the reserves recorded at the last update and records the current
+/// reserves, which the prices are accumulated for from now on. Anyone can record the reserves,
+/// as the time passed since the last update is always weighted by the reserves recorded then.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn sync_reserves(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    update_cumulative_prices(deps.storage, &env, &mut config)?;
+
+    let (pools, _) = pool_info(deps.as_ref(), config.clone())?;
+    config.reserves_last = [pools[0].amount, pools[1].amount];
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "sync_reserves"),
+        attr("reserves", format!("{}, {}", pools[0], pools[1])),
+    ]))
+}
+
+/// ## Description
+/// Returns the message recording the reserves of the pool once the preceding messages changed them.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+fn create_sync_reserves_msg(env: &Env) -> StdResult<CosmosMsg> {
+    Ok(wasm_execute(
+        env.contract.address.to_string(),
+        &ExecuteMsg::SyncReserves {},
+        vec![],
+    )?
+    .into())
+}
+
+/// ## Description
+/// Returns the cumulative prices accumulated for the reserves recorded at the last update
+/// together with the current block time, or [`None`] if no time has passed since the last update.
+/// The accumulators wrap around on overflow, so only their differences are meaningful.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn accumulate_prices(env: &Env, config: &Config) -> StdResult<Option<(Uint128, Uint128, u64)>> {
+    let [x, y] = config.reserves_last;
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+
+    let mut price0_cumulative_new = config.price0_cumulative_last;
+    let mut price1_cumulative_new = config.price1_cumulative_last;
+
+    // The prices are undefined while any reserve is empty, so nothing is accumulated then
+    if !x.is_zero() && !y.is_zero() {
+        let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+        price0_cumulative_new = price0_cumulative_new.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(y, x),
+        );
+        price1_cumulative_new = price1_cumulative_new.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(x, y),
+        );
+    }
+
+    Ok(Some((
+        price0_cumulative_new,
+        price1_cumulative_new,
+        block_time,
+    )))
+}
+
+/// ## Description
+/// Returns the LP tokens the holder escrowed for the rollover in an object of type [`RolloverSharesResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **address** is an object of type [`String`]. This is the address of the LP token holder.
+pub fn query_rollover_shares(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<RolloverSharesResponse> {
+    let holder = deps.api.addr_validate(&address)?;
+    let shares = ROLLOVER_SHARES
+        .may_load(deps.storage, &holder)?
+        .unwrap_or_default();
+
+    Ok(match ROLLOVER.may_load(deps.storage)? {
+        Some(rollover) => RolloverSharesResponse {
+            shares,
+            claimable: get_claimable_rollover_shares(deps, &env, &rollover, shares)?,
+            liquidity_pool: Some(rollover.liquidity_pool),
+        },
+        None => RolloverSharesResponse {
+            shares,
+            liquidity_pool: None,
+            claimable: Uint128::zero(),
+        },
+    })
+}
+
 /// ## Description
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
+/// The assets held on behalf of option holders of the market are not part of the pool.
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **config** is an object of type [`Config`].
 pub fn pool_info(deps: Deps, config: Config) -> StdResult<([Asset; 2], Uint128)> {
     let contract_addr = config.pair_info.contract_addr.clone();
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let mut pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
     let total_share: Uint128 = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
 
+    let LockedAssetsResponse { locked_assets } = deps.querier.query_wasm_smart(
+        config.factory_addr,
+        &cw_zll_std_market::msg::QueryMsg::GetLockedAssets {},
+    )?;
+
+    for (pool, locked_asset) in pools.iter_mut().zip(locked_assets.iter()) {
+        pool.amount = pool.amount.checked_sub(locked_asset.amount)?;
+    }
+
     Ok((pools, total_share))
 }
 
@@ -484,3 +1622,16 @@ fn reply_on_instantiate_token(
 
     Ok(Response::new().add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = assert_migration(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "migrate".to_string()),
+        ("previous_version", previous_version.to_string()),
+        ("version", CONTRACT_VERSION.to_string()),
+    ]))
+}
@@ -1,6 +1,7 @@
-use cosmwasm_std::{OverflowError, StdError};
+use cosmwasm_std::{OverflowError, StdError, Uint128};
 use cw_zll_std_liquidity_pool::liquidity::MarketLiquidityError;
-use cw_zll_std_utils::reply::ParseReplyError;
+use cw_zll_std_market::state::{AllowlistRole, PausableAction};
+use cw_zll_std_utils::{migration::MigrationError, reply::ParseReplyError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,6 +15,9 @@ pub enum ContractError {
     #[error("{0}")]
     ParseReply(#[from] ParseReplyError),
 
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
+
     #[error("{0}")]
     OverflowError(#[from] OverflowError),
 
@@ -23,6 +27,15 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Action `{action}` is paused by the guardian of the market")]
+    ActionPaused { action: PausableAction },
+
+    #[error("Account {address} is not allowlisted as a {role} by the market")]
+    NotAllowlisted {
+        role: AllowlistRole,
+        address: String,
+    },
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
@@ -31,4 +44,39 @@ pub enum ContractError {
 
     #[error("Event of zero transfer")]
     InvalidZeroAmount {},
+
+    #[error("Initial liquidity must mint more than {min_liquidity_amount} LP tokens")]
+    MinimumLiquidityAmount { min_liquidity_amount: Uint128 },
+
+    #[error(
+        "Withdrawing liquidity in a single asset is only possible in the post-settlement phase"
+    )]
+    SingleAssetWithdrawalUnavailable {},
+
+    #[error(
+        "Withdrawn amount of {asset} ({amount}) is below the requested minimum ({min_amount})"
+    )]
+    MinimumAssetOut {
+        asset: String,
+        amount: Uint128,
+        min_amount: Uint128,
+    },
+
+    #[error("Provided liquidity is too small to mint any LP tokens")]
+    ZeroShare {},
+
+    #[error("Minted LP tokens ({share}) are below the requested minimum ({min_share_out})")]
+    MinimumShareOut {
+        share: Uint128,
+        min_share_out: Uint128,
+    },
+
+    #[error("The escrowed liquidity has already been rolled over")]
+    RolloverCompleted {},
+
+    #[error("The escrowed liquidity has not been rolled over yet")]
+    RolloverPending {},
+
+    #[error("No LP tokens are escrowed for the rollover")]
+    NoRolloverShares {},
 }
@@ -1,5 +1,5 @@
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map, U64Key};
 use cw_zll_std_liquidity_pool::ap::asset::PairInfo;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -12,8 +12,57 @@ pub struct Config {
     pub pair_info: PairInfo,
     /// Market contract address
     pub factory_addr: Addr,
+    /// The last time the cumulative prices were updated, in seconds
+    pub block_time_last: u64,
+    /// The last cumulative price of the first asset in units of the second asset
+    pub price0_cumulative_last: Uint128,
+    /// The last cumulative price of the second asset in units of the first asset
+    pub price1_cumulative_last: Uint128,
+    /// The reserves the prices are accumulated for until the reserves are recorded again
+    #[serde(default)]
+    pub reserves_last: [Uint128; 2],
 }
 
 /// ## Description
 /// Stores the config struct at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// ## Description
+/// This structure stores the cumulative prices of the pool at the time of a reserve change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Observation {
+    /// The time of the observation, in seconds
+    pub timestamp: u64,
+    /// The cumulative price of the first asset in units of the second asset
+    pub price0_cumulative: Uint128,
+    /// The cumulative price of the second asset in units of the first asset
+    pub price1_cumulative: Uint128,
+}
+
+/// ## Description
+/// Stores the cumulative prices observed on reserve changes, keyed by their timestamps
+pub const OBSERVATIONS: Map<U64Key, Observation> = Map::new("observations");
+
+/// ## Description
+/// Stores the LP tokens escrowed by their holders for the rollover into the successor market
+pub const ROLLOVER_SHARES: Map<&Addr, Uint128> = Map::new("rollover_shares");
+
+/// ## Description
+/// Stores the total amount of LP tokens escrowed for the rollover
+pub const ROLLOVER_SHARES_TOTAL: Item<Uint128> = Item::new("rollover_shares_total");
+
+/// ## Description
+/// This structure stores the rollover of the escrowed liquidity into the pool of the successor market.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Rollover {
+    /// The liquidity pool of the successor market
+    pub liquidity_pool: Addr,
+    /// The LP token of the successor pool
+    pub liquidity_token: Addr,
+    /// The escrowed LP tokens whose holders have not claimed their LP tokens of the successor pool yet
+    pub unclaimed_shares: Uint128,
+}
+
+/// ## Description
+/// Stores the rollover struct once the escrowed liquidity is rolled over
+pub const ROLLOVER: Item<Rollover> = Item::new("rollover");
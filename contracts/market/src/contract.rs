@@ -1,25 +1,63 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, wasm_instantiate, Addr, Binary, Deps, DepsMut, Env, Isqrt, MessageInfo, Reply,
-    Response, StdError, Storage, SubMsg, Uint128,
+    coins, to_binary, wasm_execute, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    Fraction, MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw_zll_std_liquidity_pool::{ap::Asset, asset::create_coin_asset};
+use cw20::Cw20ExecuteMsg;
+use cw_zll_option_token::{
+    msg::{ExecuteMsg as OptionTokenExecuteMsg, InstantiateMsg as OptionTokenInstantiateMsg},
+    state::{Metadata as OptionTokenMetadata, OptionKind},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::{Asset, AssetInfo, PairInfo},
+    response::{LiquidityProgressResponse, TwapResponse},
+};
 use cw_zll_std_market::{
     borrow::{BorrowingTerms, BorrowingTermsResponse},
     expiry_time::ExpiryTime,
-    msg::{ExecuteMsg, InstantiateMsg, QueryMsg},
+    lend::{LendingTerms, LendingTermsResponse},
+    msg::{AllowlistQueryMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, StrikeInfo},
     response::{
-        LiquidityPoolResponse, MarketOperatorResponse, MarketPhase, MarketPhaseResponse,
-        MarketPhasesInfoResponse,
+        AllowlistResponse, AllowlistedAccount, AllowlistedAccountsResponse, CapacityLimitsResponse,
+        CollateralPriceResponse, CommittedCollateralResponse, EarlyCloseQuoteResponse,
+        ExitQuoteResponse, IsAllowedResponse, LiquidityPoolResponse, LoanResponse,
+        LockedAssetsResponse, MarketOperatorResponse, MarketPhase, MarketPhaseResponse,
+        MarketPhasesInfoResponse, MinimumLiquidityResponse, OptionTokenResponse,
+        OutstandingLoansResponse, PausedActionsResponse, PendingUpdateResponse, PositionResponse,
+        PredecessorResponse, PremiumEarnedResponse, PriceGuardsResponse, PriceSource,
+        ProtocolFeesResponse, SettlementPriceResponse, SettlementProgressResponse, StrikesResponse,
+        TargetLiquidityResponse,
     },
     state::{
-        get_alpha, get_blocks_per_year, get_liquidity_pool, get_market_info, get_market_operator,
-        set_config, set_liquidity_pool, Config, MarketPhasesInfo,
+        apply_market_update, cancel_market, get_account_exposure, get_allowlist,
+        get_allowlisted_accounts, get_allowlisted_roles, get_alpha, get_asset_infos,
+        get_blocks_per_year, get_buyback_spread, get_capacity_limits, get_collateral_price,
+        get_creator, get_guardian, get_lending_asset_info, get_liquidity_pool, get_loan,
+        get_market_info, get_market_operator, get_market_state, get_max_buyback_volume,
+        get_max_price_age, get_max_price_deviation, get_minimum_liquidity, get_oracle,
+        get_paused_actions, get_position, get_positions, get_predecessor, get_protocol_fee_rate,
+        get_settlement_price, get_settlement_progress, get_strikes, get_target_liquidity,
+        get_timelock_delay, get_treasury, get_twap_window, may_get_option_token,
+        may_get_pending_loan_rollover, may_get_pending_update, may_get_position, may_get_strike,
+        migrate_config, migrate_market_state, next_loan_id, next_position_id,
+        record_amm_observation, record_borrowing, record_liquidity_check,
+        remove_pending_loan_rollover, remove_pending_update, set_account_exposure, set_allowlist,
+        set_allowlisted_roles, set_capacity_limits, set_config, set_liquidity_pool, set_loan,
+        set_market_info, set_market_state, set_option_token, set_paused_actions,
+        set_pending_loan_rollover, set_pending_update, set_position, set_settlement_price,
+        set_settlement_progress, set_strike, set_strikes, Allowlist, AllowlistRole, AllowlistRoles,
+        CapacityLimits, Config, Loan, LoanStatus, MarketPhasesInfo, MarketState, MarketUpdate,
+        ObservedPrice, PausableAction, PendingLoanRollover, PendingUpdate, Position,
+        PositionStatus, SettlementPrice, SettlementProgress, Strike,
     },
 };
-use cw_zll_std_utils::reply::{parse_reply_instantiate_data, MsgInstantiateContractResponse};
+use cw_zll_std_oracle::{msg::PriceResponse, querier::query_price};
+use cw_zll_std_utils::{
+    migration::assert_migration,
+    reply::{parse_reply_instantiate_data, MsgInstantiateContractResponse},
+};
 
 use crate::error::ContractError;
 
@@ -31,38 +69,101 @@ const ADDR_WHILE_INSTANTIATION: &str = "";
 
 /// A `reply` call code ID used for sub-messages.
 const INSTANTIATE_LIQUIDITY_POOL_REPLY_ID: u64 = 1;
+const INSTANTIATE_OPTION_TOKEN_REPLY_ID: u64 = 2;
+const BORROW_FOR_ROLLOVER_REPLY_ID: u64 = 3;
+
+/// The minimum number of blocks a queued update waits, so the liquidity providers always have
+/// time to withdraw before it is applied
+const MIN_TIMELOCK_DELAY: u64 = 100;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    let oracle = msg
+        .oracle
+        .map(|oracle| deps.api.addr_validate(&oracle))
+        .transpose()?;
+    let treasury = msg
+        .treasury
+        .map(|treasury| deps.api.addr_validate(&treasury))
+        .transpose()?;
+    let protocol_fee_rate = validate_protocol_fee_rate(msg.protocol_fee_rate, &treasury)?;
+    let guardian = msg
+        .guardian
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
+    let predecessor = msg
+        .predecessor
+        .map(|predecessor| deps.api.addr_validate(&predecessor))
+        .transpose()?;
+
     set_config(
         deps.storage,
         Config {
             market_operator: msg.market_operator.clone(),
+            creator: info.sender,
             liquidity_pool: Addr::unchecked(ADDR_WHILE_INSTANTIATION),
             market_phases_info: validate_market_phases_info(msg.market_phases_info)?,
             blocks_per_year: msg.blocks_per_year,
             alpha: msg.alpha,
+            asset_infos: msg.asset_infos.clone(),
+            target_liquidity: msg.target_liquidity,
+            minimum_liquidity: validate_minimum_liquidity(
+                msg.minimum_liquidity,
+                msg.target_liquidity,
+            )?,
+            collateral_price: msg.collateral_price,
+            oracle,
+            twap_window: msg.twap_window,
+            max_price_age: msg.max_price_age,
+            max_price_deviation: msg.max_price_deviation,
+            buyback_spread: validate_buyback_spread(msg.buyback_spread)?,
+            max_buyback_volume: msg.max_buyback_volume,
+            protocol_fee_rate,
+            treasury,
+            guardian,
+            timelock_delay: validate_timelock_delay(msg.timelock_delay)?,
+            predecessor,
         },
     )?;
 
-    Ok(Response::new()
-        .add_submessage(create_liquidity_pool_contract_instantiate_msg(
-            env.contract.address,
+    // the pool is created after the market, so there is no pool price to observe yet
+    let amm_oracle_price = observe_oracle_price(deps.as_ref(), None)?;
+
+    set_market_state(
+        deps.storage,
+        MarketState {
+            amm_observed_at: env.block.time.seconds(),
+            amm_oracle_price,
+            ..MarketState::default()
+        },
+    )?;
+
+    let mut response =
+        Response::new().add_submessage(create_liquidity_pool_contract_instantiate_msg(
+            env.contract.address.clone(),
             msg.liquidity_pool_code_id,
             msg.liquidity_pool_token_code_id,
             msg.asset_infos,
-        )?)
-        .add_attributes(vec![
-            ("method", "instantiate"),
-            ("market_operator", msg.market_operator.to_string().as_ref()),
-        ]))
+        )?);
+
+    if let Some(option_token_code_id) = msg.option_token_code_id {
+        response = response.add_submessage(create_option_token_contract_instantiate_msg(
+            env.contract.address,
+            option_token_code_id,
+        )?);
+    }
+
+    Ok(response.add_attributes(vec![
+        ("method", "instantiate"),
+        ("market_operator", msg.market_operator.to_string().as_ref()),
+    ]))
 }
 
 fn validate_market_phases_info(
@@ -101,240 +202,3637 @@ fn validate_market_phases_info(
     Ok(market_phases_info)
 }
 
+fn validate_minimum_liquidity(
+    minimum_liquidity: Option<Uint128>,
+    target_liquidity: Option<Uint128>,
+) -> Result<Option<Uint128>, ContractError> {
+    if let (Some(minimum_liquidity), Some(target_liquidity)) = (minimum_liquidity, target_liquidity)
+    {
+        if minimum_liquidity > target_liquidity {
+            return Err(StdError::GenericErr {
+                msg: format!(
+                    "`minimum_liquidity` = {} must not exceed `target_liquidity` = {}",
+                    minimum_liquidity, target_liquidity
+                ),
+            }
+            .into());
+        }
+    }
+
+    Ok(minimum_liquidity)
+}
+
+fn validate_buyback_spread(buyback_spread: Option<Decimal>) -> Result<Decimal, ContractError> {
+    let buyback_spread = buyback_spread.unwrap_or_else(Decimal::zero);
+
+    if buyback_spread >= Decimal::one() {
+        return Err(StdError::GenericErr {
+            msg: format!("`buyback_spread` = {} must be lower than 1", buyback_spread),
+        }
+        .into());
+    }
+
+    Ok(buyback_spread)
+}
+
+fn validate_timelock_delay(timelock_delay: Option<u64>) -> Result<u64, ContractError> {
+    let timelock_delay = timelock_delay.unwrap_or(MIN_TIMELOCK_DELAY);
+
+    if timelock_delay < MIN_TIMELOCK_DELAY {
+        return Err(StdError::GenericErr {
+            msg: format!(
+                "`timelock_delay` = {} must not be lower than {}",
+                timelock_delay, MIN_TIMELOCK_DELAY
+            ),
+        }
+        .into());
+    }
+
+    Ok(timelock_delay)
+}
+
+fn validate_protocol_fee_rate(
+    protocol_fee_rate: Option<Decimal>,
+    treasury: &Option<Addr>,
+) -> Result<Decimal, ContractError> {
+    let protocol_fee_rate = protocol_fee_rate.unwrap_or_else(Decimal::zero);
+
+    if protocol_fee_rate >= Decimal::one() {
+        return Err(StdError::GenericErr {
+            msg: format!(
+                "`protocol_fee_rate` = {} must be lower than 1",
+                protocol_fee_rate
+            ),
+        }
+        .into());
+    }
+
+    if !protocol_fee_rate.is_zero() && treasury.is_none() {
+        return Err(StdError::GenericErr {
+            msg: "`treasury` must be set when `protocol_fee_rate` is not zero".to_string(),
+        }
+        .into());
+    }
+
+    Ok(protocol_fee_rate)
+}
+
 fn create_liquidity_pool_contract_instantiate_msg(
     market_contract_addr: Addr,
     liquidity_pool_code_id: u64,
     liquidity_pool_token_code_id: u64,
     asset_infos: [cw_zll_std_liquidity_pool::ap::AssetInfo; 2],
 ) -> Result<SubMsg, ContractError> {
+    // the market administers the pool, so it can migrate it
     Ok(SubMsg::reply_on_success(
-        wasm_instantiate(
-            liquidity_pool_code_id,
-            &cw_zll_std_liquidity_pool::msg::InstantiateMsg {
+        WasmMsg::Instantiate {
+            admin: Some(market_contract_addr.to_string()),
+            code_id: liquidity_pool_code_id,
+            msg: to_binary(&cw_zll_std_liquidity_pool::msg::InstantiateMsg {
                 asset_infos,
                 token_code_id: liquidity_pool_token_code_id,
                 factory_addr: market_contract_addr.to_string(),
                 init_params: None,
-            },
-            vec![],
-            String::from("ZLL LP"),
-        )?,
+            })?,
+            funds: vec![],
+            label: String::from("ZLL LP"),
+        },
         INSTANTIATE_LIQUIDITY_POOL_REPLY_ID,
     ))
 }
 
+fn create_option_token_contract_instantiate_msg(
+    market_contract_addr: Addr,
+    option_token_code_id: u64,
+) -> Result<SubMsg, ContractError> {
+    // the market mints, updates and burns the option tokens of its loans
+    Ok(SubMsg::reply_on_success(
+        WasmMsg::Instantiate {
+            admin: Some(market_contract_addr.to_string()),
+            code_id: option_token_code_id,
+            msg: to_binary(&OptionTokenInstantiateMsg {
+                minter: market_contract_addr.to_string(),
+            })?,
+            funds: vec![],
+            label: String::from("ZLL Option Token"),
+        },
+        INSTANTIATE_OPTION_TOKEN_REPLY_ID,
+    ))
+}
+
+fn get_option_token_id(loan_id: u64) -> String {
+    format!("call-{}", loan_id)
+}
+
+fn get_option_token_metadata(loan: &Loan) -> OptionTokenMetadata {
+    OptionTokenMetadata {
+        kind: OptionKind::Call,
+        strike: loan.strike,
+        notional: loan.notional,
+        collateral_amount: loan.collateral_amount,
+    }
+}
+
+/// Creates the message passing `msg` on to the option token of the market, if the market issues
+/// option tokens
+fn create_option_token_msg(
+    storage: &dyn Storage,
+    msg: &OptionTokenExecuteMsg,
+) -> StdResult<Option<CosmosMsg>> {
+    may_get_option_token(storage)?
+        .map(|option_token| Ok(wasm_execute(option_token, msg, vec![])?.into()))
+        .transpose()
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    let market_phase = get_current_market_phase(deps.storage, env.block.height)?;
+    let market_phase = record_market_phase(deps.branch(), &env)?;
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
 
-    match msg {
+    let response = match msg {
         ExecuteMsg::Borrow {
             expected_borrow,
             pledged_collateral,
+            strike_id,
         } => {
             if !market_phase.can_amm_accept_borrowing() {
                 return Err(ContractError::Unauthorized {});
             }
 
-            execute_borrow(deps, env, info.sender, expected_borrow, pledged_collateral)
+            assert_not_paused(deps.storage, PausableAction::Borrow)?;
+            assert_allowlisted(deps.as_ref(), AllowlistRole::Borrower, &info.sender)?;
+
+            execute_borrow(
+                deps,
+                env,
+                info,
+                expected_borrow,
+                pledged_collateral,
+                strike_id,
+            )
         }
-    }
-}
+        ExecuteMsg::Lend {
+            expected_premium,
+            lent_asset,
+        } => {
+            if !market_phase.can_amm_accept_lending() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-fn execute_borrow(
-    deps: DepsMut,
-    env: Env,
-    _borrower: Addr,
-    expected_borrow: Asset,
-    pledged_collateral: Asset,
-) -> Result<Response, ContractError> {
-    // TODO: assert if expected_borrow, and pledged_collateral are of the right asset of the LP
-    // TODO: validate & accept funds (similar to deposits in the LP flow)
-    let BorrowingTerms { borrow, .. } =
-        get_borrowing_terms(deps.as_ref(), pledged_collateral, env.block.height)?;
+            assert_not_paused(deps.storage, PausableAction::Lend)?;
+            assert_allowlisted(deps.as_ref(), AllowlistRole::Lender, &info.sender)?;
 
-    if expected_borrow.amount > borrow.amount {
-        return Err(ContractError::Std(StdError::generic_err(format!(
-            "Expected amount to borrow ({}) is higher than calculated collateral amount ({})",
-            &expected_borrow.amount, &borrow.amount
-        ))));
-    }
+            execute_lend(deps, env, info, expected_premium, lent_asset)
+        }
+        ExecuteMsg::SettlePuts { limit } => {
+            if !market_phase.can_settle_options() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    Ok(Response::default())
-}
+            execute_settle_puts(deps, env, limit)
+        }
+        ExecuteMsg::ClaimCollateral { position_id } => {
+            if !market_phase.can_claim_collateral() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-fn get_borrowing_terms(
-    deps: Deps,
-    pledged_collateral: Asset,
-    current_block_height: u64,
-) -> Result<BorrowingTerms, ContractError> {
-    let borrowable_amount = get_borrowable_amount(pledged_collateral.clone())?;
-    let interest_amount = get_interest_cost(deps, pledged_collateral, current_block_height)?;
-    // TODO: return correct asset types (denom/contract address)
-    Ok(BorrowingTerms {
-        borrow: create_coin_asset(borrowable_amount.u128(), "uluna"),
-        interest: create_coin_asset(interest_amount.u128(), "uusd"),
-        repayment: create_coin_asset(0, "uusd"),
-    })
-}
+            assert_not_paused(deps.storage, PausableAction::Withdraw)?;
 
-fn get_borrowable_amount(pledged_collateral: Asset) -> Result<Uint128, ContractError> {
-    // TODO: query these 3 values below from the LP
-    let borrow_ccy_supply = Uint128::new(0);
-    let amm_constant = Uint128::new(0);
-    let collateral_ccy_supply = Uint128::new(0);
+            execute_claim_collateral(deps, env, info.sender, position_id)
+        }
+        ExecuteMsg::BatchSettlePuts { position_ids } => {
+            if !market_phase.can_settle_options() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    calculate_borrowable_amount(
-        borrow_ccy_supply,
-        amm_constant,
-        collateral_ccy_supply,
-        pledged_collateral.amount,
-    )
-}
+            execute_batch_settle_puts(deps, env, info.sender, position_ids)
+        }
+        ExecuteMsg::BatchClaimCollateral { position_ids } => {
+            if !market_phase.can_claim_collateral() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-fn calculate_borrowable_amount(
-    borrow_ccy_supply: Uint128,
-    amm_constant: Uint128,
-    collateral_ccy_supply: Uint128,
-    collateral_amount: Uint128,
-) -> Result<Uint128, ContractError> {
-    Ok(borrow_ccy_supply.checked_sub(
-        amm_constant.checked_div(collateral_ccy_supply.checked_add(collateral_amount)?)?,
-    )?)
-}
+            assert_not_paused(deps.storage, PausableAction::Withdraw)?;
 
-fn get_interest_cost(
-    deps: Deps,
-    pledged_collateral: Asset,
-    current_block_height: u64,
-) -> Result<Uint128, ContractError> {
-    let ExpiryTime {
-        sqrt_time_to_expiry,
-        ..
-    } = get_expiry_time(deps.storage, current_block_height)?;
+            execute_batch_claim_collateral(deps, env, info.sender, position_ids)
+        }
+        ExecuteMsg::RepayLoan { loan_id, repayment } => {
+            if !market_phase.can_repay_loans() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    let oblivious_put_price = get_oblivious_put_price(deps, sqrt_time_to_expiry)?;
-    // TODO: read this info form PairInfo via deps query
-    let collateral_ccy_decimals = 6;
+            assert_not_paused(deps.storage, PausableAction::Exercise)?;
 
-    calculate_interest_cost(
-        oblivious_put_price,
-        pledged_collateral.amount,
-        collateral_ccy_decimals,
-    )
-}
+            execute_repay_loan(deps, env, info, loan_id, repayment)
+        }
+        ExecuteMsg::RolloverLoan {
+            loan_id,
+            market,
+            expected_borrow,
+            strike_id,
+        } => {
+            if !market_phase.can_repay_loans() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-fn calculate_interest_cost(
-    oblivious_put_price: Uint128,
-    collateral_amount: Uint128,
-    collateral_ccy_decimals: u8,
-) -> Result<Uint128, ContractError> {
-    Ok(oblivious_put_price
-        .checked_mul(collateral_amount)?
-        .checked_div(Uint128::from(10u8).pow(collateral_ccy_decimals.into()))?)
-}
+            assert_not_paused(deps.storage, PausableAction::Exercise)?;
 
-fn get_expiry_time(
-    storage: &dyn Storage,
-    current_block_height: u64,
-) -> Result<ExpiryTime, ContractError> {
-    let MarketPhasesInfo {
-        amm_phase_ends_at, ..
-    } = get_market_info(storage)?;
-    let blocks_per_year = get_blocks_per_year(storage)?;
+            execute_rollover_loan(
+                deps,
+                env,
+                info.sender,
+                loan_id,
+                market,
+                expected_borrow,
+                strike_id,
+            )
+        }
+        ExecuteMsg::BorrowForRollover {
+            borrower,
+            expected_borrow,
+            pledged_collateral,
+            strike_id,
+            repayment,
+        } => {
+            if !market_phase.can_amm_accept_borrowing() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    calculate_expiry_time(current_block_height, amm_phase_ends_at, blocks_per_year)
-}
+            assert_not_paused(deps.storage, PausableAction::Borrow)?;
 
-fn calculate_expiry_time(
-    current_block_height: u64,
-    amm_phase_ends_at: u64,
-    blocks_per_year: u64,
-) -> Result<ExpiryTime, ContractError> {
-    let time_to_expiry = Uint128::from(amm_phase_ends_at)
-        .checked_sub(current_block_height.into())?
-        .checked_div(blocks_per_year.into())?;
-    let sqrt_time_to_expiry = time_to_expiry.isqrt();
+            execute_borrow_for_rollover(
+                deps,
+                env,
+                info,
+                borrower,
+                expected_borrow,
+                pledged_collateral,
+                strike_id,
+                repayment,
+            )
+        }
+        ExecuteMsg::CloseLoan { loan_id, repayment } => {
+            if !market_phase.can_close_loans_early() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    Ok(ExpiryTime {
-        time_to_expiry,
-        sqrt_time_to_expiry,
-    })
-}
+            assert_not_paused(deps.storage, PausableAction::Withdraw)?;
 
-fn get_oblivious_put_price(
-    deps: Deps,
-    _sqrt_time_to_expiry: Uint128,
-) -> Result<Uint128, ContractError> {
-    let _alpha = get_alpha(deps.storage)?;
-    calculate_oblivious_put_price()
-}
+            execute_close_loan(deps, env, info, loan_id, repayment)
+        }
+        ExecuteMsg::ExitPosition {
+            position_id,
+            expected_return,
+        } => {
+            if !market_phase.can_exit_positions() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-fn calculate_oblivious_put_price() -> Result<Uint128, ContractError> {
-    // fn calculate_oblivious_put_price(collateral_price: Uint128, collateral_price_annualized_vol: Uint128, sqrt_time_to_expiry: Uint128) -> Result<Uint128, ContractError> {
+            assert_not_paused(deps.storage, PausableAction::Withdraw)?;
 
-    // let alpha = get_alpha(deps.storage)?;
-    Ok(Uint128::zero())
-}
+            execute_exit_position(deps, env, info.sender, position_id, expected_return)
+        }
+        ExecuteMsg::SetStrikes { strikes } => {
+            if market_phase != MarketPhase::ProvidingLiquidity {
+                return Err(ContractError::Unauthorized {});
+            }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
-    match msg {
-        QueryMsg::GetMarketOperator {} => query_get_market_operator(deps),
-        QueryMsg::GetLiquidityPool {} => query_get_liquidity_pool(deps),
-        QueryMsg::GetMarketPhase {} => query_get_market_phase(deps, env),
-        QueryMsg::GetMarketPhasesInfo {} => query_get_market_phases_info(deps),
-        QueryMsg::GetBorrowingTerms { pledged_collateral } => {
-            query_get_borrowing_terms(deps, pledged_collateral, env.block.height)
+            execute_set_strikes(deps, info.sender, strikes)
         }
-    }
-}
+        ExecuteMsg::CollectFees {} => execute_collect_fees(deps),
+        ExecuteMsg::SetCapacityLimits { capacity_limits } => {
+            execute_set_capacity_limits(deps, info.sender, capacity_limits)
+        }
+        ExecuteMsg::Pause { actions } => execute_set_paused(deps, info.sender, actions, true),
+        ExecuteMsg::Unpause { actions } => execute_set_paused(deps, info.sender, actions, false),
+        ExecuteMsg::SetAllowlist {
+            restricted_roles,
+            allowlist_contract,
+        } => execute_set_allowlist(deps, info.sender, restricted_roles, allowlist_contract),
+        ExecuteMsg::UpdateAllowlist { role, add, remove } => {
+            execute_update_allowlist(deps, info.sender, role, add, remove)
+        }
+        ExecuteMsg::QueueUpdate { update } => execute_queue_update(deps, env, info.sender, update),
+        ExecuteMsg::ApplyUpdate {} => execute_apply_update(deps, env, info.sender),
+        ExecuteMsg::CancelUpdate {} => execute_cancel_update(deps, info.sender),
+        ExecuteMsg::MigrateLiquidityPool {
+            liquidity_pool_code_id,
+            liquidity_pool_token_code_id,
+        } => execute_migrate_liquidity_pool(
+            deps,
+            info.sender,
+            liquidity_pool_code_id,
+            liquidity_pool_token_code_id,
+        ),
+        ExecuteMsg::RolloverLiquidity { market } => {
+            if !market_phase.can_roll_over_liquidity() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-fn query_get_market_phase(deps: Deps, env: Env) -> Result<Binary, ContractError> {
-    let response = MarketPhaseResponse {
-        phase: get_current_market_phase(deps.storage, env.block.height)?,
-    };
+            execute_rollover_liquidity(deps, env, info.sender, market)
+        }
+        ExecuteMsg::CaptureSettlementPrice {} => {
+            if !market_phase.can_capture_settlement_price() {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    Ok(to_binary(&response)?)
-}
+            if get_settlement_price(deps.storage)?.is_some() {
+                return Err(ContractError::SettlementPriceAlreadyCaptured {});
+            }
 
-fn query_get_market_phases_info(deps: Deps) -> Result<Binary, ContractError> {
-    let response: MarketPhasesInfoResponse = get_market_info(deps.storage)?.into();
+            let settlement_price = load_or_capture_settlement_price(deps, &env)?;
 
-    Ok(to_binary(&response)?)
-}
+            Ok(Response::new().add_attributes(vec![
+                ("method", "capture_settlement_price".to_string()),
+                ("price", settlement_price.price.to_string()),
+                ("captured_at", settlement_price.captured_at.to_string()),
+            ]))
+        }
+        ExecuteMsg::EnterAutomatedMarketMakerPhase {} => {
+            if market_phase != MarketPhase::ProvidingLiquidity {
+                return Err(ContractError::Unauthorized {});
+            }
 
-fn query_get_market_operator(deps: Deps) -> Result<Binary, ContractError> {
-    let response = MarketOperatorResponse {
-        market_operator: get_market_operator(deps.storage)?,
-    };
+            execute_enter_automated_market_maker_phase(deps, env)
+        }
+        ExecuteMsg::CancelMarket {} => {
+            if market_phase != MarketPhase::ProvidingLiquidity
+                && market_phase != MarketPhase::AutomatedMarketMaker
+            {
+                return Err(ContractError::Unauthorized {});
+            }
 
-    Ok(to_binary(&response)?)
+            execute_cancel_market(deps, info.sender)
+        }
+        ExecuteMsg::CheckMinimumLiquidity {} => {
+            Ok(Response::new().add_attribute("method", "check_minimum_liquidity"))
+        }
+    }?;
+
+    // the pool records its reserves once the action has changed them
+    Ok(response.add_message(wasm_execute(
+        liquidity_pool,
+        &cw_zll_std_liquidity_pool::msg::ExecuteMsg::SyncReserves {},
+        vec![],
+    )?))
 }
 
-fn query_get_liquidity_pool(deps: Deps) -> Result<Binary, ContractError> {
-    let response = LiquidityPoolResponse {
-        liquidity_pool: get_liquidity_pool(deps.storage)?,
+/// Returns the settlement price of the market, capturing the current market price first if it has
+/// not been captured yet. Every settlement action has to read the price through this function, so
+/// the first one of them fixes the price for the rest of the market.
+fn load_or_capture_settlement_price(
+    deps: DepsMut,
+    env: &Env,
+) -> Result<SettlementPrice, ContractError> {
+    if let Some(settlement_price) = get_settlement_price(deps.storage)? {
+        return Ok(settlement_price);
+    }
+
+    let market_state = get_market_state(deps.storage)?;
+    let amm_observed_at = market_state.amm_observed_at;
+    let CollateralPriceResponse {
+        price,
+        source,
+        publish_time,
+        confidence,
+    } = get_amm_end_collateral_price(deps.as_ref(), &market_state)?;
+
+    let settlement_price = SettlementPrice {
+        price,
+        source,
+        publish_time,
+        confidence,
+        observed_at: amm_observed_at,
+        captured_at: env.block.height,
     };
 
-    Ok(to_binary(&response)?)
+    set_settlement_price(deps.storage, &settlement_price)?;
+
+    Ok(settlement_price)
 }
 
-fn get_current_market_phase(
-    storage: &dyn Storage,
+fn execute_set_strikes(
+    deps: DepsMut,
+    sender: Addr,
+    strikes: Vec<StrikeInfo>,
+) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let strikes = strikes
+        .into_iter()
+        .zip(1u64..)
+        .map(|(StrikeInfo { price, capacity }, id)| {
+            if price.is_zero() {
+                return Err(StdError::GenericErr {
+                    msg: format!("strike {} must have a price greater than 0", id),
+                });
+            }
+
+            Ok(Strike {
+                id,
+                price,
+                capacity,
+                borrowed: Uint128::zero(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    set_strikes(deps.storage, &strikes)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "set_strikes".to_string()),
+        (
+            "strikes",
+            strikes
+                .iter()
+                .map(|strike| strike.price.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+    ]))
+}
+
+fn execute_set_paused(
+    deps: DepsMut,
+    sender: Addr,
+    actions: Vec<PausableAction>,
+    is_paused: bool,
+) -> Result<Response, ContractError> {
+    if Some(sender) != get_guardian(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut paused_actions = get_paused_actions(deps.storage)?;
+
+    for action in actions.iter() {
+        paused_actions.set_paused(*action, is_paused);
+    }
+
+    set_paused_actions(deps.storage, &paused_actions)?;
+
+    Ok(Response::new().add_attributes(vec![
+        (
+            "method",
+            if is_paused { "pause" } else { "unpause" }.to_string(),
+        ),
+        (
+            "actions",
+            actions
+                .iter()
+                .map(|action| action.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+    ]))
+}
+
+fn assert_not_paused(storage: &dyn Storage, action: PausableAction) -> Result<(), ContractError> {
+    if get_paused_actions(storage)?.is_paused(action) {
+        return Err(ContractError::ActionPaused { action });
+    }
+
+    Ok(())
+}
+
+fn execute_set_allowlist(
+    deps: DepsMut,
+    sender: Addr,
+    restricted_roles: AllowlistRoles,
+    allowlist_contract: Option<String>,
+) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let allowlist = Allowlist {
+        restricted_roles,
+        allowlist_contract: allowlist_contract
+            .map(|allowlist_contract| deps.api.addr_validate(&allowlist_contract))
+            .transpose()?,
+    };
+
+    set_allowlist(deps.storage, &allowlist)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "set_allowlist".to_string()),
+        (
+            "allowlist_contract",
+            allowlist
+                .allowlist_contract
+                .map_or_else(|| "none".to_string(), |addr| addr.to_string()),
+        ),
+    ]))
+}
+
+fn execute_update_allowlist(
+    deps: DepsMut,
+    sender: Addr,
+    role: AllowlistRole,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for (accounts, is_allowlisted) in [(&add, true), (&remove, false)] {
+        for account in accounts {
+            let account = deps.api.addr_validate(account)?;
+            let mut roles = get_allowlisted_roles(deps.storage, &account)?;
+
+            roles.set(role, is_allowlisted);
+            set_allowlisted_roles(deps.storage, &account, &roles)?;
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "update_allowlist".to_string()),
+        ("role", role.to_string()),
+        ("added", add.join(", ")),
+        ("removed", remove.join(", ")),
+    ]))
+}
+
+/// Checks whether the account may act in the role. A restricted role is open only to the
+/// accounts allowlisted by the allowlist contract, or by the market operator when the market has
+/// no allowlist contract.
+fn is_allowlisted(deps: Deps, role: AllowlistRole, account: &Addr) -> StdResult<bool> {
+    let Allowlist {
+        restricted_roles,
+        allowlist_contract,
+    } = get_allowlist(deps.storage)?;
+
+    if !restricted_roles.includes(role) {
+        return Ok(true);
+    }
+
+    match allowlist_contract {
+        Some(allowlist_contract) => {
+            let IsAllowedResponse { is_allowed } = deps.querier.query_wasm_smart(
+                allowlist_contract,
+                &AllowlistQueryMsg::IsAllowed {
+                    role,
+                    address: account.to_string(),
+                },
+            )?;
+
+            Ok(is_allowed)
+        }
+        None => Ok(get_allowlisted_roles(deps.storage, account)?.includes(role)),
+    }
+}
+
+fn assert_allowlisted(
+    deps: Deps,
+    role: AllowlistRole,
+    account: &Addr,
+) -> Result<(), ContractError> {
+    if !is_allowlisted(deps, role, account)? {
+        return Err(ContractError::NotAllowlisted {
+            role,
+            address: account.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn execute_queue_update(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    update: MarketUpdate,
+) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if may_get_pending_update(deps.storage)?.is_some() {
+        return Err(ContractError::UpdateAlreadyQueued {});
+    }
+
+    validate_market_update(deps.as_ref(), &update, env.block.height)?;
+
+    let pending_update = PendingUpdate {
+        update,
+        queued_at: env.block.height,
+        executable_at: env.block.height + get_timelock_delay(deps.storage)?,
+    };
+
+    set_pending_update(deps.storage, &pending_update)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "queue_update".to_string()),
+        ("executable_at", pending_update.executable_at.to_string()),
+    ]))
+}
+
+fn execute_apply_update(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let PendingUpdate {
+        update,
+        executable_at,
+        ..
+    } = may_get_pending_update(deps.storage)?.ok_or(ContractError::NoPendingUpdate {})?;
+
+    if env.block.height < executable_at {
+        return Err(ContractError::UpdateTimelocked { executable_at });
+    }
+
+    // the market may have moved on to a later phase while the update was queued
+    validate_market_update(deps.as_ref(), &update, env.block.height)?;
+
+    apply_market_update(deps.storage, &update)?;
+    remove_pending_update(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "apply_update"))
+}
+
+fn execute_cancel_update(deps: DepsMut, sender: Addr) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if may_get_pending_update(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingUpdate {});
+    }
+
+    remove_pending_update(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "cancel_update"))
+}
+
+fn validate_market_update(
+    deps: Deps,
+    update: &MarketUpdate,
+    current_block_height: u64,
+) -> Result<(), ContractError> {
+    if let Some(market_phases_info) = &update.market_phases_info {
+        validate_phase_schedule_update(
+            &get_market_info(deps.storage)?,
+            market_phases_info,
+            current_block_height,
+        )?;
+    }
+
+    if let Some(buyback_spread) = update.buyback_spread {
+        validate_buyback_spread(Some(buyback_spread))?;
+    }
+
+    if let Some(protocol_fee_rate) = update.protocol_fee_rate {
+        validate_protocol_fee_rate(Some(protocol_fee_rate), &get_treasury(deps.storage)?)?;
+    }
+
+    if let Some(timelock_delay) = update.timelock_delay {
+        validate_timelock_delay(Some(timelock_delay))?;
+    }
+
+    if let Some(capacity_limits) = &update.capacity_limits {
+        validate_capacity_limits(capacity_limits)?;
+    }
+
+    Ok(())
+}
+
+/// Validates a new phase schedule, which must leave the boundaries the market has already passed
+/// as they are and must not move the others into the past.
+fn validate_phase_schedule_update(
+    market_phases_info: &MarketPhasesInfo,
+    new_market_phases_info: &MarketPhasesInfo,
+    current_block_height: u64,
+) -> Result<(), ContractError> {
+    validate_market_phases_info(new_market_phases_info.clone())?;
+
+    let boundaries = [
+        (
+            "market_started_at",
+            market_phases_info.market_started_at,
+            new_market_phases_info.market_started_at,
+        ),
+        (
+            "lp_phase_ends_at",
+            market_phases_info.lp_phase_ends_at,
+            new_market_phases_info.lp_phase_ends_at,
+        ),
+        (
+            "amm_phase_ends_at",
+            market_phases_info.amm_phase_ends_at,
+            new_market_phases_info.amm_phase_ends_at,
+        ),
+        (
+            "settlement_phase_ends_at",
+            market_phases_info.settlement_phase_ends_at,
+            new_market_phases_info.settlement_phase_ends_at,
+        ),
+    ];
+
+    for (name, current_at, new_at) in boundaries {
+        if current_at != new_at
+            && (current_at < current_block_height || new_at < current_block_height)
+        {
+            return Err(StdError::GenericErr {
+                msg: format!(
+                    "`{}` = {} cannot be changed to {} at block {}",
+                    name, current_at, new_at, current_block_height
+                ),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_set_capacity_limits(
+    deps: DepsMut,
+    sender: Addr,
+    capacity_limits: CapacityLimits,
+) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    validate_capacity_limits(&capacity_limits)?;
+
+    // looser limits commit more of the pool, so the LPs get the chance to withdraw beforehand
+    if !capacity_limits.is_tighter_than(&get_capacity_limits(deps.storage)?) {
+        return Err(ContractError::CapacityLimitsLoosened {});
+    }
+
+    set_capacity_limits(deps.storage, &capacity_limits)?;
+
+    Ok(Response::new().add_attribute("method", "set_capacity_limits"))
+}
+
+fn validate_capacity_limits(capacity_limits: &CapacityLimits) -> Result<(), ContractError> {
+    if let Some(max_utilization) = capacity_limits.max_utilization {
+        if max_utilization > Decimal::one() {
+            return Err(StdError::GenericErr {
+                msg: format!(
+                    "`max_utilization` = {} must not be greater than 1",
+                    max_utilization
+                ),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_migrate_liquidity_pool(
+    deps: DepsMut,
+    sender: Addr,
+    liquidity_pool_code_id: Option<u64>,
+    liquidity_pool_token_code_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+
+    let mut response = Response::new().add_attribute("method", "migrate_liquidity_pool");
+
+    if let Some(code_id) = liquidity_pool_token_code_id {
+        let PairInfo {
+            liquidity_token, ..
+        } = deps.querier.query_wasm_smart(
+            liquidity_pool.clone(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pair {},
+        )?;
+
+        response = response
+            .add_message(WasmMsg::Migrate {
+                contract_addr: liquidity_token.to_string(),
+                new_code_id: code_id,
+                msg: to_binary(&cw_zll_std_liquidity_pool::msg::TokenMigrateMsg {})?,
+            })
+            .add_attribute("liquidity_pool_token_code_id", code_id.to_string());
+    }
+
+    if let Some(code_id) = liquidity_pool_code_id {
+        response = response
+            .add_message(WasmMsg::Migrate {
+                contract_addr: liquidity_pool.to_string(),
+                new_code_id: code_id,
+                msg: to_binary(&cw_zll_std_liquidity_pool::msg::MigrateMsg {})?,
+            })
+            .add_attribute("liquidity_pool_code_id", code_id.to_string());
+    }
+
+    Ok(response)
+}
+
+fn execute_rollover_liquidity(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    market: String,
+) -> Result<Response, ContractError> {
+    if sender != get_creator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let market = deps.api.addr_validate(&market)?;
+
+    // the successor is instantiated by the creator, which registers this market as its predecessor
+    let PredecessorResponse { predecessor } = deps
+        .querier
+        .query_wasm_smart(&market, &QueryMsg::GetPredecessor {})?;
+
+    if predecessor != Some(env.contract.address) {
+        return Err(ContractError::InvalidSuccessor {
+            market: market.to_string(),
+        });
+    }
+
+    let LiquidityPoolResponse { liquidity_pool } = deps
+        .querier
+        .query_wasm_smart(&market, &QueryMsg::GetLiquidityPool {})?;
+
+    Ok(Response::new()
+        .add_message(wasm_execute(
+            get_liquidity_pool(deps.storage)?,
+            &cw_zll_std_liquidity_pool::msg::ExecuteMsg::RolloverLiquidity {
+                liquidity_pool: liquidity_pool.to_string(),
+            },
+            vec![],
+        )?)
+        .add_attribute("method", "rollover_liquidity")
+        .add_attribute("market", market)
+        .add_attribute("liquidity_pool", liquidity_pool))
+}
+
+fn execute_collect_fees(deps: DepsMut) -> Result<Response, ContractError> {
+    let treasury = get_treasury(deps.storage)?.ok_or(ContractError::TreasuryNotSet {})?;
+
+    let mut market_state = get_market_state(deps.storage)?;
+
+    if market_state.accrued_fees.is_zero() {
+        return Err(ContractError::NoFeesToCollect {});
+    }
+
+    let fees = Asset {
+        info: get_lending_asset_info(deps.storage)?,
+        amount: market_state.accrued_fees,
+    };
+
+    market_state.collected_fees = market_state.collected_fees.checked_add(fees.amount)?;
+    market_state.accrued_fees = Uint128::zero();
+    set_market_state(deps.storage, market_state)?;
+
+    Ok(Response::new()
+        .add_message(create_transfer_assets_msg(
+            get_liquidity_pool(deps.storage)?,
+            vec![fees.clone()],
+            &treasury,
+        )?)
+        .add_attributes(vec![
+            ("method", "collect_fees".to_string()),
+            ("treasury", treasury.to_string()),
+            ("fees", fees.to_string()),
+        ]))
+}
+
+fn execute_cancel_market(deps: DepsMut, sender: Addr) -> Result<Response, ContractError> {
+    if sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if get_market_state(deps.storage)?.has_positions() {
+        return Err(ContractError::MarketHasBorrowings {});
+    }
+
+    cancel_market(deps.storage)?;
+
+    Ok(Response::new().add_attribute("method", "cancel_market"))
+}
+
+fn execute_enter_automated_market_maker_phase(
+    deps: DepsMut,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let LiquidityProgressResponse {
+        liquidity,
+        target_liquidity,
+        is_target_reached,
+    } = deps.querier.query_wasm_smart(
+        get_liquidity_pool(deps.storage)?,
+        &cw_zll_std_liquidity_pool::msg::QueryMsg::LiquidityProgress {},
+    )?;
+
+    let target_liquidity = target_liquidity.ok_or(ContractError::TargetLiquidityNotSet {})?;
+
+    if !is_target_reached {
+        return Err(ContractError::TargetLiquidityNotReached {
+            liquidity: liquidity.amount,
+            target_liquidity: target_liquidity.amount,
+        });
+    }
+
+    let market_phases_info = validate_market_phases_info(shift_market_phases_info(
+        get_market_info(deps.storage)?,
+        env.block.height,
+    )?)?;
+
+    set_market_info(deps.storage, market_phases_info.clone())?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "enter_automated_market_maker_phase".to_string()),
+        (
+            "lp_phase_ends_at",
+            market_phases_info.lp_phase_ends_at.to_string(),
+        ),
+        (
+            "amm_phase_ends_at",
+            market_phases_info.amm_phase_ends_at.to_string(),
+        ),
+        (
+            "settlement_phase_ends_at",
+            market_phases_info.settlement_phase_ends_at.to_string(),
+        ),
+    ]))
+}
+
+/// Moves the end of the providing liquidity phase to the block preceding `current_block_height`
+/// and shifts all later boundaries by the same number of blocks, so every phase keeps its length.
+fn shift_market_phases_info(
+    market_phases_info: MarketPhasesInfo,
+    current_block_height: u64,
+) -> Result<MarketPhasesInfo, ContractError> {
+    let lp_phase_ends_at = current_block_height.saturating_sub(1);
+    let shift = checked_sub_blocks(
+        "lp_phase_ends_at",
+        market_phases_info.lp_phase_ends_at,
+        lp_phase_ends_at,
+    )?;
+
+    Ok(MarketPhasesInfo {
+        lp_phase_ends_at,
+        amm_phase_ends_at: checked_sub_blocks(
+            "amm_phase_ends_at",
+            market_phases_info.amm_phase_ends_at,
+            shift,
+        )?,
+        settlement_phase_ends_at: checked_sub_blocks(
+            "settlement_phase_ends_at",
+            market_phases_info.settlement_phase_ends_at,
+            shift,
+        )?,
+        ..market_phases_info
+    })
+}
+
+fn checked_sub_blocks(name: &str, block_height: u64, blocks: u64) -> Result<u64, ContractError> {
+    block_height.checked_sub(blocks).ok_or_else(|| {
+        StdError::GenericErr {
+            msg: format!(
+                "`{}` = {} cannot be moved back by {} blocks",
+                name, block_height, blocks
+            ),
+        }
+        .into()
+    })
+}
+
+fn execute_borrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    expected_borrow: Asset,
+    pledged_collateral: Asset,
+    strike_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+    let borrower = info.sender.clone();
+    let (paid_out, response) = open_loan(
+        deps,
+        env,
+        info,
+        &borrower,
+        expected_borrow,
+        pledged_collateral,
+        strike_id,
+    )?;
+
+    Ok(response.add_message(create_transfer_assets_msg(
+        liquidity_pool,
+        vec![paid_out],
+        &borrower,
+    )?))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn execute_borrow_for_rollover(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: String,
+    expected_borrow: Asset,
+    pledged_collateral: Asset,
+    strike_id: Option<u64>,
+    repayment: Asset,
+) -> Result<Response, ContractError> {
+    // only the market this market succeeds in its series can roll loans over into it
+    if Some(&info.sender) != get_predecessor(deps.storage)?.as_ref() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower = deps.api.addr_validate(&borrower)?;
+
+    assert_allowlisted(deps.as_ref(), AllowlistRole::Borrower, &borrower)?;
+
+    if !repayment.info.equal(&get_lending_asset_info(deps.storage)?) {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+    let predecessor = info.sender.clone();
+    let (paid_out, response) = open_loan(
+        deps,
+        env,
+        info,
+        &borrower,
+        expected_borrow,
+        pledged_collateral,
+        strike_id,
+    )?;
+
+    if paid_out.amount < repayment.amount {
+        return Err(ContractError::InsufficientRolloverProceeds {
+            proceeds: paid_out.amount,
+            repayment: repayment.amount,
+        });
+    }
+
+    let rest = Asset {
+        info: paid_out.info.clone(),
+        amount: paid_out.amount.checked_sub(repayment.amount)?,
+    };
+
+    let mut response = response.add_message(create_transfer_assets_msg(
+        liquidity_pool.clone(),
+        vec![repayment.clone()],
+        &predecessor,
+    )?);
+
+    if !rest.amount.is_zero() {
+        response = response.add_message(create_transfer_assets_msg(
+            liquidity_pool,
+            vec![rest],
+            &borrower,
+        )?);
+    }
+
+    Ok(response.add_attributes(vec![
+        ("rolled_over_from", predecessor.to_string()),
+        ("repayment", repayment.to_string()),
+    ]))
+}
+
+/// Takes out a loan for `borrower` against the pledged collateral sent by the sender of the
+/// message. Returns the amount to pay out of the pool for the loan along with the response
+/// depositing the collateral, leaving the payout to the caller.
+fn open_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: &Addr,
+    expected_borrow: Asset,
+    pledged_collateral: Asset,
+    strike_id: Option<u64>,
+) -> Result<(Asset, Response), ContractError> {
+    assert_price_guards(deps.as_ref(), &env)?;
+
+    let [collateral_asset_info, _] = get_asset_infos(deps.storage)?;
+
+    if !pledged_collateral.info.equal(&collateral_asset_info) {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    pledged_collateral.assert_sent_native_token_balance(&info)?;
+
+    let BorrowingTerms {
+        borrow,
+        interest,
+        strike,
+        protocol_fee,
+        ..
+    } = get_borrowing_terms(
+        deps.as_ref(),
+        pledged_collateral.clone(),
+        strike_id,
+        Some(borrower),
+        env.block.height,
+    )?;
+
+    if expected_borrow.amount > borrow.amount {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Expected amount to borrow ({}) is higher than calculated collateral amount ({})",
+            &expected_borrow.amount, &borrow.amount
+        ))));
+    }
+
+    // the interest is paid out of the borrowed amount
+    if borrow.amount <= interest.amount {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    assert_capacity(
+        deps.as_ref(),
+        borrower,
+        borrow.amount,
+        Commitment::Loan,
+        env.block.height,
+    )?;
+    record_exposure(deps.storage, borrower, borrow.amount)?;
+
+    if let Some(strike_id) = strike_id {
+        let mut strike = get_strike(deps.storage, strike_id)?;
+
+        if let Some(remaining_capacity) = strike.remaining_capacity() {
+            if borrow.amount > remaining_capacity {
+                return Err(ContractError::StrikeCapacityExceeded {
+                    strike_id,
+                    borrow: borrow.amount,
+                    remaining_capacity,
+                });
+            }
+        }
+
+        strike.borrowed = strike.borrowed.checked_add(borrow.amount)?;
+        set_strike(deps.storage, &strike)?;
+    }
+
+    let loan = Loan {
+        id: next_loan_id(deps.storage)?,
+        owner: borrower.clone(),
+        strike,
+        strike_id,
+        notional: borrow.amount,
+        collateral_amount: pledged_collateral.amount,
+        interest: interest.amount,
+        status: LoanStatus::Open,
+        parent_id: None,
+        created_at: env.block.height,
+    };
+
+    set_loan(deps.storage, &loan)?;
+
+    // The pledged collateral stays locked in the pool until the loan is repaid or the market settles
+    let mut market_state = record_borrowing(
+        deps.storage,
+        interest.amount.checked_sub(protocol_fee.amount)?,
+        protocol_fee.amount,
+    )?;
+    market_state.loan_collateral = market_state
+        .loan_collateral
+        .checked_add(pledged_collateral.amount)?;
+    market_state.borrowed_notional = market_state.borrowed_notional.checked_add(borrow.amount)?;
+    set_market_state(deps.storage, market_state)?;
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+    let paid_out = Asset {
+        info: borrow.info.clone(),
+        amount: borrow.amount.checked_sub(interest.amount)?,
+    };
+
+    let mint_msg = create_option_token_msg(
+        deps.storage,
+        &OptionTokenExecuteMsg::Mint {
+            token_id: get_option_token_id(loan.id),
+            owner: borrower.to_string(),
+            metadata: get_option_token_metadata(&loan),
+        },
+    )?;
+
+    let response = Response::new()
+        .add_message(create_deposit_msg(
+            &liquidity_pool,
+            &pledged_collateral,
+            &info.sender,
+        )?)
+        .add_messages(mint_msg)
+        .add_attributes(vec![
+            ("method", "borrow".to_string()),
+            ("borrower", borrower.to_string()),
+            ("loan_id", loan.id.to_string()),
+            ("pledged_collateral", pledged_collateral.to_string()),
+            ("borrow", borrow.to_string()),
+            ("strike", strike.to_string()),
+            ("interest", interest.to_string()),
+            ("protocol_fee", protocol_fee.to_string()),
+            ("paid_out", paid_out.to_string()),
+        ]);
+
+    Ok((paid_out, response))
+}
+
+fn execute_repay_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    loan_id: u64,
+    repayment: Asset,
+) -> Result<Response, ContractError> {
+    let mut loan = get_loan(deps.storage, loan_id)?;
+
+    if loan.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if loan.status != LoanStatus::Open {
+        return Err(ContractError::LoanAlreadyRepaid { loan_id });
+    }
+
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+
+    if !repayment.info.equal(&lending_asset_info) {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    if repayment.amount.is_zero() || repayment.amount > loan.notional {
+        return Err(ContractError::InvalidRepayment {
+            repayment: repayment.amount,
+            notional: loan.notional,
+        });
+    }
+
+    repayment.assert_sent_native_token_balance(&info)?;
+
+    // rounding the released collateral down favours the pool
+    let released_collateral = Asset {
+        info: collateral_asset_info,
+        amount: loan
+            .collateral_amount
+            .multiply_ratio(repayment.amount, loan.notional),
+    };
+
+    if released_collateral.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let repaid_loan_id = if repayment.amount == loan.notional {
+        loan.id
+    } else {
+        // the repaid part of the CALL option is split off into a loan of its own
+        let interest = loan
+            .interest
+            .multiply_ratio(repayment.amount, loan.notional);
+        let repaid_loan = Loan {
+            id: next_loan_id(deps.storage)?,
+            owner: loan.owner.clone(),
+            strike: loan.strike,
+            strike_id: loan.strike_id,
+            notional: repayment.amount,
+            collateral_amount: released_collateral.amount,
+            interest,
+            status: LoanStatus::Repaid,
+            parent_id: Some(loan.id),
+            created_at: env.block.height,
+        };
+
+        set_loan(deps.storage, &repaid_loan)?;
+
+        loan.notional = loan.notional.checked_sub(repaid_loan.notional)?;
+        loan.collateral_amount = loan
+            .collateral_amount
+            .checked_sub(repaid_loan.collateral_amount)?;
+        loan.interest = loan.interest.checked_sub(repaid_loan.interest)?;
+
+        repaid_loan.id
+    };
+
+    if repaid_loan_id == loan.id {
+        loan.status = LoanStatus::Repaid;
+    }
+
+    set_loan(deps.storage, &loan)?;
+
+    let mut market_state = get_market_state(deps.storage)?;
+    market_state.loan_collateral = market_state
+        .loan_collateral
+        .checked_sub(released_collateral.amount)?;
+    set_market_state(deps.storage, market_state)?;
+    release_loan_notional(deps.storage, &loan, repayment.amount)?;
+
+    // the option token keeps standing for the part of the CALL option which is still open
+    let option_token_msg = create_option_token_msg(
+        deps.storage,
+        &if loan.status == LoanStatus::Repaid {
+            OptionTokenExecuteMsg::Burn {
+                token_id: get_option_token_id(loan.id),
+            }
+        } else {
+            OptionTokenExecuteMsg::UpdateMetadata {
+                token_id: get_option_token_id(loan.id),
+                metadata: get_option_token_metadata(&loan),
+            }
+        },
+    )?;
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+
+    Ok(Response::new()
+        .add_message(create_deposit_msg(
+            &liquidity_pool,
+            &repayment,
+            &info.sender,
+        )?)
+        .add_message(create_transfer_assets_msg(
+            liquidity_pool,
+            vec![released_collateral.clone()],
+            &info.sender,
+        )?)
+        .add_messages(option_token_msg)
+        .add_attributes(vec![
+            ("method", "repay_loan".to_string()),
+            ("loan_id", loan_id.to_string()),
+            ("repaid_loan_id", repaid_loan_id.to_string()),
+            ("repayment", repayment.to_string()),
+            ("released_collateral", released_collateral.to_string()),
+            ("remaining_notional", loan.notional.to_string()),
+        ]))
+}
+
+/// Repays the whole loan with the proceeds of a loan against the same collateral in the successor
+/// market. The collateral is sent along to the successor market, which pays the repayment back
+/// to the market; the reply checks the repayment has arrived before depositing it in the pool, so
+/// the successor market does not have to be trusted.
+fn execute_rollover_loan(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    loan_id: u64,
+    market: String,
+    expected_borrow: Asset,
+    strike_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let market = deps.api.addr_validate(&market)?;
+
+    if market == env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // a rollover must not be started while the successor market handles another one
+    if may_get_pending_loan_rollover(deps.storage)?.is_some() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut loan = get_loan(deps.storage, loan_id)?;
+
+    if loan.owner != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if loan.status != LoanStatus::Open {
+        return Err(ContractError::LoanAlreadyRepaid { loan_id });
+    }
+
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+    let collateral = Asset {
+        info: collateral_asset_info,
+        amount: loan.collateral_amount,
+    };
+    let repayment = Asset {
+        info: lending_asset_info,
+        amount: loan.notional,
+    };
+
+    set_pending_loan_rollover(
+        deps.storage,
+        &PendingLoanRollover {
+            loan_id,
+            repayment: repayment.amount,
+            balance: repayment
+                .info
+                .query_pool(&deps.querier, env.contract.address.clone())?,
+        },
+    )?;
+
+    loan.status = LoanStatus::RolledOver;
+    set_loan(deps.storage, &loan)?;
+
+    let mut market_state = get_market_state(deps.storage)?;
+    market_state.loan_collateral = market_state
+        .loan_collateral
+        .checked_sub(collateral.amount)?;
+    set_market_state(deps.storage, market_state)?;
+    release_loan_notional(deps.storage, &loan, loan.notional)?;
+
+    let mut messages = vec![create_transfer_assets_msg(
+        get_liquidity_pool(deps.storage)?,
+        vec![collateral.clone()],
+        &env.contract.address,
+    )?];
+
+    // the successor market issues an option token of its own for the new loan
+    messages.extend(create_option_token_msg(
+        deps.storage,
+        &OptionTokenExecuteMsg::Burn {
+            token_id: get_option_token_id(loan.id),
+        },
+    )?);
+
+    // native tokens are sent along with the message, tokens are transferred using an allowance
+    let funds = match &collateral.info {
+        AssetInfo::NativeToken { denom } => coins(collateral.amount.u128(), denom),
+        AssetInfo::Token { contract_addr } => {
+            messages.push(
+                wasm_execute(
+                    contract_addr,
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: market.to_string(),
+                        amount: collateral.amount,
+                        expires: None,
+                    },
+                    vec![],
+                )?
+                .into(),
+            );
+
+            vec![]
+        }
+    };
+
+    let borrow_msg = SubMsg::reply_on_success(
+        wasm_execute(
+            &market,
+            &ExecuteMsg::BorrowForRollover {
+                borrower: loan.owner.to_string(),
+                expected_borrow,
+                pledged_collateral: collateral.clone(),
+                strike_id,
+                repayment: repayment.clone(),
+            },
+            funds,
+        )?,
+        BORROW_FOR_ROLLOVER_REPLY_ID,
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(borrow_msg)
+        .add_attributes(vec![
+            ("method", "rollover_loan".to_string()),
+            ("loan_id", loan_id.to_string()),
+            ("market", market.to_string()),
+            ("collateral", collateral.to_string()),
+            ("repayment", repayment.to_string()),
+        ]))
+}
+
+fn execute_lend(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    expected_premium: Asset,
+    lent_asset: Asset,
+) -> Result<Response, ContractError> {
+    assert_price_guards(deps.as_ref(), &env)?;
+
+    if !lent_asset
+        .info
+        .equal(&get_lending_asset_info(deps.storage)?)
+    {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    lent_asset.assert_sent_native_token_balance(&info)?;
+
+    let LendingTerms {
+        premium,
+        collateral,
+        strike,
+        protocol_fee,
+        ..
+    } = get_lending_terms(
+        deps.as_ref(),
+        lent_asset.clone(),
+        Some(&info.sender),
+        env.block.height,
+    )?;
+
+    if expected_premium.amount > premium.amount {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Expected premium ({}) is higher than calculated premium ({})",
+            &expected_premium.amount, &premium.amount
+        ))));
+    }
+
+    if collateral.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    assert_capacity(
+        deps.as_ref(),
+        &info.sender,
+        lent_asset.amount,
+        Commitment::Put { strike },
+        env.block.height,
+    )?;
+    record_exposure(deps.storage, &info.sender, lent_asset.amount)?;
+
+    let position = Position {
+        id: next_position_id(deps.storage)?,
+        owner: info.sender.clone(),
+        strike,
+        lent_amount: lent_asset.amount,
+        collateral_amount: collateral.amount,
+        premium: premium.amount,
+        status: PositionStatus::Open,
+        is_claimed: false,
+        created_at: env.block.height,
+    };
+
+    set_position(deps.storage, &position)?;
+
+    // The lent asset stays locked in the pool until the lender claims it or the collateral
+    let mut market_state = get_market_state(deps.storage)?;
+    market_state.locked_lending = market_state.locked_lending.checked_add(lent_asset.amount)?;
+    market_state.accrued_fees = market_state.accrued_fees.checked_add(protocol_fee.amount)?;
+    market_state.put_collateral = market_state.put_collateral.checked_add(collateral.amount)?;
+    set_market_state(deps.storage, market_state)?;
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+
+    let mut messages = vec![create_deposit_msg(
+        &liquidity_pool,
+        &lent_asset,
+        &info.sender,
+    )?];
+
+    if !premium.amount.is_zero() {
+        messages.push(create_transfer_assets_msg(
+            liquidity_pool,
+            vec![premium.clone()],
+            &info.sender,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("method", "lend".to_string()),
+        ("lender", info.sender.to_string()),
+        ("position_id", position.id.to_string()),
+        ("lent_asset", lent_asset.to_string()),
+        ("premium", premium.to_string()),
+        ("protocol_fee", protocol_fee.to_string()),
+        ("strike", strike.to_string()),
+    ]))
+}
+
+fn get_lending_terms(
+    deps: Deps,
+    lent_asset: Asset,
+    account: Option<&Addr>,
+    current_block_height: u64,
+) -> Result<LendingTerms, ContractError> {
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+
+    // The PUT option written by the lender is struck at the current collateral price
+    let CollateralPriceResponse { price: strike, .. } = get_current_collateral_price(deps)?;
+
+    if strike.is_zero() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Collateral price must not be zero",
+        )));
+    }
+
+    let collateral = Asset {
+        info: collateral_asset_info,
+        amount: lent_asset
+            .amount
+            .multiply_ratio(strike.denominator(), strike.numerator()),
+    };
+    let premium_amount =
+        get_interest_cost(deps, collateral.clone(), Some(strike), current_block_height)?;
+    let protocol_fee_amount = calculate_protocol_fee(deps.storage, premium_amount)?;
+
+    Ok(LendingTerms {
+        lend: Asset {
+            info: lending_asset_info.clone(),
+            amount: lent_asset.amount,
+        },
+        // the protocol fee is kept by the pool and paid to the treasury when it is collected
+        premium: Asset {
+            info: lending_asset_info.clone(),
+            amount: premium_amount.checked_sub(protocol_fee_amount)?,
+        },
+        collateral,
+        strike,
+        protocol_fee: Asset {
+            info: lending_asset_info,
+            amount: protocol_fee_amount,
+        },
+        remaining_capacity: get_remaining_capacity(
+            deps,
+            account,
+            Commitment::Put { strike },
+            current_block_height,
+        )?
+        .min(),
+    })
+}
+
+/// What the pool commits to a new position, which decides the reserve its utilization is
+/// measured against.
+enum Commitment {
+    /// The pool pays out the lending asset
+    Loan,
+    /// The pool may have to deliver the collateral asset at the strike price
+    Put { strike: Decimal },
+}
+
+/// The remaining capacity under each capacity limit of the market, in the lending asset.
+struct RemainingCapacity {
+    position: Option<Uint128>,
+    account: Option<Uint128>,
+    utilization: Option<Uint128>,
+}
+
+impl RemainingCapacity {
+    fn min(&self) -> Option<Uint128> {
+        [self.position, self.account, self.utilization]
+            .iter()
+            .flatten()
+            .min()
+            .copied()
+    }
+}
+
+fn get_remaining_capacity(
+    deps: Deps,
+    account: Option<&Addr>,
+    commitment: Commitment,
+    current_block_height: u64,
+) -> Result<RemainingCapacity, ContractError> {
+    let CapacityLimits {
+        max_position_size,
+        max_account_exposure,
+        max_utilization,
+    } = get_capacity_limits(deps.storage)?;
+
+    let account = match (max_account_exposure, account) {
+        (Some(max_account_exposure), Some(account)) => {
+            Some(max_account_exposure.saturating_sub(get_account_exposure(deps.storage, account)?))
+        }
+        _ => None,
+    };
+
+    let utilization = match max_utilization {
+        Some(max_utilization) => {
+            let market_state = get_market_state(deps.storage)?;
+            let [collateral_reserve, lending_reserve] =
+                get_trading_reserves(deps, current_block_height)?;
+
+            Some(match commitment {
+                Commitment::Loan => {
+                    // the reserve is measured as it was before the loans were paid out
+                    let reserve = lending_reserve.checked_add(market_state.borrowed_notional)?;
+
+                    (reserve * max_utilization).saturating_sub(market_state.borrowed_notional)
+                }
+                Commitment::Put { strike } => {
+                    (collateral_reserve * max_utilization)
+                        .saturating_sub(market_state.put_collateral)
+                        * strike
+                }
+            })
+        }
+        None => None,
+    };
+
+    Ok(RemainingCapacity {
+        position: max_position_size,
+        account,
+        utilization,
+    })
+}
+
+/// Checks that a new position of the given size in the lending asset fits the capacity limits.
+fn assert_capacity(
+    deps: Deps,
+    account: &Addr,
+    size: Uint128,
+    commitment: Commitment,
+    current_block_height: u64,
+) -> Result<(), ContractError> {
+    let RemainingCapacity {
+        position,
+        account,
+        utilization,
+    } = get_remaining_capacity(deps, Some(account), commitment, current_block_height)?;
+
+    if let Some(max_position_size) = position {
+        if size > max_position_size {
+            return Err(ContractError::PositionSizeExceeded {
+                size,
+                max_position_size,
+            });
+        }
+    }
+
+    if let Some(remaining_exposure) = account {
+        if size > remaining_exposure {
+            return Err(ContractError::AccountExposureExceeded {
+                size,
+                remaining_exposure,
+            });
+        }
+    }
+
+    if let Some(remaining_capacity) = utilization {
+        if size > remaining_capacity {
+            return Err(ContractError::UtilizationExceeded {
+                size,
+                remaining_capacity,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn record_exposure(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    size: Uint128,
+) -> Result<(), ContractError> {
+    let exposure = get_account_exposure(storage, account)?.checked_add(size)?;
+
+    Ok(set_account_exposure(storage, account, exposure)?)
+}
+
+fn release_exposure(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    size: Uint128,
+) -> Result<(), ContractError> {
+    let exposure = get_account_exposure(storage, account)?.saturating_sub(size);
+
+    Ok(set_account_exposure(storage, account, exposure)?)
+}
+
+/// Releases the repaid, rolled over or closed notional of a loan from the notional borrowed from the
+/// market, the exposure of the borrower and the strike the loan was taken out at.
+fn release_loan_notional(
+    storage: &mut dyn Storage,
+    loan: &Loan,
+    notional: Uint128,
+) -> Result<(), ContractError> {
+    let mut market_state = get_market_state(storage)?;
+    market_state.borrowed_notional = market_state.borrowed_notional.checked_sub(notional)?;
+    set_market_state(storage, market_state)?;
+
+    release_exposure(storage, &loan.owner, notional)?;
+
+    // the strike may have been removed from the ladder since
+    if let Some(mut strike) = loan
+        .strike_id
+        .map(|strike_id| may_get_strike(storage, strike_id))
+        .transpose()?
+        .flatten()
+    {
+        strike.borrowed = strike.borrowed.saturating_sub(notional);
+        set_strike(storage, &strike)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the part of an option premium taken as the protocol fee.
+fn calculate_protocol_fee(
+    storage: &dyn Storage,
+    premium: Uint128,
+) -> Result<Uint128, ContractError> {
+    Ok(premium * get_protocol_fee_rate(storage)?)
+}
+
+/// The number of positions settled by `SettlePuts` when no limit is given.
+const DEFAULT_SETTLE_LIMIT: u32 = 10;
+/// The maximum number of positions settled by a single `SettlePuts`.
+const MAX_SETTLE_LIMIT: u32 = 100;
+
+fn execute_settle_puts(
+    mut deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let SettlementPrice {
+        price: settlement_price,
+        ..
+    } = load_or_capture_settlement_price(deps.branch(), &env)?;
+
+    let limit = limit.unwrap_or(DEFAULT_SETTLE_LIMIT).min(MAX_SETTLE_LIMIT) as usize;
+    let SettlementProgress {
+        last_position_id, ..
+    } = get_settlement_progress(deps.storage)?;
+
+    let positions = get_positions(deps.storage, last_position_id, limit)?;
+
+    let mut exercised_ids = vec![];
+    let mut expired_ids = vec![];
+
+    for position in positions.iter() {
+        if position.status == PositionStatus::Open {
+            let position = settle_put(
+                deps.branch(),
+                position.clone(),
+                settlement_price,
+                env.block.height,
+            )?;
+
+            match position.status {
+                PositionStatus::Exercised => exercised_ids.push(position.id.to_string()),
+                _ => expired_ids.push(position.id.to_string()),
+            }
+        }
+    }
+
+    let mut settlement_progress = get_settlement_progress(deps.storage)?;
+
+    if let Some(position) = positions.last() {
+        settlement_progress.last_position_id = Some(position.id);
+        set_settlement_progress(deps.storage, &settlement_progress)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "settle_puts".to_string()),
+        ("settlement_price", settlement_price.to_string()),
+        ("exercised_positions", exercised_ids.join(",")),
+        ("expired_positions", expired_ids.join(",")),
+        (
+            "last_position_id",
+            settlement_progress
+                .last_position_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// Settles an open PUT option with the settlement price. The pool exercises the option when the
+/// collateral it delivers is worth less than the lent asset and it holds enough unlocked collateral,
+/// otherwise it lets the option expire.
+fn settle_put(
+    deps: DepsMut,
+    mut position: Position,
+    settlement_price: Decimal,
+    current_block_height: u64,
+) -> Result<Position, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Exercise)?;
+
+    // the collateral pledged for loans can still be reclaimed by the borrowers
+    let [locked_collateral, _] = get_locked_amounts(deps.as_ref(), current_block_height)?;
+    let available_collateral = get_asset_infos(deps.storage)?[0]
+        .query_pool(&deps.querier, get_liquidity_pool(deps.storage)?)?
+        .checked_sub(locked_collateral)?;
+
+    let mut market_state = get_market_state(deps.storage)?;
+    let mut settlement_progress = get_settlement_progress(deps.storage)?;
+
+    market_state.put_collateral = market_state
+        .put_collateral
+        .checked_sub(position.collateral_amount)?;
+
+    if settlement_price < position.strike && available_collateral >= position.collateral_amount {
+        market_state.locked_lending = market_state
+            .locked_lending
+            .checked_sub(position.lent_amount)?;
+        market_state.locked_collateral = market_state
+            .locked_collateral
+            .checked_add(position.collateral_amount)?;
+        position.status = PositionStatus::Exercised;
+        settlement_progress.exercised_count += 1;
+    } else {
+        position.status = PositionStatus::Expired;
+        settlement_progress.expired_count += 1;
+    }
+
+    set_market_state(deps.storage, market_state)?;
+    set_settlement_progress(deps.storage, &settlement_progress)?;
+    set_position(deps.storage, &position)?;
+
+    Ok(position)
+}
+
+fn execute_claim_collateral(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    position_id: u64,
+) -> Result<Response, ContractError> {
+    let position = get_position(deps.storage, position_id)?;
+
+    if position.owner != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if position.is_claimed {
+        return Err(ContractError::PositionAlreadyClaimed { position_id });
+    }
+
+    let claimed_asset = claim_position(deps.branch(), &env, position)?;
+
+    Ok(Response::new()
+        .add_message(create_transfer_assets_msg(
+            get_liquidity_pool(deps.storage)?,
+            vec![claimed_asset.clone()],
+            &sender,
+        )?)
+        .add_attributes(vec![
+            ("method", "claim_collateral".to_string()),
+            ("position_id", position_id.to_string()),
+            ("claimed_asset", claimed_asset.to_string()),
+        ]))
+}
+
+/// Marks the position as claimed and releases the assets owed to its owner, settling the position
+/// first if it is still open. Returns the asset the pool has to send to the owner.
+fn claim_position(
+    mut deps: DepsMut,
+    env: &Env,
+    mut position: Position,
+) -> Result<Asset, ContractError> {
+    if position.status == PositionStatus::Open {
+        let SettlementPrice { price, .. } = load_or_capture_settlement_price(deps.branch(), env)?;
+        position = settle_put(deps.branch(), position, price, env.block.height)?;
+    }
+
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+    let mut market_state = get_market_state(deps.storage)?;
+
+    let claimed_asset = if position.status == PositionStatus::Exercised {
+        market_state.locked_collateral = market_state
+            .locked_collateral
+            .checked_sub(position.collateral_amount)?;
+        Asset {
+            info: collateral_asset_info,
+            amount: position.collateral_amount,
+        }
+    } else {
+        market_state.locked_lending = market_state
+            .locked_lending
+            .checked_sub(position.lent_amount)?;
+        Asset {
+            info: lending_asset_info,
+            amount: position.lent_amount,
+        }
+    };
+
+    position.is_claimed = true;
+
+    set_market_state(deps.storage, market_state)?;
+    set_position(deps.storage, &position)?;
+
+    Ok(claimed_asset)
+}
+
+/// Validates a batch of positions processed by the market operator. Any error fails the whole batch.
+fn assert_batch(deps: Deps, sender: &Addr, position_ids: &[u64]) -> Result<(), ContractError> {
+    if *sender != get_market_operator(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if position_ids.len() > MAX_SETTLE_LIMIT as usize {
+        return Err(ContractError::BatchTooLarge {
+            batch_size: position_ids.len() as u64,
+            max_batch_size: MAX_SETTLE_LIMIT as u64,
+        });
+    }
+
+    Ok(())
+}
+
+fn execute_batch_settle_puts(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    position_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    assert_batch(deps.as_ref(), &sender, &position_ids)?;
+
+    let SettlementPrice {
+        price: settlement_price,
+        ..
+    } = load_or_capture_settlement_price(deps.branch(), &env)?;
+
+    let mut results = vec![];
+    let mut settled_count = 0;
+
+    for position_id in position_ids {
+        let result = match may_get_position(deps.storage, position_id)? {
+            None => "skipped: not found".to_string(),
+            Some(position) if position.status != PositionStatus::Open => {
+                "skipped: already settled".to_string()
+            }
+            Some(position) => {
+                settled_count += 1;
+
+                match settle_put(deps.branch(), position, settlement_price, env.block.height)?
+                    .status
+                {
+                    PositionStatus::Exercised => "exercised".to_string(),
+                    _ => "expired".to_string(),
+                }
+            }
+        };
+
+        results.push((format!("position_{}", position_id), result));
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("method", "batch_settle_puts".to_string()),
+            ("settlement_price", settlement_price.to_string()),
+            ("settled", settled_count.to_string()),
+            ("skipped", (results.len() - settled_count).to_string()),
+        ])
+        .add_attributes(results))
+}
+
+fn execute_batch_claim_collateral(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    position_ids: Vec<u64>,
+) -> Result<Response, ContractError> {
+    assert_batch(deps.as_ref(), &sender, &position_ids)?;
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+
+    let mut messages = vec![];
+    let mut results = vec![];
+
+    for position_id in position_ids {
+        let result = match may_get_position(deps.storage, position_id)? {
+            None => "skipped: not found".to_string(),
+            Some(position) if position.is_claimed => "skipped: already claimed".to_string(),
+            Some(position) => {
+                let owner = position.owner.clone();
+                let claimed_asset = claim_position(deps.branch(), &env, position)?;
+
+                messages.push(create_transfer_assets_msg(
+                    liquidity_pool.clone(),
+                    vec![claimed_asset.clone()],
+                    &owner,
+                )?);
+
+                format!("claimed: {}", claimed_asset)
+            }
+        };
+
+        results.push((format!("position_{}", position_id), result));
+    }
+
+    let claimed_count = messages.len();
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("method", "batch_claim_collateral".to_string()),
+            ("claimed", claimed_count.to_string()),
+            ("skipped", (results.len() - claimed_count).to_string()),
+        ])
+        .add_attributes(results))
+}
+
+fn execute_close_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    loan_id: u64,
+    repayment: Asset,
+) -> Result<Response, ContractError> {
+    assert_price_guards(deps.as_ref(), &env)?;
+
+    let mut loan = get_loan(deps.storage, loan_id)?;
+
+    if loan.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let EarlyCloseQuote {
+        interest_due,
+        amount,
+        collateral,
+        ..
+    } = get_early_close_quote(deps.as_ref(), &loan, env.block.height)?;
+
+    if !repayment.info.equal(&amount.info) {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    if repayment.amount < amount.amount {
+        return Err(ContractError::InsufficientRepayment {
+            repayment: repayment.amount,
+            amount_due: amount.amount,
+        });
+    }
+
+    repayment.assert_sent_native_token_balance(&info)?;
+
+    loan.status = LoanStatus::Closed;
+    set_loan(deps.storage, &loan)?;
+
+    // the pool gives up the premium it bought the option back for, while the protocol keeps the
+    // fee taken when the loan was taken out
+    let mut market_state = get_market_state(deps.storage)?;
+    market_state.loan_collateral = market_state
+        .loan_collateral
+        .checked_sub(collateral.amount)?;
+    market_state.total_premium = market_state
+        .total_premium
+        .saturating_sub(loan.interest.checked_sub(interest_due.amount)?);
+    set_market_state(deps.storage, market_state)?;
+    release_loan_notional(deps.storage, &loan, loan.notional)?;
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+
+    let mut messages = vec![
+        create_deposit_msg(&liquidity_pool, &amount, &info.sender)?,
+        create_transfer_assets_msg(liquidity_pool, vec![collateral.clone()], &info.sender)?,
+    ];
+    messages.extend(create_option_token_msg(
+        deps.storage,
+        &OptionTokenExecuteMsg::Burn {
+            token_id: get_option_token_id(loan.id),
+        },
+    )?);
+
+    // only native tokens are sent along with the message, tokens are transferred as due
+    if let AssetInfo::NativeToken { denom } = &repayment.info {
+        let excess = repayment.amount.checked_sub(amount.amount)?;
+
+        if !excess.is_zero() {
+            messages.push(
+                BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: coins(excess.u128(), denom),
+                }
+                .into(),
+            );
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("method", "close_loan".to_string()),
+        ("loan_id", loan_id.to_string()),
+        ("amount", amount.to_string()),
+        ("interest_due", interest_due.to_string()),
+        ("collateral", collateral.to_string()),
+    ]))
+}
+
+struct EarlyCloseQuote {
+    principal: Asset,
+    interest_due: Asset,
+    amount: Asset,
+    collateral: Asset,
+}
+
+/// Quotes closing an open loan at the given block. The pool buys the unexpired part of the
+/// borrower's option back at the current model price, but never for more than the time-prorated
+/// share of the interest paid for it, so the borrower owes the principal and the rest of the
+/// interest.
+fn get_early_close_quote(
+    deps: Deps,
+    loan: &Loan,
+    current_block_height: u64,
+) -> Result<EarlyCloseQuote, ContractError> {
+    if loan.status != LoanStatus::Open {
+        return Err(ContractError::LoanAlreadyRepaid { loan_id: loan.id });
+    }
+
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+    let collateral = Asset {
+        info: collateral_asset_info,
+        amount: loan.collateral_amount,
+    };
+
+    let amm_phase_ends_at = get_market_info(deps.storage)?.amm_phase_ends_at;
+    let loan_term = amm_phase_ends_at.saturating_sub(loan.created_at);
+    let remaining_term = amm_phase_ends_at.saturating_sub(current_block_height);
+
+    // rounding the unexpired interest down favours the pool
+    let unexpired_interest = if loan_term == 0 {
+        Uint128::zero()
+    } else {
+        loan.interest.multiply_ratio(remaining_term, loan_term)
+    };
+    let model_price = get_interest_cost(
+        deps,
+        collateral.clone(),
+        loan.strike_id.map(|_| loan.strike),
+        current_block_height,
+    )?;
+    let buyback_price = unexpired_interest.min(model_price);
+
+    let principal = loan.notional.checked_sub(loan.interest)?;
+    let interest_due = loan.interest.checked_sub(buyback_price)?;
+
+    Ok(EarlyCloseQuote {
+        principal: Asset {
+            info: lending_asset_info.clone(),
+            amount: principal,
+        },
+        interest_due: Asset {
+            info: lending_asset_info.clone(),
+            amount: interest_due,
+        },
+        amount: Asset {
+            info: lending_asset_info,
+            amount: principal.checked_add(interest_due)?,
+        },
+        collateral,
+    })
+}
+
+fn execute_exit_position(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    position_id: u64,
+    expected_return: Asset,
+) -> Result<Response, ContractError> {
+    assert_price_guards(deps.as_ref(), &env)?;
+
+    let mut position = get_position(deps.storage, position_id)?;
+
+    if position.owner != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let ExitQuote {
+        amount,
+        remaining_buyback_volume,
+        ..
+    } = get_exit_quote(deps.as_ref(), &position, env.block.height)?;
+
+    if let Some(remaining_buyback_volume) = remaining_buyback_volume {
+        if position.lent_amount > remaining_buyback_volume {
+            return Err(ContractError::BuybackCapExceeded {
+                lent_amount: position.lent_amount,
+                remaining_buyback_volume,
+            });
+        }
+    }
+
+    if expected_return.amount > amount.amount {
+        return Err(ContractError::Std(StdError::generic_err(format!(
+            "Expected return ({}) is higher than calculated return ({})",
+            &expected_return.amount, &amount.amount
+        ))));
+    }
+
+    position.status = PositionStatus::Exited;
+    position.is_claimed = true;
+    set_position(deps.storage, &position)?;
+
+    let mut market_state = get_market_state(deps.storage)?;
+    market_state.locked_lending = market_state
+        .locked_lending
+        .checked_sub(position.lent_amount)?;
+    market_state.buyback_volume = market_state
+        .buyback_volume
+        .checked_add(position.lent_amount)?;
+    market_state.put_collateral = market_state
+        .put_collateral
+        .checked_sub(position.collateral_amount)?;
+    set_market_state(deps.storage, market_state)?;
+    release_exposure(deps.storage, &sender, position.lent_amount)?;
+
+    let mut messages = vec![];
+
+    if !amount.amount.is_zero() {
+        messages.push(create_transfer_assets_msg(
+            get_liquidity_pool(deps.storage)?,
+            vec![amount.clone()],
+            &sender,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("method", "exit_position".to_string()),
+        ("position_id", position_id.to_string()),
+        ("amount", amount.to_string()),
+    ]))
+}
+
+struct ExitQuote {
+    lent: Asset,
+    option_value: Asset,
+    spread: Asset,
+    amount: Asset,
+    remaining_buyback_volume: Option<Uint128>,
+}
+
+/// Quotes selling an open PUT option back to the pool at the given block. The lent asset is netted
+/// against the current model value of the option and the pool keeps the buyback spread of the rest.
+fn get_exit_quote(
+    deps: Deps,
+    position: &Position,
+    current_block_height: u64,
+) -> Result<ExitQuote, ContractError> {
+    if position.status != PositionStatus::Open {
+        return Err(ContractError::PositionNotOpen {
+            position_id: position.id,
+        });
+    }
+
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+
+    let option_value = get_interest_cost(
+        deps,
+        Asset {
+            info: collateral_asset_info,
+            amount: position.collateral_amount,
+        },
+        Some(position.strike),
+        current_block_height,
+    )?
+    .min(position.lent_amount);
+    let netted_amount = position.lent_amount.checked_sub(option_value)?;
+    // rounding the returned amount down favours the pool
+    let amount = netted_amount * (Decimal::one() - get_buyback_spread(deps.storage)?);
+    let spread = netted_amount.checked_sub(amount)?;
+
+    let buyback_volume = get_market_state(deps.storage)?.buyback_volume;
+    let remaining_buyback_volume = get_max_buyback_volume(deps.storage)?
+        .map(|max_buyback_volume| max_buyback_volume.saturating_sub(buyback_volume));
+
+    Ok(ExitQuote {
+        lent: Asset {
+            info: lending_asset_info.clone(),
+            amount: position.lent_amount,
+        },
+        option_value: Asset {
+            info: lending_asset_info.clone(),
+            amount: option_value,
+        },
+        spread: Asset {
+            info: lending_asset_info.clone(),
+            amount: spread,
+        },
+        amount: Asset {
+            info: lending_asset_info,
+            amount,
+        },
+        remaining_buyback_volume,
+    })
+}
+
+/// Creates the message moving an asset from `owner` to the pool. Native tokens are expected to be
+/// sent along with the executed message, tokens are transferred using the allowance of the owner.
+fn create_deposit_msg(
+    liquidity_pool: &Addr,
+    asset: &Asset,
+    owner: &Addr,
+) -> Result<CosmosMsg, ContractError> {
+    Ok(match &asset.info {
+        AssetInfo::NativeToken { denom } => BankMsg::Send {
+            to_address: liquidity_pool.to_string(),
+            amount: coins(asset.amount.u128(), denom),
+        }
+        .into(),
+        AssetInfo::Token { contract_addr } => wasm_execute(
+            contract_addr,
+            &Cw20ExecuteMsg::TransferFrom {
+                owner: owner.to_string(),
+                recipient: liquidity_pool.to_string(),
+                amount: asset.amount,
+            },
+            vec![],
+        )?
+        .into(),
+    })
+}
+
+fn create_transfer_assets_msg(
+    liquidity_pool: Addr,
+    assets: Vec<Asset>,
+    recipient: &Addr,
+) -> Result<CosmosMsg, ContractError> {
+    Ok(wasm_execute(
+        liquidity_pool,
+        &cw_zll_std_liquidity_pool::msg::ExecuteMsg::TransferAssets {
+            assets,
+            recipient: recipient.to_string(),
+        },
+        vec![],
+    )?
+    .into())
+}
+
+/// The oracle price of the market compared with the price implied by the pool reserves.
+struct PriceGuards {
+    price: Decimal,
+    price_age: u64,
+    pool_price: Option<Decimal>,
+    price_deviation: Option<Decimal>,
+}
+
+/// Reads the oracle price and compares it with the price implied by the pool reserves.
+/// Returns `None` if the market has no oracle, as the configured price needs no guards.
+fn get_price_guards(deps: Deps, env: &Env) -> Result<Option<PriceGuards>, ContractError> {
+    if get_oracle(deps.storage)?.is_none() {
+        return Ok(None);
+    }
+
+    let CollateralPriceResponse {
+        price,
+        publish_time,
+        ..
+    } = get_market_collateral_price(deps)?;
+
+    let price_age = env
+        .block
+        .time
+        .seconds()
+        .saturating_sub(publish_time.unwrap_or_default());
+    let pool_price = get_pool_price(deps, env.block.height)?;
+
+    Ok(Some(PriceGuards {
+        price,
+        price_age,
+        pool_price,
+        price_deviation: pool_price.map(|pool_price| calculate_price_deviation(price, pool_price)),
+    }))
+}
+
+/// Rejects stale oracle prices and oracle prices too far from the price implied by the pool.
+/// Every action depending on the collateral price has to call it first.
+fn assert_price_guards(deps: Deps, env: &Env) -> Result<(), ContractError> {
+    let PriceGuards {
+        price,
+        price_age,
+        pool_price,
+        price_deviation,
+    } = match get_price_guards(deps, env)? {
+        Some(price_guards) => price_guards,
+        None => return Ok(()),
+    };
+
+    if let Some(max_price_age) = get_max_price_age(deps.storage)? {
+        if price_age > max_price_age {
+            return Err(ContractError::StalePrice {
+                price_age,
+                max_price_age,
+            });
+        }
+    }
+
+    if let (Some(max_price_deviation), Some(pool_price), Some(price_deviation)) = (
+        get_max_price_deviation(deps.storage)?,
+        pool_price,
+        price_deviation,
+    ) {
+        if price_deviation > max_price_deviation {
+            return Err(ContractError::PriceDeviationTooHigh {
+                price,
+                pool_price,
+                price_deviation,
+                max_price_deviation,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the amounts of the collateral and the lending asset held by the pool on behalf of option
+/// holders. The collateral pledged for loans not repaid by the end of the settlement phase belongs
+/// to the pool.
+fn get_locked_amounts(
+    deps: Deps,
+    current_block_height: u64,
+) -> Result<[Uint128; 2], ContractError> {
+    let market_state = get_market_state(deps.storage)?;
+    let mut locked_collateral = market_state.locked_collateral;
+
+    if get_current_market_phase(deps, current_block_height)? != MarketPhase::PostSettlement {
+        locked_collateral = locked_collateral.checked_add(market_state.loan_collateral)?;
+    }
+
+    // the protocol fees are held by the pool on behalf of the treasury
+    Ok([
+        locked_collateral,
+        market_state
+            .locked_lending
+            .checked_add(market_state.accrued_fees)?,
+    ])
+}
+
+/// Returns the pool reserves of the collateral and the lending asset the AMM trades with. The
+/// assets locked by the market are excluded, the same way the pool excludes them from the
+/// reserves it values, pays out and accumulates the prices for.
+fn get_trading_reserves(
+    deps: Deps,
+    current_block_height: u64,
+) -> Result<[Uint128; 2], ContractError> {
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+    let [locked_collateral, locked_lending] = get_locked_amounts(deps, current_block_height)?;
+
+    Ok([
+        collateral_asset_info
+            .query_pool(&deps.querier, liquidity_pool.clone())?
+            .checked_sub(locked_collateral)?,
+        lending_asset_info
+            .query_pool(&deps.querier, liquidity_pool)?
+            .checked_sub(locked_lending)?,
+    ])
+}
+
+/// Returns the price of the collateral asset implied by the pool reserves,
+/// or `None` while either reserve is empty.
+fn get_pool_price(deps: Deps, current_block_height: u64) -> Result<Option<Decimal>, ContractError> {
+    let [collateral_reserve, lending_reserve] = get_trading_reserves(deps, current_block_height)?;
+
+    if collateral_reserve.is_zero() || lending_reserve.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some(Decimal::from_ratio(
+        lending_reserve,
+        collateral_reserve,
+    )))
+}
+
+/// Calculates the deviation of `price` from `pool_price` relative to `pool_price`.
+fn calculate_price_deviation(price: Decimal, pool_price: Decimal) -> Decimal {
+    let difference = if price > pool_price {
+        price - pool_price
+    } else {
+        pool_price - price
+    };
+
+    Decimal::from_ratio(difference.numerator(), pool_price.numerator())
+}
+
+fn get_strike(storage: &dyn Storage, strike_id: u64) -> Result<Strike, ContractError> {
+    may_get_strike(storage, strike_id)?.ok_or(ContractError::StrikeNotFound { strike_id })
+}
+
+/// Calculates the terms of a loan against the pledged collateral. Without a strike the borrower
+/// gets what the pool would pay for the collateral in a swap, which makes the implied strike the
+/// execution price of the swap. A strike of the strike ladder values the collateral at its price
+/// instead, as long as the pool could pay that much for it.
+fn get_borrowing_terms(
+    deps: Deps,
+    pledged_collateral: Asset,
+    strike_id: Option<u64>,
+    account: Option<&Addr>,
+    current_block_height: u64,
+) -> Result<BorrowingTerms, ContractError> {
+    let lending_asset_info = get_lending_asset_info(deps.storage)?;
+    let swap_amount =
+        get_borrowable_amount(deps, pledged_collateral.clone(), current_block_height)?;
+
+    let (borrowable_amount, strike) = match strike_id {
+        Some(strike_id) => {
+            let strike = get_strike(deps.storage, strike_id)?;
+            // rounding the borrowed amount down favours the pool
+            let borrowable_amount = pledged_collateral.amount * strike.price;
+
+            if borrowable_amount > swap_amount {
+                return Err(ContractError::StrikeAboveMarket {
+                    strike_id,
+                    strike: strike.price,
+                });
+            }
+
+            (borrowable_amount, strike.price)
+        }
+        None if pledged_collateral.amount.is_zero() => (swap_amount, Decimal::zero()),
+        None => (
+            swap_amount,
+            Decimal::from_ratio(swap_amount, pledged_collateral.amount),
+        ),
+    };
+
+    // strikes of the strike ladder are priced at their moneyness, other loans at the money
+    let interest_amount = get_interest_cost(
+        deps,
+        pledged_collateral,
+        strike_id.map(|_| strike),
+        current_block_height,
+    )?;
+    let protocol_fee_amount = calculate_protocol_fee(deps.storage, interest_amount)?;
+
+    Ok(BorrowingTerms {
+        borrow: Asset {
+            info: lending_asset_info.clone(),
+            amount: borrowable_amount,
+        },
+        interest: Asset {
+            info: lending_asset_info.clone(),
+            amount: interest_amount,
+        },
+        // the borrower reclaims the collateral by repaying the whole borrowed amount
+        repayment: Asset {
+            info: lending_asset_info.clone(),
+            amount: borrowable_amount,
+        },
+        strike,
+        strike_id,
+        protocol_fee: Asset {
+            info: lending_asset_info,
+            amount: protocol_fee_amount,
+        },
+        remaining_capacity: get_remaining_capacity(
+            deps,
+            account,
+            Commitment::Loan,
+            current_block_height,
+        )?
+        .min(),
+    })
+}
+
+fn get_borrowable_amount(
+    deps: Deps,
+    pledged_collateral: Asset,
+    current_block_height: u64,
+) -> Result<Uint128, ContractError> {
+    let [collateral_ccy_supply, borrow_ccy_supply] =
+        get_trading_reserves(deps, current_block_height)?;
+    let amm_constant = collateral_ccy_supply.checked_mul(borrow_ccy_supply)?;
+
+    calculate_borrowable_amount(
+        borrow_ccy_supply,
+        amm_constant,
+        collateral_ccy_supply,
+        pledged_collateral.amount,
+    )
+}
+
+fn calculate_borrowable_amount(
+    borrow_ccy_supply: Uint128,
+    amm_constant: Uint128,
+    collateral_ccy_supply: Uint128,
+    collateral_amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    Ok(borrow_ccy_supply.checked_sub(
+        amm_constant.checked_div(collateral_ccy_supply.checked_add(collateral_amount)?)?,
+    )?)
+}
+
+/// Returns the price of the option on the given amount of the collateral asset, which the pool
+/// charges borrowers as interest, pays lenders as premium and buys options back at. Options
+/// without a strike are priced at the money.
+fn get_interest_cost(
+    deps: Deps,
+    pledged_collateral: Asset,
+    strike: Option<Decimal>,
+    current_block_height: u64,
+) -> Result<Uint128, ContractError> {
+    let ExpiryTime {
+        sqrt_time_to_expiry,
+        ..
+    } = get_expiry_time(deps.storage, current_block_height)?;
+
+    let oblivious_put_price =
+        get_oblivious_put_price(deps, strike, sqrt_time_to_expiry, current_block_height)?;
+
+    Ok(calculate_interest_cost(
+        oblivious_put_price,
+        pledged_collateral.amount,
+    ))
+}
+
+fn calculate_interest_cost(oblivious_put_price: Decimal, collateral_amount: Uint128) -> Uint128 {
+    collateral_amount * oblivious_put_price
+}
+
+fn get_expiry_time(
+    storage: &dyn Storage,
+    current_block_height: u64,
+) -> Result<ExpiryTime, ContractError> {
+    let MarketPhasesInfo {
+        amm_phase_ends_at, ..
+    } = get_market_info(storage)?;
+    let blocks_per_year = get_blocks_per_year(storage)?;
+
+    calculate_expiry_time(current_block_height, amm_phase_ends_at, blocks_per_year)
+}
+
+/// Calculates the time to the expiry of the options at the end of the AMM phase, in years.
+fn calculate_expiry_time(
+    current_block_height: u64,
+    amm_phase_ends_at: u64,
+    blocks_per_year: u64,
+) -> Result<ExpiryTime, ContractError> {
+    let remaining_blocks =
+        Uint128::from(amm_phase_ends_at).checked_sub(current_block_height.into())?;
+
+    if blocks_per_year == 0 {
+        return Err(StdError::generic_err("`blocks_per_year` must not be zero").into());
+    }
+
+    let time_to_expiry = Decimal::from_ratio(remaining_blocks, blocks_per_year);
+
+    Ok(ExpiryTime {
+        time_to_expiry,
+        sqrt_time_to_expiry: time_to_expiry.sqrt(),
+    })
+}
+
+/// Returns the price of a PUT option on one unit of the collateral asset in units of the lending
+/// asset, struck at the given strike or at the money. The price of the collateral is the market
+/// price, or the price implied by the pool reserves for markets without one, and its volatility
+/// is `alpha`.
+fn get_oblivious_put_price(
+    deps: Deps,
+    strike: Option<Decimal>,
+    sqrt_time_to_expiry: Decimal,
+    current_block_height: u64,
+) -> Result<Decimal, ContractError> {
+    let collateral_price = match get_current_collateral_price(deps) {
+        Ok(CollateralPriceResponse { price, .. }) => price,
+        Err(ContractError::CollateralPriceNotSet {}) => {
+            get_pool_price(deps, current_block_height)?.unwrap_or_default()
+        }
+        Err(error) => return Err(error),
+    };
+    let collateral_price_annualized_vol =
+        Decimal::from_ratio(get_alpha(deps.storage)?, ALPHA_PRECISION);
+
+    Ok(calculate_oblivious_put_price(
+        collateral_price,
+        strike.unwrap_or(collateral_price),
+        collateral_price_annualized_vol,
+        sqrt_time_to_expiry,
+    ))
+}
+
+/// The scale of `alpha`, the annualized volatility of the collateral price.
+const ALPHA_PRECISION: u128 = 1_000_000_000_000;
+
+/// 1 / sqrt(2 * pi)
+const INV_SQRT_2_PI: u128 = 398_942_280_401_433_000;
+
+/// The distance from the strike, in standard deviations, beyond which the time value of an option
+/// is negligible.
+const MAX_STANDARD_DEVIATIONS: u128 = 40;
+
+/// Prices a PUT option without interest rates in the normal (Bachelier) model, where the
+/// collateral price moves by `S * sigma * sqrt(T)` in one standard deviation. With
+/// `d = (K - S) / (S * sigma * sqrt(T))`, the price is `max(K - S, 0)` plus the time value
+/// `S * sigma * sqrt(T) * (phi(d) - |d| * N(-|d|))`, which depends on the moneyness `K / S` only
+/// and is `S * sigma * sqrt(T) / sqrt(2 * pi)` at the money. Keeps the pricing in fixed-point
+/// arithmetic.
+fn calculate_oblivious_put_price(
+    collateral_price: Decimal,
+    strike: Decimal,
+    collateral_price_annualized_vol: Decimal,
+    sqrt_time_to_expiry: Decimal,
+) -> Decimal {
+    let intrinsic_value = saturating_sub(strike, collateral_price);
+    let standard_deviation = decimal_mul(
+        decimal_mul(collateral_price, collateral_price_annualized_vol),
+        sqrt_time_to_expiry,
+    );
+
+    if standard_deviation.is_zero() {
+        return intrinsic_value;
+    }
+
+    let distance = if strike > collateral_price {
+        strike - collateral_price
+    } else {
+        collateral_price - strike
+    };
+
+    if distance
+        > decimal_mul(
+            standard_deviation,
+            Decimal::from_ratio(MAX_STANDARD_DEVIATIONS, 1u128),
+        )
+    {
+        return intrinsic_value;
+    }
+
+    let standard_deviations =
+        Decimal::from_ratio(distance.numerator(), standard_deviation.numerator());
+
+    intrinsic_value + decimal_mul(standard_deviation, normal_loss(standard_deviations))
+}
+
+/// Calculates `phi(x) - x * N(-x)` for `x >= 0`, where `phi` and `N` are the density and the
+/// cumulative distribution of the standard normal distribution.
+fn normal_loss(x: Decimal) -> Decimal {
+    let density = decimal_mul(
+        Decimal::from_ratio(INV_SQRT_2_PI, Decimal::one().numerator()),
+        exp_neg(decimal_mul(x, x) / Uint128::new(2)),
+    );
+    // N(-x) = erfc(x / sqrt(2)) / 2
+    let lower_tail = erfc(decimal_mul(
+        x,
+        Decimal::from_ratio(INV_SQRT_2, Decimal::one().numerator()),
+    )) / Uint128::new(2);
+
+    saturating_sub(density, decimal_mul(x, lower_tail))
+}
+
+/// 1 / sqrt(2)
+const INV_SQRT_2: u128 = 707_106_781_186_547_524;
+
+/// Approximates the complementary error function for `z >= 0` by the Chebyshev fitting of
+/// Numerical Recipes (erfcc), `t * e^(-z^2 + P(t))` with `t = 1 / (1 + z / 2)`, which is accurate
+/// to a relative 1.2e-7.
+fn erfc(z: Decimal) -> Decimal {
+    let t = Decimal::from_ratio(
+        Decimal::one().numerator(),
+        (Decimal::one() + z / Uint128::new(2)).numerator(),
+    );
+    // the coefficients of P(t), scaled by 10^8, from the constant term up
+    let coefficients: [(u128, bool); 10] = [
+        (126_551_223, false),
+        (100_002_368, true),
+        (37_409_196, true),
+        (9_678_418, true),
+        (18_628_806, false),
+        (27_886_807, true),
+        (113_520_398, false),
+        (148_851_587, true),
+        (82_215_223, false),
+        (17_087_277, true),
+    ];
+
+    // the exponent, summing its positive and negative terms apart
+    let (mut positive, mut negative) = (Decimal::zero(), decimal_mul(z, z));
+    let mut power = Decimal::one();
+    for (coefficient, is_positive) in coefficients.iter() {
+        let term = decimal_mul(Decimal::from_ratio(*coefficient, 100_000_000u128), power);
+        if *is_positive {
+            positive = positive + term;
+        } else {
+            negative = negative + term;
+        }
+        power = decimal_mul(power, t);
+    }
+
+    let exp = if negative > positive {
+        exp_neg(negative - positive)
+    } else {
+        Decimal::from_ratio(
+            Decimal::one().numerator(),
+            exp_neg(positive - negative).numerator(),
+        )
+    };
+
+    decimal_mul(t, exp)
+}
+
+/// Calculates `e^-y`, splitting `y` into its whole part, raised from `1 / e`, and its fraction,
+/// summed as a Taylor series.
+fn exp_neg(y: Decimal) -> Decimal {
+    // 1 / e
+    let inv_e = Decimal::from_ratio(367_879_441_171_442_322u128, Decimal::one().numerator());
+    let whole = y.numerator() / y.denominator();
+    let fraction = Decimal::from_ratio(y.numerator() % y.denominator(), y.denominator());
+
+    let mut exp_neg_whole = Decimal::one();
+    for _ in 0..whole {
+        exp_neg_whole = decimal_mul(exp_neg_whole, inv_e);
+        if exp_neg_whole.is_zero() {
+            return Decimal::zero();
+        }
+    }
+
+    // e^fraction, whose terms fall below the precision of a Decimal after 20 of them
+    let (mut exp_fraction, mut term) = (Decimal::one(), Decimal::one());
+    for n in 1..=20u128 {
+        term = decimal_mul(term, fraction) / Uint128::new(n);
+        exp_fraction = exp_fraction + term;
+    }
+
+    Decimal::from_ratio(exp_neg_whole.numerator(), exp_fraction.numerator())
+}
+
+fn decimal_mul(a: Decimal, b: Decimal) -> Decimal {
+    Decimal::from_ratio(Uint128::new(a.numerator()) * b, a.denominator())
+}
+
+fn saturating_sub(a: Decimal, b: Decimal) -> Decimal {
+    if a > b {
+        a - b
+    } else {
+        Decimal::zero()
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::GetMarketOperator {} => query_get_market_operator(deps),
+        QueryMsg::GetLiquidityPool {} => query_get_liquidity_pool(deps),
+        QueryMsg::GetPredecessor {} => query_get_predecessor(deps),
+        QueryMsg::GetOptionToken {} => query_get_option_token(deps),
+        QueryMsg::GetMarketPhase {} => query_get_market_phase(deps, env),
+        QueryMsg::GetMarketPhasesInfo {} => query_get_market_phases_info(deps),
+        QueryMsg::GetBorrowingTerms {
+            pledged_collateral,
+            strike_id,
+            account,
+        } => query_get_borrowing_terms(
+            deps,
+            pledged_collateral,
+            strike_id,
+            account,
+            env.block.height,
+        ),
+        QueryMsg::GetLendingTerms {
+            lent_asset,
+            account,
+        } => query_get_lending_terms(deps, lent_asset, account, env.block.height),
+        QueryMsg::GetTargetLiquidity {} => query_get_target_liquidity(deps),
+        QueryMsg::GetMinimumLiquidity {} => query_get_minimum_liquidity(deps),
+        QueryMsg::GetCollateralPrice {} => query_get_collateral_price(deps),
+        QueryMsg::GetPremiumEarned {} => query_get_premium_earned(deps),
+        QueryMsg::GetPriceGuards {} => query_get_price_guards(deps, env),
+        QueryMsg::GetSettlementPrice {} => query_get_settlement_price(deps),
+        QueryMsg::GetPosition { position_id } => query_get_position(deps, position_id),
+        QueryMsg::GetSettlementProgress {} => query_get_settlement_progress(deps),
+        QueryMsg::GetLockedAssets {} => query_get_locked_assets(deps, env.block.height),
+        QueryMsg::GetCommittedCollateral {} => query_get_committed_collateral(deps),
+        QueryMsg::GetOutstandingLoans {} => query_get_outstanding_loans(deps, env.block.height),
+        QueryMsg::GetLoan { loan_id } => query_get_loan(deps, loan_id),
+        QueryMsg::GetEarlyCloseQuote { loan_id } => {
+            query_get_early_close_quote(deps, loan_id, env.block.height)
+        }
+        QueryMsg::GetExitQuote { position_id } => {
+            query_get_exit_quote(deps, position_id, env.block.height)
+        }
+        QueryMsg::GetStrikes {} => query_get_strikes(deps),
+        QueryMsg::GetProtocolFees {} => query_get_protocol_fees(deps),
+        QueryMsg::GetCapacityLimits {} => query_get_capacity_limits(deps),
+        QueryMsg::GetPausedActions {} => query_get_paused_actions(deps),
+        QueryMsg::GetPendingUpdate {} => query_get_pending_update(deps),
+        QueryMsg::GetAllowlist {} => query_get_allowlist(deps),
+        QueryMsg::GetAllowlistedAccounts { start_after, limit } => {
+            query_get_allowlisted_accounts(deps, start_after, limit)
+        }
+        QueryMsg::IsAllowed { role, address } => query_is_allowed(deps, role, address),
+    }
+}
+
+fn query_get_price_guards(deps: Deps, env: Env) -> Result<Binary, ContractError> {
+    let max_price_age = get_max_price_age(deps.storage)?;
+    let max_price_deviation = get_max_price_deviation(deps.storage)?;
+
+    let response = match get_price_guards(deps, &env)? {
+        Some(PriceGuards {
+            price,
+            price_age,
+            pool_price,
+            price_deviation,
+        }) => PriceGuardsResponse {
+            max_price_age,
+            max_price_deviation,
+            price: Some(price),
+            price_age: Some(price_age),
+            pool_price,
+            price_deviation,
+            is_price_stale: max_price_age
+                .map(|max_price_age| price_age > max_price_age)
+                .unwrap_or(false),
+            is_price_deviation_too_high: max_price_deviation
+                .zip(price_deviation)
+                .map(|(max_price_deviation, price_deviation)| price_deviation > max_price_deviation)
+                .unwrap_or(false),
+        },
+        None => PriceGuardsResponse {
+            max_price_age,
+            max_price_deviation,
+            price: None,
+            price_age: None,
+            pool_price: None,
+            price_deviation: None,
+            is_price_stale: false,
+            is_price_deviation_too_high: false,
+        },
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_position(deps: Deps, position_id: u64) -> Result<Binary, ContractError> {
+    let response = PositionResponse {
+        position: get_position(deps.storage, position_id)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_loan(deps: Deps, loan_id: u64) -> Result<Binary, ContractError> {
+    let response = LoanResponse {
+        loan: get_loan(deps.storage, loan_id)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_early_close_quote(
+    deps: Deps,
+    loan_id: u64,
+    current_block_height: u64,
+) -> Result<Binary, ContractError> {
+    let EarlyCloseQuote {
+        principal,
+        interest_due,
+        amount,
+        collateral,
+    } = get_early_close_quote(
+        deps,
+        &get_loan(deps.storage, loan_id)?,
+        current_block_height,
+    )?;
+
+    let response = EarlyCloseQuoteResponse {
+        principal,
+        interest_due,
+        amount,
+        collateral,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_exit_quote(
+    deps: Deps,
+    position_id: u64,
+    current_block_height: u64,
+) -> Result<Binary, ContractError> {
+    let ExitQuote {
+        lent,
+        option_value,
+        spread,
+        amount,
+        remaining_buyback_volume,
+    } = get_exit_quote(
+        deps,
+        &get_position(deps.storage, position_id)?,
+        current_block_height,
+    )?;
+
+    let response = ExitQuoteResponse {
+        lent,
+        option_value,
+        spread,
+        amount,
+        remaining_buyback_volume,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_settlement_progress(deps: Deps) -> Result<Binary, ContractError> {
+    let SettlementProgress {
+        last_position_id,
+        exercised_count,
+        expired_count,
+    } = get_settlement_progress(deps.storage)?;
+    let positions_count = get_market_state(deps.storage)?.positions_count;
+
+    let response = SettlementProgressResponse {
+        last_position_id,
+        positions_count,
+        exercised_count,
+        expired_count,
+        is_complete: last_position_id.unwrap_or_default() >= positions_count,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_locked_assets(deps: Deps, current_block_height: u64) -> Result<Binary, ContractError> {
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+    let [locked_collateral, locked_lending] = get_locked_amounts(deps, current_block_height)?;
+
+    let response = LockedAssetsResponse {
+        locked_assets: [
+            Asset {
+                info: collateral_asset_info,
+                amount: locked_collateral,
+            },
+            Asset {
+                info: lending_asset_info,
+                amount: locked_lending,
+            },
+        ],
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_committed_collateral(deps: Deps) -> Result<Binary, ContractError> {
+    let response = CommittedCollateralResponse {
+        committed_collateral: Asset {
+            info: get_asset_infos(deps.storage)?[0].clone(),
+            amount: get_market_state(deps.storage)?.put_collateral,
+        },
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_outstanding_loans(
+    deps: Deps,
+    current_block_height: u64,
+) -> Result<Binary, ContractError> {
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+    let market_state = get_market_state(deps.storage)?;
+
+    // the collateral of the loans left unrepaid after the settlement phase is part of the reserves
+    let (collateral, notional) =
+        if get_current_market_phase(deps, current_block_height)? == MarketPhase::PostSettlement {
+            (Uint128::zero(), Uint128::zero())
+        } else {
+            (market_state.loan_collateral, market_state.borrowed_notional)
+        };
+
+    let response = OutstandingLoansResponse {
+        collateral: Asset {
+            info: collateral_asset_info,
+            amount: collateral,
+        },
+        notional: Asset {
+            info: lending_asset_info,
+            amount: notional,
+        },
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_settlement_price(deps: Deps) -> Result<Binary, ContractError> {
+    let response = SettlementPriceResponse {
+        settlement_price: get_settlement_price(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_collateral_price(deps: Deps) -> Result<Binary, ContractError> {
+    let response = get_current_collateral_price(deps)?;
+
+    Ok(to_binary(&response)?)
+}
+
+/// Returns the settlement price once it is captured, otherwise the current market price.
+fn get_current_collateral_price(deps: Deps) -> Result<CollateralPriceResponse, ContractError> {
+    if let Some(SettlementPrice {
+        price,
+        publish_time,
+        confidence,
+        ..
+    }) = get_settlement_price(deps.storage)?
+    {
+        return Ok(CollateralPriceResponse {
+            price,
+            source: PriceSource::Settlement,
+            publish_time,
+            confidence,
+        });
+    }
+
+    get_market_collateral_price(deps)
+}
+
+/// Returns the collateral price as of `amm_observed_at`, the end of the AMM phase. The oracle price
+/// is the latest one published by then, which is the current oracle price unless it was published
+/// later, otherwise the one the market observed by then. It is only accepted if it is not stale at
+/// that time and not too far from the pool price observed by then, otherwise the TWAP of the pool
+/// until then is used if a TWAP window is configured. Without an oracle it returns the TWAP until
+/// then, or the configured collateral price.
+fn get_amm_end_collateral_price(
+    deps: Deps,
+    market_state: &MarketState,
+) -> Result<CollateralPriceResponse, ContractError> {
+    let twap_window = get_twap_window(deps.storage)?;
+    let amm_observed_at = market_state.amm_observed_at;
+
+    if let Some(oracle) = get_oracle(deps.storage)? {
+        let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+        let observed_price = market_state.amm_oracle_price.clone();
+        let is_published_since_observed = |publish_time: u64| match &observed_price {
+            Some(observed_price) => observed_price.publish_time <= publish_time,
+            None => true,
+        };
+
+        let oracle_price = match query_price(
+            &deps.querier,
+            oracle,
+            collateral_asset_info,
+            lending_asset_info,
+        ) {
+            Ok(PriceResponse {
+                price,
+                publish_time,
+                confidence,
+            }) if publish_time <= amm_observed_at && is_published_since_observed(publish_time) => {
+                Some(ObservedPrice {
+                    price,
+                    publish_time,
+                    confidence,
+                    pool_price: observed_price.and_then(|observed_price| observed_price.pool_price),
+                })
+            }
+            _ => observed_price,
+        };
+
+        let observation = match &oracle_price {
+            Some(oracle_price) => assert_observed_by(deps, oracle_price, amm_observed_at),
+            None => Err(ContractError::OraclePriceNotObserved { amm_observed_at }),
+        };
+
+        match observation {
+            Ok(ObservedPrice {
+                price,
+                publish_time,
+                confidence,
+                ..
+            }) => {
+                return Ok(CollateralPriceResponse {
+                    price,
+                    source: PriceSource::Oracle,
+                    publish_time: Some(publish_time),
+                    confidence: Some(confidence),
+                })
+            }
+            Err(error) if twap_window.is_none() => return Err(error),
+            Err(_) => {}
+        }
+    }
+
+    if let Some(window) = twap_window {
+        // The collateral asset is the first asset of the pool
+        let TwapResponse { price0_average, .. } = deps.querier.query_wasm_smart(
+            get_liquidity_pool(deps.storage)?,
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Twap {
+                window,
+                end: Some(amm_observed_at),
+            },
+        )?;
+
+        return Ok(CollateralPriceResponse {
+            price: price0_average,
+            source: PriceSource::Twap,
+            publish_time: None,
+            confidence: None,
+        });
+    }
+
+    Ok(CollateralPriceResponse {
+        price: get_collateral_price(deps.storage)?
+            .ok_or(ContractError::CollateralPriceNotSet {})?,
+        source: PriceSource::Config,
+        publish_time: None,
+        confidence: None,
+    })
+}
+
+/// Rejects an oracle price which is stale at `amm_observed_at` or too far from the price implied by
+/// the pool reserves observed by then.
+fn assert_observed_by(
+    deps: Deps,
+    oracle_price: &ObservedPrice,
+    amm_observed_at: u64,
+) -> Result<ObservedPrice, ContractError> {
+    let price_age = amm_observed_at.saturating_sub(oracle_price.publish_time);
+
+    if let Some(max_price_age) = get_max_price_age(deps.storage)? {
+        if price_age > max_price_age {
+            return Err(ContractError::StalePrice {
+                price_age,
+                max_price_age,
+            });
+        }
+    }
+
+    if let (Some(max_price_deviation), Some(pool_price)) = (
+        get_max_price_deviation(deps.storage)?,
+        oracle_price.pool_price,
+    ) {
+        let price = oracle_price.price;
+        let price_deviation = calculate_price_deviation(price, pool_price);
+
+        if price_deviation > max_price_deviation {
+            return Err(ContractError::PriceDeviationTooHigh {
+                price,
+                pool_price,
+                price_deviation,
+                max_price_deviation,
+            });
+        }
+    }
+
+    Ok(oracle_price.clone())
+}
+
+/// Queries the oracle price of the market, if it has an oracle reporting a price, along with the
+/// price implied by the pool reserves at `current_block_height` when it is given.
+fn observe_oracle_price(
+    deps: Deps,
+    current_block_height: Option<u64>,
+) -> Result<Option<ObservedPrice>, ContractError> {
+    let oracle = match get_oracle(deps.storage)? {
+        Some(oracle) => oracle,
+        None => return Ok(None),
+    };
+    let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+
+    // a failing oracle must not block the market, which keeps the price observed before
+    let PriceResponse {
+        price,
+        publish_time,
+        confidence,
+    } = match query_price(
+        &deps.querier,
+        oracle,
+        collateral_asset_info,
+        lending_asset_info,
+    ) {
+        Ok(response) => response,
+        Err(_) => return Ok(None),
+    };
+
+    let pool_price = match current_block_height {
+        Some(current_block_height) => get_pool_price(deps, current_block_height)?,
+        None => None,
+    };
+
+    Ok(Some(ObservedPrice {
+        price,
+        publish_time,
+        confidence,
+        pool_price,
+    }))
+}
+
+/// Returns the collateral price reported by the oracle of the market. Without an oracle it returns
+/// the TWAP of the pool if a TWAP window is configured, otherwise the configured collateral price.
+fn get_market_collateral_price(deps: Deps) -> Result<CollateralPriceResponse, ContractError> {
+    if let Some(oracle) = get_oracle(deps.storage)? {
+        let [collateral_asset_info, lending_asset_info] = get_asset_infos(deps.storage)?;
+
+        let PriceResponse {
+            price,
+            publish_time,
+            confidence,
+        } = query_price(
+            &deps.querier,
+            oracle,
+            collateral_asset_info,
+            lending_asset_info,
+        )?;
+
+        return Ok(CollateralPriceResponse {
+            price,
+            source: PriceSource::Oracle,
+            publish_time: Some(publish_time),
+            confidence: Some(confidence),
+        });
+    }
+
+    if let Some(window) = get_twap_window(deps.storage)? {
+        // The collateral asset is the first asset of the pool
+        let TwapResponse { price0_average, .. } = deps.querier.query_wasm_smart(
+            get_liquidity_pool(deps.storage)?,
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Twap { window, end: None },
+        )?;
+
+        return Ok(CollateralPriceResponse {
+            price: price0_average,
+            source: PriceSource::Twap,
+            publish_time: None,
+            confidence: None,
+        });
+    }
+
+    Ok(CollateralPriceResponse {
+        price: get_collateral_price(deps.storage)?
+            .ok_or(ContractError::CollateralPriceNotSet {})?,
+        source: PriceSource::Config,
+        publish_time: None,
+        confidence: None,
+    })
+}
+
+fn query_get_premium_earned(deps: Deps) -> Result<Binary, ContractError> {
+    let response = PremiumEarnedResponse {
+        premium: Asset {
+            info: get_lending_asset_info(deps.storage)?,
+            amount: get_market_state(deps.storage)?.total_premium,
+        },
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_paused_actions(deps: Deps) -> Result<Binary, ContractError> {
+    let response = PausedActionsResponse {
+        guardian: get_guardian(deps.storage)?,
+        paused_actions: get_paused_actions(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_allowlist(deps: Deps) -> Result<Binary, ContractError> {
+    let response = AllowlistResponse {
+        allowlist: get_allowlist(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+/// The default number of allowlisted accounts returned by a query.
+const DEFAULT_ALLOWLIST_LIMIT: u32 = 10;
+/// The maximum number of allowlisted accounts returned by a query.
+const MAX_ALLOWLIST_LIMIT: u32 = 100;
+
+fn query_get_allowlisted_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Binary, ContractError> {
+    let start_after = start_after
+        .map(|account| deps.api.addr_validate(&account))
+        .transpose()?;
+    let limit = limit
+        .unwrap_or(DEFAULT_ALLOWLIST_LIMIT)
+        .min(MAX_ALLOWLIST_LIMIT) as usize;
+
+    let response = AllowlistedAccountsResponse {
+        accounts: get_allowlisted_accounts(deps.storage, start_after, limit)?
+            .into_iter()
+            .map(|(address, roles)| AllowlistedAccount { address, roles })
+            .collect(),
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_pending_update(deps: Deps) -> Result<Binary, ContractError> {
+    let response = PendingUpdateResponse {
+        timelock_delay: get_timelock_delay(deps.storage)?,
+        pending_update: may_get_pending_update(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_is_allowed(
+    deps: Deps,
+    role: AllowlistRole,
+    address: String,
+) -> Result<Binary, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+
+    let response = IsAllowedResponse {
+        is_allowed: is_allowlisted(deps, role, &address)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_capacity_limits(deps: Deps) -> Result<Binary, ContractError> {
+    let response = CapacityLimitsResponse {
+        capacity_limits: get_capacity_limits(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_protocol_fees(deps: Deps) -> Result<Binary, ContractError> {
+    let lending_asset_info = get_lending_asset_info(deps.storage)?;
+    let market_state = get_market_state(deps.storage)?;
+
+    let response = ProtocolFeesResponse {
+        protocol_fee_rate: get_protocol_fee_rate(deps.storage)?,
+        treasury: get_treasury(deps.storage)?,
+        accrued_fees: Asset {
+            info: lending_asset_info.clone(),
+            amount: market_state.accrued_fees,
+        },
+        collected_fees: Asset {
+            info: lending_asset_info,
+            amount: market_state.collected_fees,
+        },
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_minimum_liquidity(deps: Deps) -> Result<Binary, ContractError> {
+    let lending_asset_info = get_lending_asset_info(deps.storage)?;
+
+    let response = MinimumLiquidityResponse {
+        minimum_liquidity: get_minimum_liquidity(deps.storage)?.map(|amount| Asset {
+            info: lending_asset_info,
+            amount,
+        }),
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_target_liquidity(deps: Deps) -> Result<Binary, ContractError> {
+    let lending_asset_info = get_lending_asset_info(deps.storage)?;
+
+    let response = TargetLiquidityResponse {
+        target_liquidity: get_target_liquidity(deps.storage)?.map(|amount| Asset {
+            info: lending_asset_info,
+            amount,
+        }),
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_market_phase(deps: Deps, env: Env) -> Result<Binary, ContractError> {
+    let response = MarketPhaseResponse {
+        phase: get_current_market_phase(deps, env.block.height)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_market_phases_info(deps: Deps) -> Result<Binary, ContractError> {
+    let response: MarketPhasesInfoResponse = get_market_info(deps.storage)?.into();
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_market_operator(deps: Deps) -> Result<Binary, ContractError> {
+    let response = MarketOperatorResponse {
+        market_operator: get_market_operator(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_liquidity_pool(deps: Deps) -> Result<Binary, ContractError> {
+    let response = LiquidityPoolResponse {
+        liquidity_pool: get_liquidity_pool(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_predecessor(deps: Deps) -> Result<Binary, ContractError> {
+    let response = PredecessorResponse {
+        predecessor: get_predecessor(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_option_token(deps: Deps) -> Result<Binary, ContractError> {
+    let response = OptionTokenResponse {
+        option_token: may_get_option_token(deps.storage)?,
+    };
+
+    Ok(to_binary(&response)?)
+}
+
+fn get_current_market_phase(
+    deps: Deps,
     current_block_height: u64,
 ) -> Result<MarketPhase, ContractError> {
-    let market_phases_info = get_market_info(storage)?;
+    let market_phases_info = get_market_info(deps.storage)?;
+    let market_state = get_market_state(deps.storage)?;
+
+    if market_state.is_cancelled {
+        return Ok(MarketPhase::Cancelled);
+    }
 
     if current_block_height <= market_phases_info.lp_phase_ends_at {
         return Ok(MarketPhase::ProvidingLiquidity);
     }
 
+    // until the check is recorded, the phase reflects the outcome it would have now
+    if !market_state.is_liquidity_checked
+        && !market_state.has_positions()
+        && !has_minimum_liquidity(deps)?
+    {
+        return Ok(MarketPhase::Cancelled);
+    }
+
     if current_block_height <= market_phases_info.amm_phase_ends_at {
         return Ok(MarketPhase::AutomatedMarketMaker);
     }
@@ -346,13 +3844,89 @@ fn get_current_market_phase(
     Ok(MarketPhase::PostSettlement)
 }
 
+/// Records whether the market has the minimum liquidity the first time the market is called after
+/// the end of the LP phase and returns the current phase. Once recorded, changes of the pool
+/// balance neither cancel the market nor reverse its cancellation. Until the end of the AMM phase it
+/// also records the block time of the call and the oracle price, which the settlement price is
+/// taken as of.
+fn record_market_phase(deps: DepsMut, env: &Env) -> Result<MarketPhase, ContractError> {
+    let current_block_height = env.block.height;
+    let market_state = get_market_state(deps.storage)?;
+    let market_info = get_market_info(deps.storage)?;
+
+    if !market_state.is_cancelled
+        && !market_state.is_liquidity_checked
+        && current_block_height > market_info.lp_phase_ends_at
+    {
+        let has_minimum_liquidity =
+            market_state.has_positions() || has_minimum_liquidity(deps.as_ref())?;
+
+        record_liquidity_check(deps.storage, has_minimum_liquidity)?;
+    }
+
+    if current_block_height <= market_info.amm_phase_ends_at {
+        let oracle_price = observe_oracle_price(deps.as_ref(), Some(current_block_height))?;
+
+        record_amm_observation(deps.storage, env.block.time.seconds(), oracle_price)?;
+    }
+
+    get_current_market_phase(deps.as_ref(), current_block_height)
+}
+
+/// Checks whether the pool holds the minimum liquidity of the lending asset required by the market.
+fn has_minimum_liquidity(deps: Deps) -> Result<bool, ContractError> {
+    let minimum_liquidity = match get_minimum_liquidity(deps.storage)? {
+        Some(minimum_liquidity) => minimum_liquidity,
+        None => return Ok(true),
+    };
+
+    let liquidity = get_lending_asset_info(deps.storage)?
+        .query_pool(&deps.querier, get_liquidity_pool(deps.storage)?)?;
+
+    Ok(liquidity >= minimum_liquidity)
+}
+
+fn query_get_lending_terms(
+    deps: Deps,
+    lent_asset: Asset,
+    account: Option<String>,
+    current_block_height: u64,
+) -> Result<Binary, ContractError> {
+    let account = account
+        .map(|account| deps.api.addr_validate(&account))
+        .transpose()?;
+    let response: LendingTermsResponse =
+        get_lending_terms(deps, lent_asset, account.as_ref(), current_block_height)?.into();
+
+    Ok(to_binary(&response)?)
+}
+
 fn query_get_borrowing_terms(
     deps: Deps,
     pledged_collateral: Asset,
+    strike_id: Option<u64>,
+    account: Option<String>,
     current_block_height: u64,
 ) -> Result<Binary, ContractError> {
-    let response: BorrowingTermsResponse =
-        get_borrowing_terms(deps, pledged_collateral, current_block_height)?.into();
+    let account = account
+        .map(|account| deps.api.addr_validate(&account))
+        .transpose()?;
+    let response: BorrowingTermsResponse = get_borrowing_terms(
+        deps,
+        pledged_collateral,
+        strike_id,
+        account.as_ref(),
+        current_block_height,
+    )?
+    .into();
+
+    Ok(to_binary(&response)?)
+}
+
+fn query_get_strikes(deps: Deps) -> Result<Binary, ContractError> {
+    let response = StrikesResponse {
+        strikes: get_strikes(deps.storage)?,
+    };
 
     Ok(to_binary(&response)?)
 }
@@ -367,6 +3941,8 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 
     match msg.id {
         INSTANTIATE_LIQUIDITY_POOL_REPLY_ID => reply_on_instantiate_liquidity_pool(deps, env, msg),
+        INSTANTIATE_OPTION_TOKEN_REPLY_ID => reply_on_instantiate_option_token(deps, msg),
+        BORROW_FOR_ROLLOVER_REPLY_ID => reply_on_borrow_for_rollover(deps, env),
         _ => Err(ContractError::Std(StdError::GenericErr {
             msg: format!("reply id `{:?}` is invalid", msg.id),
         })),
@@ -391,3 +3967,92 @@ fn reply_on_instantiate_liquidity_pool(
 
     Ok(Response::new().add_attribute("liquidity_pool_addr", liquidity_pool))
 }
+
+fn reply_on_instantiate_option_token(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    if may_get_option_token(deps.storage)?.is_some() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let response: MsgInstantiateContractResponse = parse_reply_instantiate_data(msg)?;
+    let option_token = deps.api.addr_validate(&response.contract_address)?;
+
+    set_option_token(deps.storage, &option_token)?;
+
+    Ok(Response::new().add_attribute("option_token_addr", option_token))
+}
+
+fn reply_on_borrow_for_rollover(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let PendingLoanRollover {
+        loan_id,
+        repayment,
+        balance,
+    } = may_get_pending_loan_rollover(deps.storage)?.ok_or(ContractError::Unauthorized {})?;
+
+    remove_pending_loan_rollover(deps.storage);
+
+    let repayment = Asset {
+        info: get_lending_asset_info(deps.storage)?,
+        amount: repayment,
+    };
+    let received = repayment
+        .info
+        .query_pool(&deps.querier, env.contract.address)?
+        .saturating_sub(balance);
+
+    if received < repayment.amount {
+        return Err(ContractError::LoanRolloverNotRepaid { loan_id });
+    }
+
+    let liquidity_pool = get_liquidity_pool(deps.storage)?;
+    let deposit_msg: CosmosMsg = match &repayment.info {
+        AssetInfo::NativeToken { denom } => BankMsg::Send {
+            to_address: liquidity_pool.to_string(),
+            amount: coins(repayment.amount.u128(), denom),
+        }
+        .into(),
+        AssetInfo::Token { contract_addr } => wasm_execute(
+            contract_addr,
+            &Cw20ExecuteMsg::Transfer {
+                recipient: liquidity_pool.to_string(),
+                amount: repayment.amount,
+            },
+            vec![],
+        )?
+        .into(),
+    };
+
+    Ok(Response::new()
+        .add_message(deposit_msg)
+        .add_attributes(vec![
+            ("loan_id", loan_id.to_string()),
+            ("repaid", repayment.to_string()),
+        ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = assert_migration(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let querier = &deps.querier;
+    let is_config_migrated = migrate_config(deps.storage, MIN_TIMELOCK_DELAY, |liquidity_pool| {
+        let PairInfo { asset_infos, .. } = querier.query_wasm_smart(
+            liquidity_pool,
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pair {},
+        )?;
+        Ok(asset_infos)
+    })?;
+    let is_market_state_migrated = migrate_market_state(deps.storage, env.block.time.seconds())?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "migrate".to_string()),
+        ("previous_version", previous_version.to_string()),
+        ("version", CONTRACT_VERSION.to_string()),
+        ("is_config_migrated", is_config_migrated.to_string()),
+        (
+            "is_market_state_migrated",
+            is_market_state_migrated.to_string(),
+        ),
+    ]))
+}
@@ -1,5 +1,6 @@
-use cosmwasm_std::{DivideByZeroError, OverflowError, StdError};
-use cw_zll_std_utils::reply::ParseReplyError;
+use cosmwasm_std::{Decimal, DivideByZeroError, OverflowError, StdError, Uint128};
+use cw_zll_std_market::state::{AllowlistRole, PausableAction};
+use cw_zll_std_utils::{migration::MigrationError, reply::ParseReplyError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +11,9 @@ pub enum ContractError {
     #[error("{0}")]
     ParseReply(#[from] ParseReplyError),
 
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
+
     #[error("{0}")]
     OverflowError(#[from] OverflowError),
 
@@ -21,4 +25,149 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Collateral price is not configured for the market")]
+    CollateralPriceNotSet {},
+
+    #[error(
+        "Oracle price is stale: published {price_age}s ago, the maximum age is {max_price_age}s"
+    )]
+    StalePrice { price_age: u64, max_price_age: u64 },
+
+    #[error("Oracle price ({price}) deviates from the pool price ({pool_price}) by {price_deviation}, the maximum deviation is {max_price_deviation}")]
+    PriceDeviationTooHigh {
+        price: Decimal,
+        pool_price: Decimal,
+        price_deviation: Decimal,
+        max_price_deviation: Decimal,
+    },
+
+    #[error("No oracle price was observed by the end of the AMM phase at {amm_observed_at}")]
+    OraclePriceNotObserved { amm_observed_at: u64 },
+
+    #[error("Settlement price has already been captured")]
+    SettlementPriceAlreadyCaptured {},
+
+    #[error("Asset does not match the market asset")]
+    AssetMismatch {},
+
+    #[error("Amount must not be zero")]
+    InvalidZeroAmount {},
+
+    #[error("Position {position_id} has already been claimed")]
+    PositionAlreadyClaimed { position_id: u64 },
+
+    #[error("Loan {loan_id} has already been repaid")]
+    LoanAlreadyRepaid { loan_id: u64 },
+
+    #[error("Repayment ({repayment}) must be greater than zero and must not exceed the notional of the loan ({notional})")]
+    InvalidRepayment {
+        repayment: Uint128,
+        notional: Uint128,
+    },
+
+    #[error("Repayment ({repayment}) is lower than the amount due ({amount_due})")]
+    InsufficientRepayment {
+        repayment: Uint128,
+        amount_due: Uint128,
+    },
+
+    #[error("Loan proceeds ({proceeds}) do not cover the repayment of the rolled over loan ({repayment})")]
+    InsufficientRolloverProceeds {
+        proceeds: Uint128,
+        repayment: Uint128,
+    },
+
+    #[error("Successor market has not repaid loan {loan_id}")]
+    LoanRolloverNotRepaid { loan_id: u64 },
+
+    #[error("Market {market} does not succeed this market")]
+    InvalidSuccessor { market: String },
+
+    #[error("Position {position_id} is not open")]
+    PositionNotOpen { position_id: u64 },
+
+    #[error("Buying back {lent_amount} exceeds the remaining buyback volume of the market ({remaining_buyback_volume})")]
+    BuybackCapExceeded {
+        lent_amount: Uint128,
+        remaining_buyback_volume: Uint128,
+    },
+
+    #[error("Strike {strike_id} does not exist")]
+    StrikeNotFound { strike_id: u64 },
+
+    #[error("Strike {strike_id} ({strike}) values the collateral above the amount the pool can lend against it")]
+    StrikeAboveMarket { strike_id: u64, strike: Decimal },
+
+    #[error("Borrowing {borrow} exceeds the remaining capacity of strike {strike_id} ({remaining_capacity})")]
+    StrikeCapacityExceeded {
+        strike_id: u64,
+        borrow: Uint128,
+        remaining_capacity: Uint128,
+    },
+
+    #[error("Treasury is not configured for the market")]
+    TreasuryNotSet {},
+
+    #[error("There are no protocol fees to collect")]
+    NoFeesToCollect {},
+
+    #[error("Position size {size} exceeds the maximum position size of {max_position_size}")]
+    PositionSizeExceeded {
+        size: Uint128,
+        max_position_size: Uint128,
+    },
+
+    #[error(
+        "Position size {size} exceeds the remaining exposure of the account ({remaining_exposure})"
+    )]
+    AccountExposureExceeded {
+        size: Uint128,
+        remaining_exposure: Uint128,
+    },
+
+    #[error("Position size {size} exceeds the remaining capacity under the maximum pool utilization ({remaining_capacity})")]
+    UtilizationExceeded {
+        size: Uint128,
+        remaining_capacity: Uint128,
+    },
+
+    #[error("An update of the market is already queued")]
+    UpdateAlreadyQueued {},
+
+    #[error("There is no queued update of the market")]
+    NoPendingUpdate {},
+
+    #[error("Queued update of the market cannot be applied before block {executable_at}")]
+    UpdateTimelocked { executable_at: u64 },
+
+    #[error("Capacity limits can only be loosened by a queued update")]
+    CapacityLimitsLoosened {},
+
+    #[error("Account {address} is not allowlisted as a {role}")]
+    NotAllowlisted {
+        role: AllowlistRole,
+        address: String,
+    },
+
+    #[error("Action `{action}` is paused by the guardian")]
+    ActionPaused { action: PausableAction },
+
+    #[error("Batch of {batch_size} positions exceeds the maximum batch size of {max_batch_size}")]
+    BatchTooLarge {
+        batch_size: u64,
+        max_batch_size: u64,
+    },
+
+    #[error("Market cannot be cancelled once borrowing or lending has started")]
+    MarketHasBorrowings {},
+
+    #[error("Target liquidity is not configured for the market")]
+    TargetLiquidityNotSet {},
+
+    #[error("Liquidity ({liquidity}) has not reached the target liquidity ({target_liquidity})")]
+    TargetLiquidityNotReached {
+        liquidity: Uint128,
+        target_liquidity: Uint128,
+    },
 }
@@ -0,0 +1,895 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    attr, coin, from_binary, to_binary, wasm_execute, Addr, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg, Uint128,
+    WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_zll_std_liquidity_pool::{
+    ap::{
+        asset::{Asset, AssetInfo, PairInfo},
+        querier::{query_supply, query_token_balance},
+    },
+    msg::TokenInstantiateMsg,
+    response::HolderShareResponse,
+};
+use cw_zll_std_meta_pool::{
+    msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg},
+    response::{ConfigResponse, HoldingsResponse, LiquidityPoolHolding, NavPerShareResponse},
+};
+use cw_zll_std_oracle::{msg::PriceResponse, querier::query_price};
+use cw_zll_std_utils::{
+    migration::assert_migration,
+    reply::{parse_reply_instantiate_data, MsgInstantiateContractResponse},
+};
+
+use crate::{
+    error::ContractError,
+    state::{Config, CONFIG, LIQUIDITY_POOLS},
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:cw-zll-meta-pool";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A `reply` call code ID used for sub-messages.
+const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+
+const ADDR_WHILE_INSTANTIATION: &str = "";
+
+/// The amount of meta pool tokens minted to the meta pool itself on the first deposit. These
+/// tokens are locked forever, so the share price cannot be inflated by a tiny first deposit.
+pub const MINIMUM_LIQUIDITY_AMOUNT: Uint128 = Uint128::new(1_000);
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    msg.asset_infos[0].check(deps.api)?;
+    msg.asset_infos[1].check(deps.api)?;
+
+    if msg.asset_infos[0] == msg.asset_infos[1] {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    if msg.collateral_price.is_none() && msg.oracle.is_none() {
+        return Err(ContractError::CollateralPriceNotSet {});
+    }
+
+    let config = Config {
+        maintainer: deps.api.addr_validate(&msg.maintainer)?,
+        asset_infos: msg.asset_infos,
+        share_token: Addr::unchecked(ADDR_WHILE_INSTANTIATION),
+        collateral_price: msg.collateral_price,
+        oracle: msg
+            .oracle
+            .map(|oracle| deps.api.addr_validate(&oracle))
+            .transpose()?,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+
+    // Create the meta pool token contract, administered by the maintainer
+    let instantiate_token_msg = SubMsg::reply_on_success(
+        WasmMsg::Instantiate {
+            admin: Some(config.maintainer.to_string()),
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
+                name: "ZLL Meta Pool token".to_string(),
+                symbol: "uMPT".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            label: String::from("ZLL Meta Pool token"),
+        },
+        INSTANTIATE_TOKEN_REPLY_ID,
+    );
+
+    Ok(Response::new()
+        .add_submessage(instantiate_token_msg)
+        .add_attribute("method", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::Deposit {
+            assets,
+            min_share_out,
+        } => deposit(deps, env, info, assets, min_share_out),
+        ExecuteMsg::AddLiquidityPool { liquidity_pool } => {
+            add_liquidity_pool(deps, info, liquidity_pool)
+        }
+        ExecuteMsg::RemoveLiquidityPool { liquidity_pool } => {
+            remove_liquidity_pool(deps, env, info, liquidity_pool)
+        }
+        ExecuteMsg::ProvideLiquidity {
+            liquidity_pool,
+            assets,
+        } => provide_liquidity(deps, info, liquidity_pool, assets),
+        ExecuteMsg::WithdrawLiquidity {
+            liquidity_pool,
+            amount,
+        } => withdraw_liquidity(deps, info, liquidity_pool, amount),
+        ExecuteMsg::SetMaintainer { maintainer } => set_maintainer(deps, info, maintainer),
+    }
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If the template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise it returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is an object of type [`Cw20ReceiveMsg`]. This is the CW20 message that has to be processed.
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Withdraw { recipient }) => withdraw(
+            deps,
+            env,
+            info,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            recipient,
+        ),
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// ## Description
+/// Deposits assets in the meta pool and mints meta pool tokens worth the deposited value at the
+/// current net asset value per token. Returns a [`ContractError`] on failure, otherwise returns a
+/// [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **min_share_out** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of meta pool tokens the depositor accepts to receive.
+pub fn deposit(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: [Asset; 2],
+    min_share_out: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    for asset in assets.iter() {
+        asset.info.check(deps.api)?;
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let deposits = get_amounts(&config.asset_infos, &assets)?;
+
+    if deposits[0].is_zero() && deposits[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    for (asset_info, amount) in config.asset_infos.iter().zip(deposits.iter()) {
+        // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
+        if let AssetInfo::Token { contract_addr } = asset_info {
+            if !amount.is_zero() {
+                messages.push(
+                    wasm_execute(
+                        contract_addr,
+                        &Cw20ExecuteMsg::TransferFrom {
+                            owner: info.sender.to_string(),
+                            recipient: env.contract.address.to_string(),
+                            amount: *amount,
+                        },
+                        vec![],
+                    )?
+                    .into(),
+                );
+            }
+        }
+    }
+
+    let collateral_price = query_collateral_price(deps.as_ref(), &config)?;
+    let HoldingsResponse { nav, .. } =
+        query_holdings_with_deposits(deps.as_ref(), &env, &config, &deposits, collateral_price)?;
+    let deposit_value = get_value(deposits, collateral_price)?;
+
+    let total_share = query_supply(&deps.querier, config.share_token.clone())?;
+    let share = if total_share.is_zero() {
+        let share = deposit_value
+            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
+            .map_err(|_| ContractError::MinimumLiquidityAmount {
+                min_liquidity_amount: MINIMUM_LIQUIDITY_AMOUNT,
+            })?;
+
+        // Mint the minimum liquidity to the meta pool itself, where it stays locked forever
+        messages.push(mint_share_token_message(
+            &config,
+            env.contract.address.clone(),
+            MINIMUM_LIQUIDITY_AMOUNT,
+        )?);
+
+        share
+    } else if nav.amount.is_zero() {
+        return Err(ContractError::ZeroNav {});
+    } else {
+        deposit_value.multiply_ratio(total_share, nav.amount)
+    };
+
+    if let Some(min_share_out) = min_share_out {
+        if share < min_share_out {
+            return Err(ContractError::MinimumShareOut {
+                share,
+                min_share_out,
+            });
+        }
+    }
+
+    messages.push(mint_share_token_message(
+        &config,
+        info.sender.clone(),
+        share,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "deposit"),
+        attr("sender", info.sender.as_str()),
+        attr("assets", format!("{}, {}", assets[0], assets[1])),
+        attr("share", share.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Burns meta pool tokens and sends their share of the assets and of the LP tokens held by the
+/// meta pool. Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
+/// specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that sent the meta pool tokens.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of meta pool tokens to burn.
+///
+/// * **recipient** is an [`Option`] field of type [`String`]. This is the address that receives the withdrawal, defaults to the sender.
+pub fn withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.share_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => sender.clone(),
+    };
+
+    let total_share = query_supply(&deps.querier, config.share_token.clone())?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes = vec![
+        attr("action", "withdraw"),
+        attr("sender", sender.as_str()),
+        attr("recipient", recipient.as_str()),
+        attr("withdrawn_share", amount.to_string()),
+    ];
+
+    for asset_info in config.asset_infos.iter() {
+        let refund_asset = Asset {
+            info: asset_info.clone(),
+            amount: asset_info
+                .query_pool(&deps.querier, env.contract.address.clone())?
+                .multiply_ratio(amount, total_share),
+        };
+
+        if !refund_asset.amount.is_zero() {
+            attributes.push(attr("refund_asset", refund_asset.to_string()));
+            messages.push(refund_asset.into_msg(&deps.querier, recipient.clone())?);
+        }
+    }
+
+    for item in LIQUIDITY_POOLS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, liquidity_token) = item?;
+
+        let refund_share = query_token_balance(
+            &deps.querier,
+            liquidity_token.clone(),
+            env.contract.address.clone(),
+        )?
+        .multiply_ratio(amount, total_share);
+
+        if !refund_share.is_zero() {
+            attributes.push(attr(
+                "refund_share",
+                format!("{}{}", refund_share, liquidity_token),
+            ));
+            messages.push(
+                wasm_execute(
+                    liquidity_token,
+                    &Cw20ExecuteMsg::Transfer {
+                        recipient: recipient.to_string(),
+                        amount: refund_share,
+                    },
+                    vec![],
+                )?
+                .into(),
+            );
+        }
+    }
+
+    messages.push(
+        wasm_execute(
+            config.share_token.to_string(),
+            &Cw20ExecuteMsg::Burn { amount },
+            vec![],
+        )?
+        .into(),
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// ## Description
+/// Adds a liquidity pool with the assets of the meta pool the maintainer can provide liquidity to.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **liquidity_pool** is an object of type [`String`]. This is the liquidity pool to add.
+pub fn add_liquidity_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    liquidity_pool: String,
+) -> Result<Response, ContractError> {
+    let config = load_config_as_maintainer(deps.as_ref(), &info)?;
+
+    let liquidity_pool = deps.api.addr_validate(&liquidity_pool)?;
+
+    if LIQUIDITY_POOLS.has(deps.storage, &liquidity_pool) {
+        return Err(ContractError::LiquidityPoolAlreadyAdded {
+            liquidity_pool: liquidity_pool.to_string(),
+        });
+    }
+
+    let PairInfo {
+        liquidity_token,
+        asset_infos,
+        ..
+    } = deps.querier.query_wasm_smart(
+        liquidity_pool.clone(),
+        &cw_zll_std_liquidity_pool::msg::QueryMsg::Pair {},
+    )?;
+
+    if asset_infos != config.asset_infos {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    LIQUIDITY_POOLS.save(deps.storage, &liquidity_pool, &liquidity_token)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_liquidity_pool"),
+        attr("liquidity_pool", liquidity_pool.as_str()),
+        attr("liquidity_token", liquidity_token.as_str()),
+    ]))
+}
+
+/// ## Description
+/// Removes a liquidity pool the meta pool holds no LP tokens of. Returns a [`ContractError`] on
+/// failure, otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **liquidity_pool** is an object of type [`String`]. This is the liquidity pool to remove.
+pub fn remove_liquidity_pool(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    liquidity_pool: String,
+) -> Result<Response, ContractError> {
+    load_config_as_maintainer(deps.as_ref(), &info)?;
+
+    let (liquidity_pool, liquidity_token) = load_liquidity_pool(deps.as_ref(), liquidity_pool)?;
+
+    let share = query_token_balance(&deps.querier, liquidity_token, env.contract.address)?;
+
+    if !share.is_zero() {
+        return Err(ContractError::LiquidityPoolNotEmpty {
+            liquidity_pool: liquidity_pool.to_string(),
+        });
+    }
+
+    LIQUIDITY_POOLS.remove(deps.storage, &liquidity_pool);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_liquidity_pool"),
+        attr("liquidity_pool", liquidity_pool.as_str()),
+    ]))
+}
+
+/// ## Description
+/// Provides assets held by the meta pool as liquidity to one of its liquidity pools. The LP tokens
+/// are minted to the meta pool. Returns a [`ContractError`] on failure, otherwise returns a
+/// [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **liquidity_pool** is an object of type [`String`]. This is the liquidity pool to provide liquidity to.
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to provide.
+pub fn provide_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    liquidity_pool: String,
+    assets: [Asset; 2],
+) -> Result<Response, ContractError> {
+    let config = load_config_as_maintainer(deps.as_ref(), &info)?;
+
+    let (liquidity_pool, _) = load_liquidity_pool(deps.as_ref(), liquidity_pool)?;
+
+    let amounts = get_amounts(&config.asset_infos, &assets)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds = vec![];
+
+    for (asset_info, amount) in config.asset_infos.iter().zip(amounts.iter()) {
+        match asset_info {
+            AssetInfo::Token { contract_addr } => messages.push(
+                wasm_execute(
+                    contract_addr,
+                    &Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: liquidity_pool.to_string(),
+                        amount: *amount,
+                        expires: None,
+                    },
+                    vec![],
+                )?
+                .into(),
+            ),
+            AssetInfo::NativeToken { denom } => funds.push(coin(amount.u128(), denom)),
+        }
+    }
+
+    messages.push(
+        wasm_execute(
+            liquidity_pool.clone(),
+            &cw_zll_std_liquidity_pool::msg::ExecuteMsg::ProvideLiquidity {
+                assets: assets.clone(),
+                slippage_tolerance: None,
+                auto_stake: None,
+                receiver: None,
+                min_share_out: None,
+            },
+            funds,
+        )?
+        .into(),
+    );
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "provide_liquidity"),
+        attr("liquidity_pool", liquidity_pool.as_str()),
+        attr("assets", format!("{}, {}", assets[0], assets[1])),
+    ]))
+}
+
+/// ## Description
+/// Withdraws liquidity from one of the liquidity pools by sending LP tokens held by the meta pool
+/// to the liquidity pool. Returns a [`ContractError`] on failure, otherwise returns a [`Response`]
+/// with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **liquidity_pool** is an object of type [`String`]. This is the liquidity pool to withdraw liquidity from.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    liquidity_pool: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    load_config_as_maintainer(deps.as_ref(), &info)?;
+
+    let (liquidity_pool, liquidity_token) = load_liquidity_pool(deps.as_ref(), liquidity_pool)?;
+
+    Ok(Response::new()
+        .add_message(wasm_execute(
+            liquidity_token,
+            &Cw20ExecuteMsg::Send {
+                contract: liquidity_pool.to_string(),
+                amount,
+                msg: to_binary(
+                    &cw_zll_std_liquidity_pool::msg::Cw20HookMsg::WithdrawLiquidity {
+                        recipient: None,
+                        min_assets: None,
+                        output_asset: None,
+                    },
+                )?,
+            },
+            vec![],
+        )?)
+        .add_attributes(vec![
+            attr("action", "withdraw_liquidity"),
+            attr("liquidity_pool", liquidity_pool.as_str()),
+            attr("withdrawn_share", amount.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Hands the maintenance of the meta pool over to a new maintainer. Returns a [`ContractError`] on
+/// failure, otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **maintainer** is an object of type [`String`]. This is the new maintainer.
+pub fn set_maintainer(
+    deps: DepsMut,
+    info: MessageInfo,
+    maintainer: String,
+) -> Result<Response, ContractError> {
+    let mut config = load_config_as_maintainer(deps.as_ref(), &info)?;
+
+    config.maintainer = deps.api.addr_validate(&maintainer)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_maintainer"),
+        attr("maintainer", config.maintainer.as_str()),
+    ]))
+}
+
+fn load_config_as_maintainer(deps: Deps, info: &MessageInfo) -> Result<Config, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.maintainer {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(config)
+}
+
+/// Returns an added liquidity pool together with its LP token.
+fn load_liquidity_pool(deps: Deps, liquidity_pool: String) -> Result<(Addr, Addr), ContractError> {
+    let liquidity_pool = deps.api.addr_validate(&liquidity_pool)?;
+
+    let liquidity_token = LIQUIDITY_POOLS
+        .may_load(deps.storage, &liquidity_pool)?
+        .ok_or_else(|| ContractError::LiquidityPoolNotFound {
+            liquidity_pool: liquidity_pool.to_string(),
+        })?;
+
+    Ok((liquidity_pool, liquidity_token))
+}
+
+/// Returns the amounts of the given assets in the order of the asset infos of the meta pool.
+fn get_amounts(
+    asset_infos: &[AssetInfo; 2],
+    assets: &[Asset; 2],
+) -> Result<[Uint128; 2], ContractError> {
+    let mut amounts = [Uint128::zero(); 2];
+
+    for asset in assets.iter() {
+        let index = asset_infos
+            .iter()
+            .position(|info| info.equal(&asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+
+        amounts[index] = amounts[index].checked_add(asset.amount)?;
+    }
+
+    Ok(amounts)
+}
+
+fn mint_share_token_message(
+    config: &Config,
+    recipient: Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(wasm_execute(
+        config.share_token.clone(),
+        &Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount,
+        },
+        vec![],
+    )?
+    .into())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Holdings {} => to_binary(&query_holdings(deps, env)?),
+        QueryMsg::NavPerShare {} => to_binary(&query_nav_per_share(deps, env)?),
+    }
+}
+
+/// ## Description
+/// Returns the config of the meta pool in an object of type [`ConfigResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        maintainer: config.maintainer,
+        asset_infos: config.asset_infos,
+        share_token: config.share_token,
+        collateral_price: config.collateral_price,
+        oracle: config.oracle,
+        liquidity_pools: LIQUIDITY_POOLS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|key| deps.api.addr_validate(&String::from_utf8(key)?))
+            .collect::<StdResult<_>>()?,
+    })
+}
+
+/// ## Description
+/// Returns the assets and the LP tokens held by the meta pool and their value in an object of type
+/// [`HoldingsResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_holdings(deps: Deps, env: Env) -> StdResult<HoldingsResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let collateral_price = query_collateral_price(deps, &config)?;
+
+    query_holdings_with_deposits(deps, &env, &config, &[Uint128::zero(); 2], collateral_price)
+}
+
+/// ## Description
+/// Returns the net asset value of the meta pool per meta pool token in an object of type
+/// [`NavPerShareResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_nav_per_share(deps: Deps, env: Env) -> StdResult<NavPerShareResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let collateral_price = query_collateral_price(deps, &config)?;
+    let HoldingsResponse { nav, .. } =
+        query_holdings_with_deposits(deps, &env, &config, &[Uint128::zero(); 2], collateral_price)?;
+    let total_share = query_supply(&deps.querier, config.share_token)?;
+
+    let nav_per_share = if total_share.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(nav.amount, total_share)
+    };
+
+    Ok(NavPerShareResponse {
+        nav,
+        total_share,
+        nav_per_share,
+        collateral_price,
+    })
+}
+
+/// ## Description
+/// Returns the holdings of the meta pool, excluding native deposits that are already part of its
+/// balance. The assets held by the meta pool itself and its share of the reserves and of the
+/// outstanding loans of each liquidity pool are valued at the collateral price of the meta pool.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **deposits** is an array with two objects of type [`Uint128`]. These are the deposited amounts of the collateral and the lending asset.
+///
+/// * **collateral_price** is an object of type [`Decimal`]. This is the price of one unit of the collateral asset.
+fn query_holdings_with_deposits(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    deposits: &[Uint128; 2],
+    collateral_price: Decimal,
+) -> StdResult<HoldingsResponse> {
+    let mut assets = [
+        Asset {
+            info: config.asset_infos[0].clone(),
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: config.asset_infos[1].clone(),
+            amount: Uint128::zero(),
+        },
+    ];
+
+    for (asset, deposit) in assets.iter_mut().zip(deposits.iter()) {
+        asset.amount = asset
+            .info
+            .query_pool(&deps.querier, env.contract.address.clone())?;
+
+        // The native deposits are already part of the balance, the tokens are transferred later
+        if asset.info.is_native_token() {
+            asset.amount = asset.amount.checked_sub(*deposit)?;
+        }
+    }
+
+    let mut nav_amount = get_value([assets[0].amount, assets[1].amount], collateral_price)?;
+    let mut liquidity_pools = vec![];
+
+    for item in LIQUIDITY_POOLS.range(deps.storage, None, None, Order::Ascending) {
+        let (key, liquidity_token) = item?;
+        let liquidity_pool = deps.api.addr_validate(&String::from_utf8(key)?)?;
+
+        let HolderShareResponse {
+            share,
+            assets,
+            loans,
+            ..
+        } = deps.querier.query_wasm_smart(
+            liquidity_pool.clone(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::HolderShare {
+                address: env.contract.address.to_string(),
+            },
+        )?;
+
+        // The liquidity pools share the assets of the meta pool, so their reserves come in its order
+        let nav = Asset {
+            info: config.asset_infos[1].clone(),
+            amount: get_value([assets[0].amount, assets[1].amount], collateral_price)?
+                .checked_add(get_loans_value(
+                    [loans[0].amount, loans[1].amount],
+                    collateral_price,
+                ))?,
+        };
+
+        nav_amount = nav_amount.checked_add(nav.amount)?;
+        liquidity_pools.push(LiquidityPoolHolding {
+            liquidity_pool,
+            liquidity_token,
+            share,
+            nav,
+        });
+    }
+
+    Ok(HoldingsResponse {
+        nav: Asset {
+            info: config.asset_infos[1].clone(),
+            amount: nav_amount,
+        },
+        assets,
+        liquidity_pools,
+    })
+}
+
+/// ## Description
+/// Returns the value of the collateral asset (first) and the lending asset (second) amounts in
+/// units of the lending asset.
+fn get_value(amounts: [Uint128; 2], collateral_price: Decimal) -> StdResult<Uint128> {
+    Ok((amounts[0] * collateral_price).checked_add(amounts[1])?)
+}
+
+/// ## Description
+/// Returns the value of the collateral pledged for (first) and the notional owed by (second)
+/// outstanding loans in units of the lending asset, which is the lesser of the two since borrowers
+/// only repay the notional to reclaim collateral worth more.
+fn get_loans_value(loans: [Uint128; 2], collateral_price: Decimal) -> Uint128 {
+    (loans[0] * collateral_price).min(loans[1])
+}
+
+fn query_collateral_price(deps: Deps, config: &Config) -> StdResult<Decimal> {
+    if let Some(oracle) = &config.oracle {
+        let PriceResponse { price, .. } = query_price(
+            &deps.querier,
+            oracle.clone(),
+            config.asset_infos[0].clone(),
+            config.asset_infos[1].clone(),
+        )?;
+
+        return Ok(price);
+    }
+
+    config
+        .collateral_price
+        .ok_or_else(|| StdError::generic_err("Collateral price is not set"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.result.is_err() {
+        return Err(ContractError::Std(StdError::GenericErr {
+            msg: msg.result.unwrap_err(),
+        }));
+    }
+
+    match msg.id {
+        INSTANTIATE_TOKEN_REPLY_ID => reply_on_instantiate_token(deps, env, msg),
+        _ => Err(ContractError::Std(StdError::GenericErr {
+            msg: format!("reply id `{:?}` is invalid", msg.id),
+        })),
+    }
+}
+
+fn reply_on_instantiate_token(
+    deps: DepsMut,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if config.share_token != Addr::unchecked(ADDR_WHILE_INSTANTIATION) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let res: MsgInstantiateContractResponse = parse_reply_instantiate_data(msg)?;
+
+    config.share_token = deps.api.addr_validate(&res.contract_address)?;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("share_token_addr", config.share_token))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = assert_migration(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "migrate".to_string()),
+        ("previous_version", previous_version.to_string()),
+        ("version", CONTRACT_VERSION.to_string()),
+    ]))
+}
@@ -0,0 +1,56 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_zll_std_utils::{migration::MigrationError, reply::ParseReplyError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Doubling assets in asset infos")]
+    DoublingAssets {},
+
+    #[error("Asset mismatch between the requested and the stored asset in contract")]
+    AssetMismatch {},
+
+    #[error("Event of zero transfer")]
+    InvalidZeroAmount {},
+
+    #[error("Either a collateral price or an oracle is required to value the meta pool")]
+    CollateralPriceNotSet {},
+
+    #[error("Initial deposit must mint more than {min_liquidity_amount} meta pool tokens")]
+    MinimumLiquidityAmount { min_liquidity_amount: Uint128 },
+
+    #[error("Meta pool tokens cannot be minted while the meta pool holds no value")]
+    ZeroNav {},
+
+    #[error("Minted meta pool tokens ({share}) are below the requested minimum ({min_share_out})")]
+    MinimumShareOut {
+        share: Uint128,
+        min_share_out: Uint128,
+    },
+
+    #[error("Liquidity pool {liquidity_pool} is already added")]
+    LiquidityPoolAlreadyAdded { liquidity_pool: String },
+
+    #[error("Liquidity pool {liquidity_pool} is not added")]
+    LiquidityPoolNotFound { liquidity_pool: String },
+
+    #[error(
+        "Liquidity pool {liquidity_pool} cannot be removed while the meta pool holds its LP tokens"
+    )]
+    LiquidityPoolNotEmpty { liquidity_pool: String },
+}
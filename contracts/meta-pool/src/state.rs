@@ -0,0 +1,30 @@
+use cosmwasm_std::{Addr, Decimal};
+use cw_storage_plus::{Item, Map};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure stores the main config parameters for a meta pool contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// The maintainer who allocates the liquidity of the meta pool across liquidity pools
+    pub maintainer: Addr,
+    /// The collateral asset (first) and the lending asset (second) of the meta pool
+    pub asset_infos: [AssetInfo; 2],
+    /// The CW20 contract of the meta pool token
+    pub share_token: Addr,
+    /// The price of one unit of the collateral asset in units of the lending asset
+    pub collateral_price: Option<Decimal>,
+    /// The price oracle queried for the collateral price, preferred over `collateral_price`
+    pub oracle: Option<Addr>,
+}
+
+/// ## Description
+/// Stores the config struct at the given key
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// ## Description
+/// Stores the LP tokens of the liquidity pools the maintainer can provide liquidity to, keyed by
+/// the liquidity pools
+pub const LIQUIDITY_POOLS: Map<&Addr, Addr> = Map::new("liquidity_pools");
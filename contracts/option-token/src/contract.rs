@@ -2,9 +2,11 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 use cw2::set_contract_version;
+use cw_zll_std_utils::migration::assert_migration;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, QueryMsg, TokenResponse};
+use crate::state::{Config, Metadata, OptionToken, CONFIG, TOKENS};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw-zll-option-token";
@@ -15,25 +17,134 @@ pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    Ok(Response::new().add_attribute("method", "instantiate"))
+    let minter = deps.api.addr_validate(&msg.minter)?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            minter: minter.clone(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("minter", minter))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: ExecuteMsg,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    if info.sender != CONFIG.load(deps.storage)?.minter {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match msg {
+        ExecuteMsg::Mint {
+            token_id,
+            owner,
+            metadata,
+        } => execute_mint(deps, token_id, owner, metadata),
+        ExecuteMsg::UpdateMetadata { token_id, metadata } => {
+            execute_update_metadata(deps, token_id, metadata)
+        }
+        ExecuteMsg::Burn { token_id } => execute_burn(deps, token_id),
+    }
+}
+
+fn execute_mint(
+    deps: DepsMut,
+    token_id: String,
+    owner: String,
+    metadata: Metadata,
+) -> Result<Response, ContractError> {
+    if TOKENS.has(deps.storage, &token_id) {
+        return Err(ContractError::TokenAlreadyExists { token_id });
+    }
+
+    let owner = deps.api.addr_validate(&owner)?;
+
+    TOKENS.save(
+        deps.storage,
+        &token_id,
+        &OptionToken {
+            owner: owner.clone(),
+            metadata,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "mint".to_string()),
+        ("token_id", token_id),
+        ("owner", owner.to_string()),
+    ]))
+}
+
+fn execute_update_metadata(
+    deps: DepsMut,
+    token_id: String,
+    metadata: Metadata,
 ) -> Result<Response, ContractError> {
-    Ok(Response::default())
+    let mut token = TOKENS.load(deps.storage, &token_id)?;
+
+    let notional = metadata.notional;
+    token.metadata = metadata;
+    TOKENS.save(deps.storage, &token_id, &token)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "update_metadata".to_string()),
+        ("token_id", token_id),
+        ("notional", notional.to_string()),
+    ]))
+}
+
+fn execute_burn(deps: DepsMut, token_id: String) -> Result<Response, ContractError> {
+    // fails for unknown tokens
+    TOKENS.load(deps.storage, &token_id)?;
+    TOKENS.remove(deps.storage, &token_id);
+
+    Ok(
+        Response::new()
+            .add_attributes(vec![("method", "burn".to_string()), ("token_id", token_id)]),
+    )
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
+        QueryMsg::Token { token_id } => to_binary(&query_token(deps, token_id)?),
+    }
+}
+
+fn query_minter(deps: Deps) -> StdResult<MinterResponse> {
+    Ok(MinterResponse {
+        minter: CONFIG.load(deps.storage)?.minter,
+    })
+}
+
+fn query_token(deps: Deps, token_id: String) -> StdResult<TokenResponse> {
+    let OptionToken { owner, metadata } = TOKENS.load(deps.storage, &token_id)?;
+
+    Ok(TokenResponse { owner, metadata })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, _msg: QueryMsg) -> StdResult<Binary> {
-    let stub: [u8; 0] = [];
-    to_binary(&stub)
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = assert_migration(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("method", "migrate".to_string()),
+        ("previous_version", previous_version.to_string()),
+        ("version", CONTRACT_VERSION.to_string()),
+    ]))
 }
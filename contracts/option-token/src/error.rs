@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use cw_zll_std_utils::migration::MigrationError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,8 +7,15 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Migration(#[from] MigrationError),
+
     #[error("Custom Error val: {val:?}")]
     CustomError { val: String },
-    // Add any other custom errors you like here.
-    // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Option token {token_id} already exists")]
+    TokenAlreadyExists { token_id: String },
 }
@@ -1,14 +1,58 @@
+use cosmwasm_std::Addr;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::state::Metadata;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    /// The market which issues the option tokens
+    pub minter: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Issues an option token to the holder of the option. Only the minter can issue option tokens.
+    Mint {
+        token_id: String,
+        owner: String,
+        metadata: Metadata,
+    },
+    /// Updates the terms of an option token, e.g. to the remaining notional of an option which
+    /// has been partially exercised. Only the minter can update option tokens.
+    UpdateMetadata {
+        token_id: String,
+        metadata: Metadata,
+    },
+    /// Burns an option token once its option has been exercised in full, closed or rolled over.
+    /// Only the minter can burn option tokens.
+    Burn { token_id: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns the minter of the option tokens in an object of type [`MinterResponse`]
+    Minter {},
+    /// Returns the holder and the terms of an option token in an object of type [`TokenResponse`]
+    Token { token_id: String },
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct InstantiateMsg {}
+pub struct MigrateMsg {}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum ExecuteMsg {}
+pub struct MinterResponse {
+    pub minter: Addr,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum QueryMsg {}
+pub struct TokenResponse {
+    pub owner: Addr,
+    pub metadata: Metadata,
+}
@@ -1,9 +1,42 @@
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct State {}
+pub struct Config {
+    /// The market which issues, updates and burns the option tokens
+    pub minter: Addr,
+}
 
-pub const STATE: Item<State> = Item::new("state");
+pub const CONFIG: Item<Config> = Item::new("config");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionKind {
+    /// The option of a borrower to buy the pledged collateral back by repaying the notional
+    Call,
+    /// The option of the pool to sell collateral to a lender for the lent notional
+    Put,
+}
+
+/// The terms of the option an option token stands for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Metadata {
+    pub kind: OptionKind,
+    /// The strike price in units of the lending asset per unit of the collateral asset
+    pub strike: Decimal,
+    /// The amount of the lending asset the option is exercised for
+    pub notional: Uint128,
+    /// The amount of the collateral asset the option is exercised into
+    pub collateral_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OptionToken {
+    pub owner: Addr,
+    pub metadata: Metadata,
+}
+
+pub const TOKENS: Map<&str, OptionToken> = Map::new("tokens");
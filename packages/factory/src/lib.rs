@@ -0,0 +1,3 @@
+pub mod msg;
+pub mod response;
+pub mod state;
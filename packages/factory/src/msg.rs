@@ -0,0 +1,87 @@
+use cosmwasm_std::{Decimal, Uint128};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use cw_zll_std_market::{response::MarketPhase, state::MarketPhasesInfo};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::state::{CodeIds, MarketTemplate};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    pub owner: String,
+    pub code_ids: CodeIds,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Replaces the approved code ids used for new markets. Only the owner can set the code ids.
+    SetCodeIds { code_ids: CodeIds },
+    /// Creates or replaces a market template. Only the owner can set templates.
+    SetTemplate {
+        name: String,
+        template: MarketTemplate,
+    },
+    /// Removes a market template. Markets created from the template are not affected. Only the
+    /// owner can remove templates.
+    RemoveTemplate { name: String },
+    /// Creates a market, its liquidity pool and LP token from the approved code ids and the
+    /// parameters of the given template, and adds the market to the registry. Only the owner can
+    /// create markets.
+    CreateMarket {
+        template: String,
+        market_operator: String,
+        /// The collateral asset (first) and the lending asset (second) of the market
+        asset_infos: [AssetInfo; 2],
+        market_phases_info: MarketPhasesInfo,
+        target_liquidity: Option<Uint128>,
+        minimum_liquidity: Option<Uint128>,
+        collateral_price: Option<Decimal>,
+        oracle: Option<String>,
+        /// Starts a series with the market, which is continued by `RollSeries` once the market
+        /// is settled or cancelled
+        recurring: Option<bool>,
+    },
+    /// Creates the successor of the latest market of the series once that market is settled or
+    /// cancelled. The phase boundaries of the latest market are shifted to start at the current
+    /// block and the liquidity the LPs escrowed for the rollover is moved into the pool of the
+    /// successor. Only the owner and the market operator of the series can roll it.
+    RollSeries { series_id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    GetConfig {},
+    GetTemplate {
+        name: String,
+    },
+    /// Returns the names of all templates
+    GetTemplates {},
+    GetMarket {
+        market_id: u64,
+    },
+    /// Returns up to `limit` markets of the registry in the order they were created, starting
+    /// after `start_after`. Only the markets matching all given filters are returned.
+    GetMarkets {
+        /// The collateral asset (first) and the lending asset (second) of the market
+        asset_infos: Option<[AssetInfo; 2]>,
+        /// The current phase of the market
+        phase: Option<MarketPhase>,
+        /// Only markets created at or after this block height
+        created_from: Option<u64>,
+        /// Only markets created at or before this block height
+        created_until: Option<u64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetSeries {
+        series_id: u64,
+    },
+}
+
+/// This structure describes the migrate message of the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
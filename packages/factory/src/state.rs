@@ -0,0 +1,195 @@
+use cosmwasm_std::{Addr, Decimal, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map, U64Key};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use cw_zll_std_market::state::MarketPhasesInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The code ids of the contracts of a market stack approved by the factory owner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CodeIds {
+    pub market: u64,
+    pub liquidity_pool: u64,
+    pub liquidity_pool_token: u64,
+    pub option_token: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// The address which approves code ids and templates and creates markets
+    pub owner: Addr,
+    pub code_ids: CodeIds,
+}
+
+const CONFIG: Item<Config> = Item::new("config");
+
+pub fn set_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    CONFIG.save(storage, config)
+}
+
+pub fn get_config(storage: &dyn Storage) -> StdResult<Config> {
+    CONFIG.load(storage)
+}
+
+pub fn get_owner(storage: &dyn Storage) -> StdResult<Addr> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.owner)
+}
+
+pub fn set_code_ids(storage: &mut dyn Storage, code_ids: CodeIds) -> StdResult<Config> {
+    CONFIG.update(storage, |mut config| -> StdResult<_> {
+        config.code_ids = code_ids;
+        Ok(config)
+    })
+}
+
+/// The market parameters shared by all markets created from a template. See the market
+/// `InstantiateMsg` for the meaning of each parameter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketTemplate {
+    pub blocks_per_year: u64,
+    pub alpha: u64,
+    pub twap_window: Option<u64>,
+    pub max_price_age: Option<u64>,
+    pub max_price_deviation: Option<Decimal>,
+    pub buyback_spread: Option<Decimal>,
+    pub max_buyback_volume: Option<Uint128>,
+    pub protocol_fee_rate: Option<Decimal>,
+    pub treasury: Option<String>,
+    pub guardian: Option<String>,
+    pub timelock_delay: Option<u64>,
+}
+
+const TEMPLATES: Map<&str, MarketTemplate> = Map::new("templates");
+
+pub fn set_template(
+    storage: &mut dyn Storage,
+    name: &str,
+    template: &MarketTemplate,
+) -> StdResult<()> {
+    TEMPLATES.save(storage, name, template)
+}
+
+pub fn may_get_template(storage: &dyn Storage, name: &str) -> StdResult<Option<MarketTemplate>> {
+    TEMPLATES.may_load(storage, name)
+}
+
+pub fn remove_template(storage: &mut dyn Storage, name: &str) {
+    TEMPLATES.remove(storage, name)
+}
+
+/// Returns the names of all templates in alphabetical order.
+pub fn get_template_names(storage: &dyn Storage) -> StdResult<Vec<String>> {
+    TEMPLATES
+        .keys(storage, None, None, Order::Ascending)
+        .map(|name| String::from_utf8(name).map_err(Into::into))
+        .collect()
+}
+
+/// A market created by the factory
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketRecord {
+    pub id: u64,
+    pub market: Addr,
+    pub liquidity_pool: Addr,
+    /// The name of the template the market was created from
+    pub template: String,
+    /// The collateral asset (first) and the lending asset (second) of the market
+    pub asset_infos: [AssetInfo; 2],
+    pub market_phases_info: MarketPhasesInfo,
+    /// The block height the market was created at
+    pub created_at: u64,
+    /// The series the market belongs to, if it is a recurring market
+    pub series_id: Option<u64>,
+}
+
+const MARKETS: Map<U64Key, MarketRecord> = Map::new("markets");
+
+const MARKETS_COUNT: Item<u64> = Item::new("markets_count");
+
+/// Reserves the next market id.
+pub fn next_market_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let markets_count = get_markets_count(storage)? + 1;
+
+    MARKETS_COUNT.save(storage, &markets_count)?;
+
+    Ok(markets_count)
+}
+
+pub fn get_markets_count(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(MARKETS_COUNT.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn set_market(storage: &mut dyn Storage, market: &MarketRecord) -> StdResult<()> {
+    MARKETS.save(storage, U64Key::new(market.id), market)
+}
+
+pub fn get_market(storage: &dyn Storage, market_id: u64) -> StdResult<MarketRecord> {
+    MARKETS.load(storage, U64Key::new(market_id))
+}
+
+pub fn may_get_market(storage: &dyn Storage, market_id: u64) -> StdResult<Option<MarketRecord>> {
+    MARKETS.may_load(storage, U64Key::new(market_id))
+}
+
+/// Returns the markets in the order they were created, starting after `start_after`.
+pub fn get_markets<'a>(
+    storage: &'a dyn Storage,
+    start_after: Option<u64>,
+) -> Box<dyn Iterator<Item = StdResult<MarketRecord>> + 'a> {
+    Box::new(
+        MARKETS
+            .range(
+                storage,
+                start_after.map(Bound::exclusive_int),
+                None,
+                Order::Ascending,
+            )
+            .map(|item| item.map(|(_, market)| market)),
+    )
+}
+
+/// A recurring market. Every market of the series is created from the parameters of the series
+/// once its predecessor is settled, and the liquidity the LPs escrowed in the predecessor is rolled
+/// over into its pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Series {
+    pub id: u64,
+    /// The name of the template the markets are created from
+    pub template: String,
+    pub market_operator: Addr,
+    /// The collateral asset (first) and the lending asset (second) of the markets
+    pub asset_infos: [AssetInfo; 2],
+    pub target_liquidity: Option<Uint128>,
+    pub minimum_liquidity: Option<Uint128>,
+    pub collateral_price: Option<Decimal>,
+    pub oracle: Option<String>,
+    /// The markets of the series in the order they were created
+    pub market_ids: Vec<u64>,
+}
+
+const SERIES: Map<U64Key, Series> = Map::new("series");
+
+const SERIES_COUNT: Item<u64> = Item::new("series_count");
+
+/// Reserves the next series id.
+pub fn next_series_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let series_count = SERIES_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+
+    SERIES_COUNT.save(storage, &series_count)?;
+
+    Ok(series_count)
+}
+
+pub fn set_series(storage: &mut dyn Storage, series: &Series) -> StdResult<()> {
+    SERIES.save(storage, U64Key::new(series.id), series)
+}
+
+pub fn get_series(storage: &dyn Storage, series_id: u64) -> StdResult<Series> {
+    SERIES.load(storage, U64Key::new(series_id))
+}
+
+pub fn may_get_series(storage: &dyn Storage, series_id: u64) -> StdResult<Option<Series>> {
+    SERIES.may_load(storage, U64Key::new(series_id))
+}
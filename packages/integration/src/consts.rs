@@ -1,15 +1,21 @@
 pub const ADDR_ADMIN: &str = "admin";
 
+pub const ADDR_GUARDIAN: &str = "guardian";
+
 pub const ADDR_LIQUIDITY_PROVIDER: &str = "liquidity_provider";
 
 pub const ADDR_LIQUIDITY_PROVIDER_2: &str = "liquidity_provider_2";
 
 pub const ADDR_MARKET_OPERATOR: &str = "market_operator";
 
+pub const ADDR_META_POOL_MAINTAINER: &str = "meta_pool_maintainer";
+
 pub const ADDR_REGULAR_USER: &str = "regular_user";
 
 pub const ADDR_REGULAR_USER_2: &str = "regular_user_2";
 
+pub const ADDR_TREASURY: &str = "treasury";
+
 pub const BLOCK_HEIGHT_MARKET_STARTS_AT: u64 = 1234;
 
 pub const BLOCK_HEIGHT_LP_ENDS_AT: u64 = 2345;
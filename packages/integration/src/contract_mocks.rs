@@ -1,6 +1,6 @@
 use cosmwasm_std::{Addr, StdResult};
 use cw_multi_test::{App, ContractWrapper};
-use cw_zll_std_market::response::{MarketPhase, MarketPhaseResponse};
+use cw_zll_std_market::response::{MarketPhase, MarketPhaseResponse, MarketPhasesInfoResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -21,6 +21,16 @@ impl MarketContract {
 
         Ok(response.phase)
     }
+
+    pub fn get_market_phases_info(
+        &self,
+        app: &App<cosmwasm_std::Empty, terra_cosmwasm::TerraQueryWrapper>,
+    ) -> StdResult<MarketPhasesInfoResponse> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetMarketPhasesInfo {},
+        )
+    }
 }
 
 impl ContractBase for MarketContract {
@@ -36,7 +46,8 @@ impl ContractBase for MarketContract {
             cw_zll_market::contract::instantiate,
             cw_zll_market::contract::query,
         )
-        .with_reply(cw_zll_market::contract::reply);
+        .with_reply(cw_zll_market::contract::reply)
+        .with_migrate(cw_zll_market::contract::migrate);
 
         Box::new(contract)
     }
@@ -64,7 +75,8 @@ impl ContractBase for LiquidityPoolContract {
             cw_zll_liquidity_pool::contract::instantiate,
             cw_zll_liquidity_pool::contract::query,
         )
-        .with_reply(cw_zll_liquidity_pool::contract::reply);
+        .with_reply(cw_zll_liquidity_pool::contract::reply)
+        .with_migrate(cw_zll_liquidity_pool::contract::migrate);
 
         Box::new(contract)
     }
@@ -106,7 +118,8 @@ impl ContractBase for LiquidityPoolTokenContract {
             cw_zll_liquidity_pool_token::contract::execute,
             cw_zll_liquidity_pool_token::contract::instantiate,
             cw_zll_liquidity_pool_token::contract::query,
-        );
+        )
+        .with_migrate(cw_zll_liquidity_pool_token::contract::migrate);
 
         Box::new(contract)
     }
@@ -127,6 +140,101 @@ impl ContractBase for OptionTokenContract {
             cw_zll_option_token::contract::execute,
             cw_zll_option_token::contract::instantiate,
             cw_zll_option_token::contract::query,
+        )
+        .with_migrate(cw_zll_option_token::contract::migrate);
+
+        Box::new(contract)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FactoryContract(pub Addr);
+
+impl ContractBase for FactoryContract {
+    type ExecuteMsg = cw_zll_std_factory::msg::ExecuteMsg;
+
+    fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    fn contract_code() -> Box<dyn cw_multi_test::Contract<cosmwasm_std::Empty>> {
+        let contract = ContractWrapper::new(
+            cw_zll_factory::contract::execute,
+            cw_zll_factory::contract::instantiate,
+            cw_zll_factory::contract::query,
+        )
+        .with_reply(cw_zll_factory::contract::reply)
+        .with_migrate(cw_zll_factory::contract::migrate);
+
+        Box::new(contract)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MetaPoolContract(pub Addr, pub Addr);
+
+impl MetaPoolContract {
+    pub fn share_token_contract(&self) -> LiquidityPoolTokenContract {
+        LiquidityPoolTokenContract(self.1.clone())
+    }
+}
+
+impl ContractBase for MetaPoolContract {
+    type ExecuteMsg = cw_zll_std_meta_pool::msg::ExecuteMsg;
+
+    fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    fn contract_code() -> Box<dyn cw_multi_test::Contract<cosmwasm_std::Empty>> {
+        let contract = ContractWrapper::new(
+            cw_zll_meta_pool::contract::execute,
+            cw_zll_meta_pool::contract::instantiate,
+            cw_zll_meta_pool::contract::query,
+        )
+        .with_reply(cw_zll_meta_pool::contract::reply)
+        .with_migrate(cw_zll_meta_pool::contract::migrate);
+
+        Box::new(contract)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowlistContract(pub Addr);
+
+impl ContractBase for AllowlistContract {
+    type ExecuteMsg = crate::mock_allowlist::ExecuteMsg;
+
+    fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    fn contract_code() -> Box<dyn cw_multi_test::Contract<cosmwasm_std::Empty>> {
+        let contract = ContractWrapper::new(
+            crate::mock_allowlist::execute,
+            crate::mock_allowlist::instantiate,
+            crate::mock_allowlist::query,
+        );
+
+        Box::new(contract)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OracleContract(pub Addr);
+
+impl ContractBase for OracleContract {
+    type ExecuteMsg = crate::mock_oracle::ExecuteMsg;
+
+    fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    fn contract_code() -> Box<dyn cw_multi_test::Contract<cosmwasm_std::Empty>> {
+        let contract = ContractWrapper::new(
+            crate::mock_oracle::execute,
+            crate::mock_oracle::instantiate,
+            crate::mock_oracle::query,
         );
 
         Box::new(contract)
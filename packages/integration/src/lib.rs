@@ -1,5 +1,7 @@
 pub mod consts;
 pub mod contract_helpers;
 pub mod contract_mocks;
+pub mod mock_allowlist;
+pub mod mock_oracle;
 pub mod terra_querier_mock;
 pub mod test_env;
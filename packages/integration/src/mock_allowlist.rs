@@ -0,0 +1,63 @@
+//! An allowlist contract for tests which allows the accounts set by its `SetAllowed` messages.
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+};
+use cw_storage_plus::Map;
+use cw_zll_std_market::{
+    msg::AllowlistQueryMsg, response::IsAllowedResponse, state::AllowlistRole,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Sets whether the account may act in the role.
+    SetAllowed {
+        role: AllowlistRole,
+        address: String,
+        is_allowed: bool,
+    },
+}
+
+const ALLOWED: Map<(&str, &str), bool> = Map::new("allowed");
+
+pub fn instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::SetAllowed {
+            role,
+            address,
+            is_allowed,
+        } => {
+            ALLOWED.save(deps.storage, (&role.to_string(), &address), &is_allowed)?;
+
+            Ok(Response::new().add_attribute("method", "set_allowed"))
+        }
+    }
+}
+
+pub fn query(deps: Deps, _env: Env, msg: AllowlistQueryMsg) -> StdResult<Binary> {
+    match msg {
+        AllowlistQueryMsg::IsAllowed { role, address } => {
+            let is_allowed = ALLOWED
+                .may_load(deps.storage, (&role.to_string(), &address))?
+                .unwrap_or_default();
+
+            to_binary(&IsAllowedResponse { is_allowed })
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! A price oracle for tests which reports the prices set by its `SetPrice` messages.
+use cosmwasm_std::{
+    to_binary, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+    StdResult,
+};
+use cw_storage_plus::Map;
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use cw_zll_std_oracle::msg::{PriceResponse, QueryMsg};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Sets the price of one unit of the `base` asset in units of the `quote` asset.
+    /// The publish time defaults to the current block time and the confidence to zero.
+    SetPrice {
+        base: AssetInfo,
+        quote: AssetInfo,
+        price: Decimal,
+        publish_time: Option<u64>,
+        confidence: Option<Decimal>,
+    },
+}
+
+const PRICES: Map<(&str, &str), PriceResponse> = Map::new("prices");
+
+pub fn instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::SetPrice {
+            base,
+            quote,
+            price,
+            publish_time,
+            confidence,
+        } => {
+            PRICES.save(
+                deps.storage,
+                (&base.to_string(), &quote.to_string()),
+                &PriceResponse {
+                    price,
+                    publish_time: publish_time.unwrap_or_else(|| env.block.time.seconds()),
+                    confidence: confidence.unwrap_or_else(Decimal::zero),
+                },
+            )?;
+
+            Ok(Response::new().add_attribute("method", "set_price"))
+        }
+    }
+}
+
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Price { base, quote } => {
+            let response = PRICES
+                .may_load(deps.storage, (&base.to_string(), &quote.to_string()))?
+                .ok_or_else(|| StdError::not_found(format!("price of {} in {}", base, quote)))?;
+
+            to_binary(&response)
+        }
+    }
+}
@@ -1,21 +1,30 @@
 use crate::{
     consts::{
         ADDR_ADMIN, ADDR_LIQUIDITY_PROVIDER, ADDR_LIQUIDITY_PROVIDER_2, ADDR_MARKET_OPERATOR,
-        BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_MARKET_STARTS_AT,
-        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+        ADDR_META_POOL_MAINTAINER, BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_MARKET_STARTS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
     },
     contract_helpers::ContractBase,
     contract_mocks::{
-        Cw20TokenContract, LiquidityPoolContract, LiquidityPoolTokenContract, MarketContract,
+        AllowlistContract, Cw20TokenContract, FactoryContract, LiquidityPoolContract,
+        LiquidityPoolTokenContract, MarketContract, MetaPoolContract, OptionTokenContract,
+        OracleContract,
     },
     terra_querier_mock::TerraCustomQueryHandler,
 };
 use anyhow::Result;
-use cosmwasm_std::{coin, to_binary, Addr, Empty, Uint128};
+use cosmwasm_std::{coin, to_binary, Addr, Decimal, Empty, Uint128};
 use cw20::{Cw20Coin, Cw20ExecuteMsg};
 use cw_multi_test::{App, AppBuilder, AppResponse, Executor};
-use cw_zll_std_liquidity_pool::ap::{Asset, AssetInfo, PairInfo};
-use cw_zll_std_market::{response::LiquidityPoolResponse, state::MarketPhasesInfo};
+use cw_zll_std_factory::state::CodeIds;
+use cw_zll_std_liquidity_pool::{
+    ap::{Asset, AssetInfo, PairInfo},
+    asset::create_coin_asset,
+};
+use cw_zll_std_market::{
+    borrow::BorrowingTermsResponse, response::LiquidityPoolResponse, state::MarketPhasesInfo,
+};
+use cw_zll_std_meta_pool::response::ConfigResponse;
 use terra_cosmwasm::TerraQueryWrapper;
 
 pub fn mock_app() -> App<Empty, TerraQueryWrapper> {
@@ -32,6 +41,15 @@ pub struct MarketSetup {
 pub fn create_martket_setup(
     app: &mut App<Empty, TerraQueryWrapper>,
     asset_infos: [AssetInfo; 2],
+) -> MarketSetup {
+    create_martket_setup_with(app, asset_infos, |_| {})
+}
+
+/// Creates a market setup with the default instantiate message adjusted by `customize_msg`.
+pub fn create_martket_setup_with(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    asset_infos: [AssetInfo; 2],
+    customize_msg: impl FnOnce(&mut cw_zll_std_market::msg::InstantiateMsg),
 ) -> MarketSetup {
     let liquidity_pool_code_id = app.store_code(LiquidityPoolContract::contract_code());
     let liquidity_pool_token_code_id = app.store_code(LiquidityPoolTokenContract::contract_code());
@@ -43,24 +61,43 @@ pub fn create_martket_setup(
         block.height = market_start_at;
     });
 
+    let mut instantiate_msg = cw_zll_std_market::msg::InstantiateMsg {
+        market_operator: Addr::unchecked(ADDR_MARKET_OPERATOR),
+        asset_infos,
+        liquidity_pool_code_id,
+        liquidity_pool_token_code_id,
+        option_token_code_id: None,
+        market_phases_info: MarketPhasesInfo {
+            market_started_at: market_start_at,
+            lp_phase_ends_at: BLOCK_HEIGHT_LP_ENDS_AT,
+            amm_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT,
+            settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+        },
+        blocks_per_year: 4_204_800, // assuming one block per 7.5 seconds
+        alpha: 200_000_000_000,
+        target_liquidity: None,
+        minimum_liquidity: None,
+        collateral_price: None,
+        oracle: None,
+        twap_window: None,
+        max_price_age: None,
+        max_price_deviation: None,
+        buyback_spread: None,
+        max_buyback_volume: None,
+        protocol_fee_rate: None,
+        treasury: None,
+        guardian: None,
+        timelock_delay: None,
+        predecessor: None,
+    };
+
+    customize_msg(&mut instantiate_msg);
+
     let market_contract_addr = app
         .instantiate_contract(
             market_contract_code_id,
             Addr::unchecked(ADDR_ADMIN),
-            &cw_zll_std_market::msg::InstantiateMsg {
-                market_operator: Addr::unchecked(ADDR_MARKET_OPERATOR),
-                asset_infos,
-                liquidity_pool_code_id,
-                liquidity_pool_token_code_id,
-                market_phases_info: MarketPhasesInfo {
-                    market_started_at: market_start_at,
-                    lp_phase_ends_at: BLOCK_HEIGHT_LP_ENDS_AT,
-                    amm_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT,
-                    settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
-                },
-                blocks_per_year: 4_204_800, // assuming one block per 7.5 seconds
-                alpha: 200_000_000_000,
-            },
+            &instantiate_msg,
             &[],
             "ZLL Market",
             Some(ADDR_ADMIN.into()),
@@ -98,6 +135,57 @@ pub fn create_martket_setup(
     }
 }
 
+/// Creates a LUNA/UST market at a collateral price of 250 UST with a deposit of 20 LUNA & 5000 UST,
+/// adjusting the instantiate message by `customize_msg`. The market stays in the LP phase.
+pub fn setup_market_with_liquidity(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    customize_msg: impl FnOnce(&mut cw_zll_std_market::msg::InstantiateMsg),
+) -> MarketContract {
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ];
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = create_martket_setup_with(app, asset_infos, |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+        customize_msg(msg);
+    });
+
+    try_to_deposit_liquidity(
+        app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    market_contract
+}
+
+/// Creates a market like [`setup_market_with_liquidity`] and moves on to the AMM phase.
+pub fn setup_market_in_amm_phase(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    customize_msg: impl FnOnce(&mut cw_zll_std_market::msg::InstantiateMsg),
+) -> MarketContract {
+    let market_contract = setup_market_with_liquidity(app, customize_msg);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    market_contract
+}
+
 pub fn create_cw20_token(app: &mut App<Empty, TerraQueryWrapper>) -> Cw20TokenContract {
     // deploy custom CW20 token that will serve as one of the pool's assets
     let cw20_token_code_id = app.store_code(Cw20TokenContract::contract_code());
@@ -131,10 +219,222 @@ pub fn create_cw20_token(app: &mut App<Empty, TerraQueryWrapper>) -> Cw20TokenCo
     Cw20TokenContract(cw20_token_addr)
 }
 
+pub fn create_oracle(app: &mut App<Empty, TerraQueryWrapper>) -> OracleContract {
+    let oracle_code_id = app.store_code(OracleContract::contract_code());
+    let oracle_addr = app
+        .instantiate_contract(
+            oracle_code_id,
+            Addr::unchecked(ADDR_ADMIN),
+            &Empty {},
+            &[],
+            "ZLL Mock Oracle",
+            None,
+        )
+        .unwrap();
+
+    OracleContract(oracle_addr)
+}
+
+pub fn create_allowlist(app: &mut App<Empty, TerraQueryWrapper>) -> AllowlistContract {
+    let allowlist_code_id = app.store_code(AllowlistContract::contract_code());
+    let allowlist_addr = app
+        .instantiate_contract(
+            allowlist_code_id,
+            Addr::unchecked(ADDR_ADMIN),
+            &Empty {},
+            &[],
+            "ZLL Mock Allowlist",
+            None,
+        )
+        .unwrap();
+
+    AllowlistContract(allowlist_addr)
+}
+
+/// Creates a factory owned by the admin with the code ids of all contracts of a market stack.
+pub fn create_factory(app: &mut App<Empty, TerraQueryWrapper>) -> FactoryContract {
+    let code_ids = CodeIds {
+        market: app.store_code(MarketContract::contract_code()),
+        liquidity_pool: app.store_code(LiquidityPoolContract::contract_code()),
+        liquidity_pool_token: app.store_code(LiquidityPoolTokenContract::contract_code()),
+        option_token: app.store_code(OptionTokenContract::contract_code()),
+    };
+    let factory_code_id = app.store_code(FactoryContract::contract_code());
+
+    let factory_addr = app
+        .instantiate_contract(
+            factory_code_id,
+            Addr::unchecked(ADDR_ADMIN),
+            &cw_zll_std_factory::msg::InstantiateMsg {
+                owner: ADDR_ADMIN.into(),
+                code_ids,
+            },
+            &[],
+            "ZLL Factory",
+            Some(ADDR_ADMIN.into()),
+        )
+        .unwrap();
+
+    FactoryContract(factory_addr)
+}
+
+/// Creates a meta pool of the given assets which values the collateral asset at `collateral_price`.
+pub fn create_meta_pool(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    asset_infos: [AssetInfo; 2],
+    collateral_price: Decimal,
+) -> MetaPoolContract {
+    let token_code_id = app.store_code(LiquidityPoolTokenContract::contract_code());
+    let meta_pool_code_id = app.store_code(MetaPoolContract::contract_code());
+
+    let meta_pool_addr = app
+        .instantiate_contract(
+            meta_pool_code_id,
+            Addr::unchecked(ADDR_META_POOL_MAINTAINER),
+            &cw_zll_std_meta_pool::msg::InstantiateMsg {
+                maintainer: ADDR_META_POOL_MAINTAINER.into(),
+                asset_infos,
+                token_code_id,
+                collateral_price: Some(collateral_price),
+                oracle: None,
+            },
+            &[],
+            "ZLL Meta Pool",
+            Some(ADDR_META_POOL_MAINTAINER.into()),
+        )
+        .unwrap();
+
+    let ConfigResponse { share_token, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            meta_pool_addr.clone(),
+            &cw_zll_std_meta_pool::msg::QueryMsg::Config {},
+        )
+        .unwrap();
+
+    MetaPoolContract(meta_pool_addr, share_token)
+}
+
+/// Sets the price of one unit of the `base` asset in units of the `quote` asset reported by the oracle.
+pub fn set_oracle_price(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    oracle_contract: &OracleContract,
+    base: AssetInfo,
+    quote: AssetInfo,
+    price: Decimal,
+) -> Result<AppResponse> {
+    let set_price_msg = oracle_contract
+        .call(
+            &crate::mock_oracle::ExecuteMsg::SetPrice {
+                base,
+                quote,
+                price,
+                publish_time: None,
+                confidence: None,
+            },
+            None,
+        )
+        .unwrap();
+
+    app.execute(Addr::unchecked(ADDR_ADMIN), set_price_msg)
+}
+
+pub fn try_to_borrow(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_borrower: &str,
+    expected_borrow: Asset,
+    pledged_collateral: Asset,
+) -> Result<AppResponse> {
+    let coins = match &pledged_collateral.info {
+        AssetInfo::NativeToken { denom } => vec![coin(pledged_collateral.amount.u128(), denom)],
+        _ => vec![],
+    };
+
+    let _ = app.init_bank_balance(&Addr::unchecked(addr_borrower), coins.clone());
+
+    let borrow_msg = market_contract
+        .call(
+            &cw_zll_std_market::msg::ExecuteMsg::Borrow {
+                expected_borrow,
+                pledged_collateral,
+                strike_id: None,
+            },
+            Some(coins),
+        )
+        .unwrap();
+
+    app.execute(Addr::unchecked(addr_borrower), borrow_msg)
+}
+
+/// Borrows as much as the market lends against `uluna` of collateral.
+pub fn borrow_luna(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_borrower: &str,
+    uluna: u128,
+) -> Result<AppResponse> {
+    try_to_borrow(
+        app,
+        market_contract,
+        addr_borrower,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(uluna, "uluna"),
+    )
+}
+
+pub fn try_to_lend(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_lender: &str,
+    lent_asset: Asset,
+) -> Result<AppResponse> {
+    let coins = match &lent_asset.info {
+        AssetInfo::NativeToken { denom } => vec![coin(lent_asset.amount.u128(), denom)],
+        _ => vec![],
+    };
+
+    let _ = app.init_bank_balance(&Addr::unchecked(addr_lender), coins.clone());
+
+    let lend_msg = market_contract
+        .call(
+            &cw_zll_std_market::msg::ExecuteMsg::Lend {
+                expected_premium: Asset {
+                    info: lent_asset.info.clone(),
+                    amount: Uint128::zero(),
+                },
+                lent_asset,
+            },
+            Some(coins),
+        )
+        .unwrap();
+
+    app.execute(Addr::unchecked(addr_lender), lend_msg)
+}
+
 pub fn try_to_withdraw_liquidity(
     app: &mut App<Empty, TerraQueryWrapper>,
     _market_contract: &MarketContract,
     liquidity_pool_contract: &LiquidityPoolContract,
+) -> Result<AppResponse, anyhow::Error> {
+    try_to_withdraw_liquidity_with(
+        app,
+        liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        cw_zll_std_liquidity_pool::msg::Cw20HookMsg::WithdrawLiquidity {
+            recipient: None,
+            min_assets: None,
+            output_asset: None,
+        },
+    )
+}
+
+/// Sends all LP tokens of the liquidity provider to the pool with the given withdraw message.
+pub fn try_to_withdraw_liquidity_with(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+    withdraw_msg: cw_zll_std_liquidity_pool::msg::Cw20HookMsg,
 ) -> Result<AppResponse, anyhow::Error> {
     let PairInfo {
         liquidity_token, ..
@@ -147,20 +447,19 @@ pub fn try_to_withdraw_liquidity(
         .unwrap();
 
     let provider_lp_token_balance = cw20::Cw20Contract(liquidity_token.clone())
-        .balance(app, ADDR_LIQUIDITY_PROVIDER)
+        .balance(app, addr_liquidity_provider)
         .unwrap();
 
     let send_lp_tokens_to_for_burning_msg = cw20::Cw20Contract(liquidity_token)
         .call(Cw20ExecuteMsg::Send {
             contract: liquidity_pool_contract.addr().to_string(),
             amount: provider_lp_token_balance,
-            msg: to_binary(&cw_zll_std_liquidity_pool::msg::Cw20HookMsg::WithdrawLiquidity {})
-                .unwrap(),
+            msg: to_binary(&withdraw_msg).unwrap(),
         })
         .unwrap();
 
     app.execute(
-        Addr::unchecked(ADDR_LIQUIDITY_PROVIDER),
+        Addr::unchecked(addr_liquidity_provider),
         send_lp_tokens_to_for_burning_msg,
     )
 }
@@ -170,6 +469,22 @@ pub fn try_to_deposit_liquidity(
     liquidity_pool_contract: &LiquidityPoolContract,
     addr_liquidity_provider: &str,
     assets_to_provide_as_liquidity: [Asset; 2],
+) -> Result<Vec<AppResponse>> {
+    try_to_deposit_liquidity_with_min_share_out(
+        app,
+        liquidity_pool_contract,
+        addr_liquidity_provider,
+        assets_to_provide_as_liquidity,
+        None,
+    )
+}
+
+pub fn try_to_deposit_liquidity_with_min_share_out(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+    assets_to_provide_as_liquidity: [Asset; 2],
+    min_share_out: Option<Uint128>,
 ) -> Result<Vec<AppResponse>> {
     let coins = assets_to_provide_as_liquidity
         .iter()
@@ -204,6 +519,7 @@ pub fn try_to_deposit_liquidity(
                 slippage_tolerance: None,
                 auto_stake: None,
                 receiver: None,
+                min_share_out,
             },
             Some(coins),
         )
@@ -214,3 +530,34 @@ pub fn try_to_deposit_liquidity(
 
     app.execute_multi(Addr::unchecked(addr_liquidity_provider), messages)
 }
+
+/// Queries the terms of a loan against `uluna` of collateral, at the given strike and taking the
+/// exposure of `account` into account when they are given.
+pub fn query_borrowing_terms(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    uluna: u128,
+    strike_id: Option<u64>,
+    account: Option<&str>,
+) -> BorrowingTermsResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetBorrowingTerms {
+                pledged_collateral: create_coin_asset(uluna, "uluna"),
+                strike_id,
+                account: account.map(String::from),
+            },
+        )
+        .unwrap()
+}
+
+pub fn query_lp_token_balance(
+    app: &App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    address: &str,
+) -> Uint128 {
+    cw20::Cw20Contract(liquidity_pool_contract.1.clone())
+        .balance(app, address)
+        .unwrap()
+}
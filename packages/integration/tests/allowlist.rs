@@ -0,0 +1,542 @@
+use cosmwasm_std::{coin, to_binary, Addr, Decimal, Empty, Uint128};
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_liquidity_pool::ContractError as LiquidityPoolContractError;
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_LIQUIDITY_PROVIDER_2, ADDR_MARKET_OPERATOR,
+        ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_LP_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{AllowlistContract, LiquidityPoolContract, MarketContract},
+    mock_allowlist,
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{ap::AssetInfo, asset::create_coin_asset};
+use cw_zll_std_market::{
+    response::{
+        AllowlistResponse, AllowlistedAccount, AllowlistedAccountsResponse,
+        EarlyCloseQuoteResponse, ExitQuoteResponse, IsAllowedResponse,
+    },
+    state::{Allowlist, AllowlistRole, AllowlistRoles},
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn operator_restricts_roles_to_allowlisted_accounts() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    let restricted_roles = AllowlistRoles {
+        borrower: true,
+        liquidity_provider: true,
+        ..AllowlistRoles::default()
+    };
+
+    let response = try_to_set_allowlist(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        restricted_roles.clone(),
+        None,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_set_allowlist(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        restricted_roles,
+        None,
+    );
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to set the allowlist mode"
+    );
+
+    try_to_update_allowlist(
+        &mut app,
+        &market_contract,
+        AllowlistRole::LiquidityProvider,
+        vec![ADDR_LIQUIDITY_PROVIDER],
+        vec![],
+    )
+    .unwrap();
+    try_to_update_allowlist(
+        &mut app,
+        &market_contract,
+        AllowlistRole::Borrower,
+        vec![ADDR_REGULAR_USER, ADDR_REGULAR_USER_2],
+        vec![ADDR_REGULAR_USER_2],
+    )
+    .unwrap();
+
+    let response = try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::NotAllowlisted {
+            role: AllowlistRole::LiquidityProvider,
+            address: ADDR_LIQUIDITY_PROVIDER_2.to_string(),
+        }
+        .to_string()
+    );
+
+    let response =
+        try_to_deposit_liquidity(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert!(
+        response.is_ok(),
+        "Allowlisted liquidity provider is able to deposit"
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    let response = test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::NotAllowlisted {
+            role: AllowlistRole::Borrower,
+            address: ADDR_REGULAR_USER_2.to_string(),
+        }
+        .to_string()
+    );
+
+    let response = test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    );
+
+    assert!(response.is_ok(), "Allowlisted borrower is able to borrow");
+
+    let response = test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        create_coin_asset(100_000_000, "uusd"),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Anyone is able to lend as the lender role is not restricted"
+    );
+
+    // the account removed from its only role is no longer listed
+    assert_eq!(
+        query_allowlisted_accounts(&app, &market_contract),
+        vec![
+            AllowlistedAccount {
+                address: Addr::unchecked(ADDR_LIQUIDITY_PROVIDER),
+                roles: AllowlistRoles {
+                    liquidity_provider: true,
+                    ..AllowlistRoles::default()
+                },
+            },
+            AllowlistedAccount {
+                address: Addr::unchecked(ADDR_REGULAR_USER),
+                roles: AllowlistRoles {
+                    borrower: true,
+                    ..AllowlistRoles::default()
+                },
+            },
+        ]
+    );
+
+    assert!(query_is_allowed(
+        &app,
+        &market_contract,
+        AllowlistRole::Lender,
+        ADDR_REGULAR_USER_2
+    ));
+    assert!(!query_is_allowed(
+        &app,
+        &market_contract,
+        AllowlistRole::Borrower,
+        ADDR_LIQUIDITY_PROVIDER
+    ));
+}
+
+#[test]
+fn allowlist_contract_decides_on_restricted_roles() {
+    let mut app = test_env::mock_app();
+
+    let allowlist_contract = test_env::create_allowlist(&mut app);
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    let restricted_roles = AllowlistRoles {
+        lender: true,
+        ..AllowlistRoles::default()
+    };
+
+    try_to_set_allowlist(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        restricted_roles.clone(),
+        Some(&allowlist_contract),
+    )
+    .unwrap();
+
+    let AllowlistResponse { allowlist } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetAllowlist {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        allowlist,
+        Allowlist {
+            restricted_roles,
+            allowlist_contract: Some(allowlist_contract.addr()),
+        }
+    );
+
+    // the accounts allowlisted by the market operator are ignored
+    try_to_update_allowlist(
+        &mut app,
+        &market_contract,
+        AllowlistRole::Lender,
+        vec![ADDR_REGULAR_USER],
+        vec![],
+    )
+    .unwrap();
+
+    try_to_deposit_liquidity(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    let response = test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(100_000_000, "uusd"),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::NotAllowlisted {
+            role: AllowlistRole::Lender,
+            address: ADDR_REGULAR_USER.to_string(),
+        }
+        .to_string()
+    );
+
+    app.execute(
+        Addr::unchecked(ADDR_MARKET_OPERATOR),
+        allowlist_contract
+            .call(
+                &mock_allowlist::ExecuteMsg::SetAllowed {
+                    role: AllowlistRole::Lender,
+                    address: ADDR_REGULAR_USER.to_string(),
+                    is_allowed: true,
+                },
+                None,
+            )
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert!(query_is_allowed(
+        &app,
+        &market_contract,
+        AllowlistRole::Lender,
+        ADDR_REGULAR_USER
+    ));
+
+    let response = test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(100_000_000, "uusd"),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Lender allowlisted by the allowlist contract is able to lend"
+    );
+}
+
+/// Creates a market with an empty pool.
+#[test]
+fn accounts_removed_from_the_allowlist_can_still_exit_the_market() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    try_to_set_allowlist(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        AllowlistRoles {
+            borrower: true,
+            lender: true,
+            liquidity_provider: true,
+        },
+        None,
+    )
+    .unwrap();
+
+    let roles = [
+        (AllowlistRole::LiquidityProvider, ADDR_LIQUIDITY_PROVIDER),
+        (AllowlistRole::Borrower, ADDR_REGULAR_USER),
+        (AllowlistRole::Lender, ADDR_REGULAR_USER_2),
+    ];
+
+    for (role, address) in roles {
+        try_to_update_allowlist(&mut app, &market_contract, role, vec![address], vec![]).unwrap();
+    }
+
+    try_to_deposit_liquidity(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+    .unwrap();
+    test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        create_coin_asset(100_000_000, "uusd"),
+    )
+    .unwrap();
+
+    for (role, address) in roles {
+        try_to_update_allowlist(&mut app, &market_contract, role, vec![], vec![address]).unwrap();
+    }
+
+    let EarlyCloseQuoteResponse { amount, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetEarlyCloseQuote { loan_id: 1 },
+        )
+        .unwrap();
+    let _ = app.init_bank_balance(
+        &Addr::unchecked(ADDR_REGULAR_USER),
+        vec![coin(amount.amount.u128(), "uusd")],
+    );
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::CloseLoan {
+                    loan_id: 1,
+                    repayment: amount.clone(),
+                },
+                Some(vec![coin(amount.amount.u128(), "uusd")]),
+            )
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Borrower removed from the allowlist is able to close their loan"
+    );
+
+    let ExitQuoteResponse { amount, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetExitQuote { position_id: 1 },
+        )
+        .unwrap();
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER_2),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::ExitPosition {
+                    position_id: 1,
+                    expected_return: amount,
+                },
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Lender removed from the allowlist is able to exit their position"
+    );
+
+    // opting in to the rollover provides liquidity to the successor market
+    let response = app.execute(
+        Addr::unchecked(ADDR_LIQUIDITY_PROVIDER),
+        cw20::Cw20Contract(liquidity_pool_contract.1.clone())
+            .call(Cw20ExecuteMsg::Send {
+                contract: liquidity_pool_contract.addr().to_string(),
+                amount: Uint128::new(1_000),
+                msg: to_binary(&cw_zll_std_liquidity_pool::msg::Cw20HookMsg::OptInRollover {})
+                    .unwrap(),
+            })
+            .unwrap(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::NotAllowlisted {
+            role: AllowlistRole::LiquidityProvider,
+            address: ADDR_LIQUIDITY_PROVIDER.to_string(),
+        }
+        .to_string()
+    );
+}
+
+fn setup_market(app: &mut App<Empty, TerraQueryWrapper>) -> MarketSetup {
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ];
+
+    test_env::create_martket_setup_with(app, asset_infos, |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+    })
+}
+
+/// Deposits 20 LUNA & 5000 UST.
+fn try_to_deposit_liquidity(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+) -> anyhow::Result<Vec<AppResponse>> {
+    test_env::try_to_deposit_liquidity(
+        app,
+        liquidity_pool_contract,
+        addr_liquidity_provider,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, "uusd"),
+        ],
+    )
+}
+
+fn try_to_set_allowlist(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    restricted_roles: AllowlistRoles,
+    allowlist_contract: Option<&AllowlistContract>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::SetAllowlist {
+                    restricted_roles,
+                    allowlist_contract: allowlist_contract
+                        .map(|allowlist_contract| allowlist_contract.addr().to_string()),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_update_allowlist(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    role: AllowlistRole,
+    add: Vec<&str>,
+    remove: Vec<&str>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_MARKET_OPERATOR),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::UpdateAllowlist {
+                    role,
+                    add: add.into_iter().map(String::from).collect(),
+                    remove: remove.into_iter().map(String::from).collect(),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_allowlisted_accounts(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> Vec<AllowlistedAccount> {
+    let AllowlistedAccountsResponse { accounts } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetAllowlistedAccounts {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    accounts
+}
+
+fn query_is_allowed(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    role: AllowlistRole,
+    address: &str,
+) -> bool {
+    let IsAllowedResponse { is_allowed } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::IsAllowed {
+                role,
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+
+    is_allowed
+}
@@ -0,0 +1,292 @@
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER, ADDR_REGULAR_USER_2,
+        BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{MarketContract, OracleContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{ap::AssetInfo, asset::create_coin_asset};
+use cw_zll_std_market::{
+    response::PositionResponse,
+    state::{Position, PositionStatus},
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn market_operator_can_settle_a_batch_of_puts() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_positions(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response =
+        try_to_batch_settle_puts(&mut app, &market_contract, ADDR_MARKET_OPERATOR, vec![1]);
+
+    assert!(
+        response.is_ok(),
+        "Market operator can settle a batch of puts in the settlement phase"
+    );
+
+    let response = try_to_batch_settle_puts(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        vec![1, 2, 7],
+    )
+    .unwrap();
+
+    assert_eq!(
+        attribute(&response, "position_1"),
+        Some("skipped: already settled".to_string())
+    );
+    assert_eq!(
+        attribute(&response, "position_2"),
+        Some("exercised".to_string())
+    );
+    assert_eq!(
+        attribute(&response, "position_7"),
+        Some("skipped: not found".to_string())
+    );
+    assert_eq!(attribute(&response, "settled"), Some("1".to_string()));
+    assert_eq!(attribute(&response, "skipped"), Some("2".to_string()));
+
+    assert_eq!(
+        query_position(&app, &market_contract, 2).status,
+        PositionStatus::Exercised
+    );
+}
+
+#[test]
+fn only_market_operator_can_process_batches() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_positions(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response =
+        try_to_batch_settle_puts(&mut app, &market_contract, ADDR_REGULAR_USER, vec![1, 2]);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    // nothing from the failed batch was settled
+    assert_eq!(
+        query_position(&app, &market_contract, 1).status,
+        PositionStatus::Open
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    let response =
+        try_to_batch_claim_collateral(&mut app, &market_contract, ADDR_REGULAR_USER, vec![1]);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_batch_claim_collateral(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        (1..=101).collect(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::BatchTooLarge {
+            batch_size: 101,
+            max_batch_size: 100,
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn market_operator_can_process_a_batch_of_claims() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_positions(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    let response = try_to_batch_claim_collateral(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        vec![1, 2, 1],
+    )
+    .unwrap();
+
+    assert_eq!(
+        attribute(&response, "position_2"),
+        Some("claimed: 400000uluna".to_string())
+    );
+    assert_eq!(attribute(&response, "claimed"), Some("2".to_string()));
+    assert_eq!(attribute(&response, "skipped"), Some("1".to_string()));
+
+    // each lender receives 0.4 LUNA for their 100 UST
+    for lender in [ADDR_REGULAR_USER, ADDR_REGULAR_USER_2] {
+        assert_eq!(
+            app.wrap().query_balance(lender, "uluna").unwrap().amount,
+            Uint128::new(400_000)
+        );
+    }
+
+    let position = query_position(&app, &market_contract, 1);
+
+    assert_eq!(position.status, PositionStatus::Exercised);
+    assert!(
+        position.is_claimed,
+        "Claimed positions are marked as claimed"
+    );
+}
+
+fn asset_infos() -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ]
+}
+
+/// Creates a market with two lenders of 100 UST each, struck at 250 UST per LUNA, and moves the
+/// oracle price to 200 UST per LUNA so both PUT options are in the money.
+fn setup_market_with_positions(app: &mut App<Empty, TerraQueryWrapper>) -> MarketContract {
+    let oracle_contract = test_env::create_oracle(app);
+
+    set_luna_price(app, &oracle_contract, 250);
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = test_env::create_martket_setup_with(app, asset_infos(), |msg| {
+        msg.oracle = Some(oracle_contract.addr().to_string());
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(2_000_000, "uluna"),
+            create_coin_asset(500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    for lender in [ADDR_REGULAR_USER, ADDR_REGULAR_USER_2] {
+        test_env::try_to_lend(
+            app,
+            &market_contract,
+            lender,
+            create_coin_asset(100_000_000, "uusd"),
+        )
+        .unwrap();
+    }
+
+    set_luna_price(app, &oracle_contract, 200);
+
+    market_contract
+}
+
+fn set_luna_price(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    oracle_contract: &OracleContract,
+    price: u128,
+) {
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        app,
+        oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(price, 1u128),
+    )
+    .unwrap();
+}
+
+fn try_to_batch_settle_puts(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    position_ids: Vec<u64>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::BatchSettlePuts { position_ids },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_batch_claim_collateral(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    position_ids: Vec<u64>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::BatchClaimCollateral { position_ids },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_position(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    position_id: u64,
+) -> Position {
+    let PositionResponse { position } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPosition { position_id },
+        )
+        .unwrap();
+
+    position
+}
+
+/// Returns the value of the first attribute with the given key emitted by the contracts.
+fn attribute(response: &AppResponse, key: &str) -> Option<String> {
+    response
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.clone())
+}
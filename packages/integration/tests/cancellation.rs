@@ -0,0 +1,306 @@
+use cosmwasm_std::{coins, Addr, BankMsg, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER, BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::MarketContract,
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{ap::Asset, asset::create_coin_asset};
+use cw_zll_std_market::{borrow::BorrowingTermsResponse, response::MarketPhase};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn market_is_cancelled_when_minimum_liquidity_is_not_reached_by_the_end_of_lp_phase() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market_with_deposit(&mut app, Uint128::new(1_000_000_000)); // 1000 UST
+
+    // Last block of the LP phase
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT;
+    });
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::ProvidingLiquidity
+    );
+
+    // First block after the LP phase
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::Cancelled
+    );
+
+    let response =
+        test_env::try_to_withdraw_liquidity(&mut app, &market_contract, &liquidity_pool_contract);
+
+    assert!(
+        response.is_ok(),
+        "withdrawing liquidity must be possible in a cancelled market"
+    );
+
+    // the provider gets back the pro-rata part of the pool, only the locked minimum liquidity stays
+    let share_ratio = Decimal::from_ratio(31_621_776u128, 31_622_776u128);
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_LIQUIDITY_PROVIDER, "uusd")
+            .unwrap()
+            .amount,
+        Uint128::new(500_000_000) * share_ratio
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_LIQUIDITY_PROVIDER, "uluna")
+            .unwrap()
+            .amount,
+        Uint128::new(2_000_000) * share_ratio
+    );
+}
+
+#[test]
+fn market_is_not_cancelled_when_minimum_liquidity_is_reached_by_the_end_of_lp_phase() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market_with_deposit(&mut app, Uint128::new(500_000_000)); // 500 UST
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::AutomatedMarketMaker
+    );
+}
+
+#[test]
+fn recorded_cancellation_is_not_reversed_by_a_donation() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market_with_deposit(&mut app, Uint128::new(1_000_000_000)); // 1000 UST
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    // the withdrawal records the cancellation before paying out
+    test_env::try_to_withdraw_liquidity(&mut app, &market_contract, &liquidity_pool_contract)
+        .unwrap();
+
+    app.init_bank_balance(
+        &Addr::unchecked(ADDR_REGULAR_USER),
+        coins(2_000_000_000, "uusd"),
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        BankMsg::Send {
+            to_address: liquidity_pool_contract.addr().to_string(),
+            amount: coins(2_000_000_000, "uusd"),
+        }
+        .into(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::Cancelled
+    );
+}
+
+#[test]
+fn market_without_positions_is_not_cancelled_by_withdrawals_after_settlement() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market_with_deposit(&mut app, Uint128::new(500_000_000)); // 500 UST
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    let response =
+        test_env::try_to_withdraw_liquidity(&mut app, &market_contract, &liquidity_pool_contract);
+
+    assert!(
+        response.is_ok(),
+        "withdrawing liquidity must be possible after settlement"
+    );
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::PostSettlement
+    );
+}
+
+#[test]
+fn market_operator_can_cancel_the_market_before_any_borrowing() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market_with_deposit(&mut app, Uint128::new(500_000_000)); // 500 UST
+
+    let response = try_to_cancel_market(&mut app, &market_contract, ADDR_MARKET_OPERATOR);
+
+    assert!(response.is_ok(), "market operator can cancel the market");
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::Cancelled
+    );
+
+    let response = test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(2_000_000, "uluna"),
+            create_coin_asset(500_000_000, "uusd"),
+        ],
+    );
+
+    assert!(
+        response.is_err(),
+        "depositing liquidity is not possible in a cancelled market"
+    );
+
+    let response =
+        test_env::try_to_withdraw_liquidity(&mut app, &market_contract, &liquidity_pool_contract);
+
+    assert!(
+        response.is_ok(),
+        "withdrawing liquidity must be possible in a cancelled market"
+    );
+}
+
+#[test]
+fn only_market_operator_can_cancel_the_market() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market_with_deposit(&mut app, Uint128::new(500_000_000)); // 500 UST
+
+    let response = try_to_cancel_market(&mut app, &market_contract, ADDR_REGULAR_USER);
+
+    assert!(
+        response.is_err(),
+        "only the market operator can cancel the market"
+    );
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::ProvidingLiquidity
+    );
+}
+
+#[test]
+fn market_operator_cannot_cancel_the_market_after_borrowing_started() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market_with_deposit(&mut app, Uint128::new(500_000_000)); // 500 UST
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    let pledged_collateral = create_coin_asset(111_000_000, "uluna");
+
+    let BorrowingTermsResponse { borrow, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetBorrowingTerms {
+                pledged_collateral: pledged_collateral.clone(),
+                strike_id: None,
+                account: None,
+            },
+        )
+        .unwrap();
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        borrow,
+        pledged_collateral,
+    )
+    .unwrap();
+
+    let response = try_to_cancel_market(&mut app, &market_contract, ADDR_MARKET_OPERATOR);
+
+    assert!(
+        response.is_err(),
+        "market cannot be cancelled once borrowing has started"
+    );
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::AutomatedMarketMaker
+    );
+}
+
+fn setup_market_with_deposit(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    minimum_liquidity: Uint128,
+) -> MarketSetup {
+    //provide 500 UST & 2 LUNA
+    let assets_to_provide_as_liquidity: [Asset; 2] = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos, |msg| {
+        msg.minimum_liquidity = Some(minimum_liquidity);
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    market_setup
+}
+
+fn try_to_cancel_market(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(&cw_zll_std_market::msg::ExecuteMsg::CancelMarket {}, None)
+            .unwrap(),
+    )
+}
@@ -0,0 +1,251 @@
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER, ADDR_REGULAR_USER_2},
+    contract_helpers::ContractBase,
+    contract_mocks::MarketContract,
+    test_env,
+};
+use cw_zll_std_liquidity_pool::asset::create_coin_asset;
+use cw_zll_std_market::{
+    borrow::BorrowingTermsResponse, lend::LendingTermsResponse, response::CapacityLimitsResponse,
+    state::CapacityLimits,
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn market_operator_can_set_capacity_limits() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+
+    let capacity_limits = CapacityLimits {
+        max_position_size: Some(Uint128::new(200_000_000)),
+        max_account_exposure: None,
+        max_utilization: Some(Decimal::percent(50)),
+    };
+
+    let response = try_to_set_capacity_limits(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        capacity_limits.clone(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_set_capacity_limits(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        capacity_limits.clone(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to set the capacity limits"
+    );
+
+    let response: CapacityLimitsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCapacityLimits {},
+        )
+        .unwrap();
+
+    assert_eq!(response.capacity_limits, capacity_limits);
+}
+
+#[test]
+fn positions_are_limited_in_size_and_per_account() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+
+    try_to_set_capacity_limits(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        CapacityLimits {
+            max_position_size: Some(Uint128::new(200_000_000)),
+            max_account_exposure: Some(Uint128::new(300_000_000)),
+            max_utilization: None,
+        },
+    )
+    .unwrap();
+
+    // 5000 UST - 20 LUNA * 5000 UST / 21 LUNA
+    let response = test_env::borrow_luna(&mut app, &market_contract, ADDR_REGULAR_USER, 1_000_000);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::PositionSizeExceeded {
+            size: Uint128::new(238_095_239),
+            max_position_size: Uint128::new(200_000_000),
+        }
+        .to_string()
+    );
+
+    try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER, 150_000_000).unwrap();
+
+    let BorrowingTermsResponse {
+        remaining_capacity, ..
+    } = test_env::query_borrowing_terms(
+        &app,
+        &market_contract,
+        500_000,
+        None,
+        Some(ADDR_REGULAR_USER),
+    );
+
+    assert_eq!(remaining_capacity, Some(Uint128::new(150_000_000)));
+
+    let response = try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER, 200_000_000);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::AccountExposureExceeded {
+            size: Uint128::new(200_000_000),
+            remaining_exposure: Uint128::new(150_000_000),
+        }
+        .to_string()
+    );
+
+    let response = try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER_2, 200_000_000);
+
+    assert!(
+        response.is_ok(),
+        "The exposure of other accounts does not count against the account"
+    );
+}
+
+#[test]
+fn positions_are_limited_by_pool_utilization() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+
+    try_to_set_capacity_limits(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        CapacityLimits {
+            max_position_size: None,
+            max_account_exposure: None,
+            max_utilization: Some(Decimal::percent(10)),
+        },
+    )
+    .unwrap();
+
+    // 10% of 20 LUNA at 250 UST per LUNA
+    let LendingTermsResponse {
+        remaining_capacity, ..
+    } = query_lending_terms(&app, &market_contract, 100_000_000);
+
+    assert_eq!(remaining_capacity, Some(Uint128::new(500_000_000)));
+
+    let LendingTermsResponse { premium, .. } =
+        query_lending_terms(&app, &market_contract, 400_000_000);
+
+    try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER, 400_000_000).unwrap();
+
+    let response = try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER_2, 200_000_000);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::UtilizationExceeded {
+            size: Uint128::new(200_000_000),
+            remaining_capacity: Uint128::new(100_000_000),
+        }
+        .to_string()
+    );
+
+    // 10% of 5000 UST less the premium paid to the lender
+    let lending_reserve = Uint128::new(5_000_000_000) - premium.amount;
+    let BorrowingTermsResponse {
+        remaining_capacity, ..
+    } = test_env::query_borrowing_terms(
+        &app,
+        &market_contract,
+        1_000_000,
+        None,
+        Some(ADDR_REGULAR_USER),
+    );
+
+    assert_eq!(
+        remaining_capacity,
+        Some(lending_reserve.multiply_ratio(1u128, 10u128))
+    );
+
+    // the lending reserve - 20 LUNA * the lending reserve / 23 LUNA
+    let response = test_env::borrow_luna(&mut app, &market_contract, ADDR_REGULAR_USER, 3_000_000);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::UtilizationExceeded {
+            size: lending_reserve
+                - (Uint128::new(20_000_000) * lending_reserve) / Uint128::new(23_000_000),
+            remaining_capacity: lending_reserve.multiply_ratio(1u128, 10u128),
+        }
+        .to_string()
+    );
+
+    let response = test_env::borrow_luna(&mut app, &market_contract, ADDR_REGULAR_USER, 2_000_000);
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to borrow within the maximum utilization"
+    );
+}
+
+fn try_to_set_capacity_limits(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    capacity_limits: CapacityLimits,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::SetCapacityLimits { capacity_limits },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_lend(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_lender: &str,
+    uusd: u128,
+) -> anyhow::Result<AppResponse> {
+    test_env::try_to_lend(
+        app,
+        market_contract,
+        addr_lender,
+        create_coin_asset(uusd, "uusd"),
+    )
+}
+
+fn query_lending_terms(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    uusd: u128,
+) -> LendingTermsResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLendingTerms {
+                lent_asset: create_coin_asset(uusd, "uusd"),
+                account: None,
+            },
+        )
+        .unwrap()
+}
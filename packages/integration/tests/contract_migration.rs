@@ -0,0 +1,174 @@
+use cosmwasm_std::{Addr, Empty, Event};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{ADDR_ADMIN, ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER},
+    contract_helpers::ContractBase,
+    contract_mocks::{LiquidityPoolContract, LiquidityPoolTokenContract, MarketContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn market_administers_its_liquidity_pool_and_lp_token() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    assert_eq!(
+        app.contract_data(&liquidity_pool_contract.addr())
+            .unwrap()
+            .admin,
+        Some(market_contract.addr())
+    );
+    assert_eq!(
+        app.contract_data(&liquidity_pool_contract.lp_token_contract().addr())
+            .unwrap()
+            .admin,
+        Some(market_contract.addr())
+    );
+}
+
+#[test]
+fn market_operator_can_migrate_the_liquidity_pool() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    let liquidity_pool_code_id = app.store_code(LiquidityPoolContract::contract_code());
+    let liquidity_pool_token_code_id = app.store_code(LiquidityPoolTokenContract::contract_code());
+
+    let response = try_to_migrate_liquidity_pool(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        Some(liquidity_pool_code_id),
+        Some(liquidity_pool_token_code_id),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_migrate_liquidity_pool(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        Some(liquidity_pool_code_id),
+        Some(liquidity_pool_token_code_id),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to migrate the liquidity pool and its LP token"
+    );
+
+    assert_eq!(
+        app.contract_data(&liquidity_pool_contract.addr())
+            .unwrap()
+            .code_id,
+        liquidity_pool_code_id as usize
+    );
+    assert_eq!(
+        app.contract_data(&liquidity_pool_contract.lp_token_contract().addr())
+            .unwrap()
+            .code_id,
+        liquidity_pool_token_code_id as usize
+    );
+
+    // the LP token would be migrated to the code of another contract
+    let response = try_to_migrate_liquidity_pool(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        None,
+        Some(liquidity_pool_code_id),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        "Cannot migrate contract `crates.io:cw-zll-liquidity-pool-token` to \
+         `crates.io:cw-zll-liquidity-pool`"
+    );
+}
+
+#[test]
+fn admin_can_migrate_the_market() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app);
+
+    let market_contract_code_id = app.store_code(MarketContract::contract_code());
+
+    let response = app.migrate_contract(
+        Addr::unchecked(ADDR_ADMIN),
+        market_contract.addr(),
+        &cw_zll_std_market::msg::MigrateMsg {},
+        market_contract_code_id,
+    );
+
+    assert!(
+        response
+            .unwrap()
+            .has_event(&Event::new("wasm").add_attribute("previous_version", "0.1.0")),
+        "Admin is able to migrate the market"
+    );
+
+    let liquidity_pool_code_id = app.store_code(LiquidityPoolContract::contract_code());
+
+    let response = app.migrate_contract(
+        Addr::unchecked(ADDR_ADMIN),
+        market_contract.addr(),
+        &cw_zll_std_market::msg::MigrateMsg {},
+        liquidity_pool_code_id,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        "Cannot migrate contract `crates.io:cw-zll-market` to `crates.io:cw-zll-liquidity-pool`"
+    );
+}
+
+fn setup_market(app: &mut App<Empty, TerraQueryWrapper>) -> MarketSetup {
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ];
+
+    test_env::create_martket_setup_with(app, asset_infos, |_| {})
+}
+
+fn try_to_migrate_liquidity_pool(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    liquidity_pool_code_id: Option<u64>,
+    liquidity_pool_token_code_id: Option<u64>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::MigrateLiquidityPool {
+                    liquidity_pool_code_id,
+                    liquidity_pool_token_code_id,
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
@@ -0,0 +1,375 @@
+use cosmwasm_std::{Addr, Decimal, Empty};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_liquidity_pool::ContractError as LiquidityPoolContractError;
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_GUARDIAN, ADDR_LIQUIDITY_PROVIDER, ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER,
+        BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{LiquidityPoolContract, MarketContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{ap::AssetInfo, asset::create_coin_asset};
+use cw_zll_std_market::{
+    response::PausedActionsResponse,
+    state::{PausableAction, PausedActions},
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn only_guardian_can_pause_actions() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app);
+
+    for sender in [ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER] {
+        let response = try_to_pause(
+            &mut app,
+            &market_contract,
+            sender,
+            vec![PausableAction::Borrow],
+        );
+
+        assert_eq!(
+            response.unwrap_err().root_cause().to_string(),
+            ContractError::Unauthorized {}.to_string()
+        );
+    }
+
+    let response = try_to_pause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Borrow, PausableAction::Withdraw],
+    );
+
+    assert!(response.is_ok(), "Guardian is able to pause actions");
+
+    assert_eq!(
+        query_paused_actions(&app, &market_contract),
+        PausedActionsResponse {
+            guardian: Some(Addr::unchecked(ADDR_GUARDIAN)),
+            paused_actions: PausedActions {
+                borrow: true,
+                withdraw: true,
+                ..PausedActions::default()
+            },
+        }
+    );
+
+    let response = try_to_unpause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Withdraw],
+    );
+
+    assert!(response.is_ok(), "Guardian is able to unpause actions");
+
+    assert_eq!(
+        query_paused_actions(&app, &market_contract).paused_actions,
+        PausedActions {
+            borrow: true,
+            ..PausedActions::default()
+        }
+    );
+}
+
+#[test]
+fn market_rejects_paused_actions() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    deposit_liquidity(&mut app, &liquidity_pool_contract).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    try_to_pause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Borrow],
+    )
+    .unwrap();
+
+    let response = borrow_against_1_luna(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::ActionPaused {
+            action: PausableAction::Borrow
+        }
+        .to_string()
+    );
+
+    let response = test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(100_000_000, "uusd"),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Actions which are not paused remain available"
+    );
+
+    try_to_unpause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Borrow],
+    )
+    .unwrap();
+
+    let response = borrow_against_1_luna(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to borrow once borrowing is unpaused"
+    );
+}
+
+#[test]
+fn market_rejects_withdrawals_of_assets_while_withdrawals_are_paused() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    deposit_liquidity(&mut app, &liquidity_pool_contract).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    borrow_against_1_luna(&mut app, &market_contract).unwrap();
+    test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(100_000_000, "uusd"),
+    )
+    .unwrap();
+
+    try_to_pause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Withdraw],
+    )
+    .unwrap();
+
+    let paused_error = ContractError::ActionPaused {
+        action: PausableAction::Withdraw,
+    }
+    .to_string();
+
+    let response = try_to_execute(
+        &mut app,
+        &market_contract,
+        cw_zll_std_market::msg::ExecuteMsg::CloseLoan {
+            loan_id: 1,
+            repayment: create_coin_asset(0, "uusd"),
+        },
+    );
+
+    assert_eq!(response.unwrap_err().root_cause().to_string(), paused_error);
+
+    let response = try_to_execute(
+        &mut app,
+        &market_contract,
+        cw_zll_std_market::msg::ExecuteMsg::ExitPosition {
+            position_id: 1,
+            expected_return: create_coin_asset(0, "uusd"),
+        },
+    );
+
+    assert_eq!(response.unwrap_err().root_cause().to_string(), paused_error);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    let response = try_to_execute(
+        &mut app,
+        &market_contract,
+        cw_zll_std_market::msg::ExecuteMsg::ClaimCollateral { position_id: 1 },
+    );
+
+    assert_eq!(response.unwrap_err().root_cause().to_string(), paused_error);
+
+    try_to_unpause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Withdraw],
+    )
+    .unwrap();
+
+    let response = try_to_execute(
+        &mut app,
+        &market_contract,
+        cw_zll_std_market::msg::ExecuteMsg::ClaimCollateral { position_id: 1 },
+    );
+
+    assert!(
+        response.is_ok(),
+        "Lender is able to claim once withdrawals are unpaused"
+    );
+}
+
+#[test]
+fn liquidity_pool_rejects_deposits_paused_in_the_market() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    try_to_pause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Deposit],
+    )
+    .unwrap();
+
+    let response = deposit_liquidity(&mut app, &liquidity_pool_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::ActionPaused {
+            action: PausableAction::Deposit
+        }
+        .to_string()
+    );
+
+    try_to_unpause(
+        &mut app,
+        &market_contract,
+        ADDR_GUARDIAN,
+        vec![PausableAction::Deposit],
+    )
+    .unwrap();
+
+    let response = deposit_liquidity(&mut app, &liquidity_pool_contract);
+
+    assert!(
+        response.is_ok(),
+        "Liquidity provider is able to deposit once deposits are unpaused"
+    );
+}
+
+/// Creates a market in the LP phase with a guardian.
+fn setup_market(app: &mut App<Empty, TerraQueryWrapper>) -> MarketSetup {
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ];
+
+    test_env::create_martket_setup_with(app, asset_infos, |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+        msg.guardian = Some(ADDR_GUARDIAN.to_string());
+    })
+}
+
+/// Deposits 20 LUNA & 5000 UST into the pool.
+fn deposit_liquidity(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+) -> anyhow::Result<Vec<AppResponse>> {
+    test_env::try_to_deposit_liquidity(
+        app,
+        liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, "uusd"),
+        ],
+    )
+}
+
+fn borrow_against_1_luna(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    test_env::try_to_borrow(
+        app,
+        market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+}
+
+fn try_to_execute(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    msg: cw_zll_std_market::msg::ExecuteMsg,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract.call(&msg, None).unwrap(),
+    )
+}
+
+fn try_to_pause(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    actions: Vec<PausableAction>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(&cw_zll_std_market::msg::ExecuteMsg::Pause { actions }, None)
+            .unwrap(),
+    )
+}
+
+fn try_to_unpause(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    actions: Vec<PausableAction>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::Unpause { actions },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_paused_actions(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> PausedActionsResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPausedActions {},
+        )
+        .unwrap()
+}
@@ -0,0 +1,238 @@
+use cosmwasm_std::{coin, Addr, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::MarketContract,
+    test_env,
+};
+use cw_zll_std_liquidity_pool::asset::create_coin_asset;
+use cw_zll_std_market::{
+    borrow::BorrowingTermsResponse,
+    response::{EarlyCloseQuoteResponse, LoanResponse, PremiumEarnedResponse},
+    state::LoanStatus,
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+/// The loans are taken out right after the LP phase ends, so half of their term has passed here.
+const BLOCK_HEIGHT_HALF_TERM: u64 = BLOCK_HEIGHT_LP_ENDS_AT + 1 + 555;
+
+#[test]
+fn borrower_can_check_the_early_close_quote() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+    let BorrowingTermsResponse {
+        borrow, interest, ..
+    } = borrow_one_luna(&mut app, &market_contract);
+
+    // 1 LUNA * 250 UST * 20% * sqrt(1110 / 4_204_800 years) / sqrt(2 * pi)
+    assert_eq!(interest, create_coin_asset(324_092, "uusd"));
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_HALF_TERM;
+    });
+
+    let quote = query_early_close_quote(&app, &market_contract, 1);
+
+    // the unexpired half of the option is worth sqrt(1/2) of the interest at the model price, so
+    // the pool buys it back for the time-prorated half of the interest
+    let interest_due = interest.amount - interest.amount.multiply_ratio(555u128, 1110u128);
+
+    assert_eq!(
+        quote,
+        EarlyCloseQuoteResponse {
+            principal: create_coin_asset((borrow.amount - interest.amount).u128(), "uusd"),
+            interest_due: create_coin_asset(interest_due.u128(), "uusd"),
+            amount: create_coin_asset(
+                (borrow.amount - interest.amount + interest_due).u128(),
+                "uusd"
+            ),
+            collateral: create_coin_asset(1_000_000, "uluna"),
+        }
+    );
+}
+
+#[test]
+fn borrower_can_close_the_loan_early_during_amm_phase() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+    borrow_one_luna(&mut app, &market_contract);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_HALF_TERM;
+    });
+
+    let EarlyCloseQuoteResponse {
+        amount,
+        interest_due,
+        ..
+    } = query_early_close_quote(&app, &market_contract, 1);
+
+    let response = try_to_close_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        amount.amount + Uint128::new(100),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to close the loan during the AMM phase"
+    );
+
+    // the collateral is returned and the excess of the repayment is refunded
+    assert_eq!(
+        app.wrap().query_all_balances(ADDR_REGULAR_USER).unwrap(),
+        vec![coin(1_000_000, "uluna"), coin(100, "uusd")]
+    );
+
+    let LoanResponse { loan } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLoan { loan_id: 1 },
+        )
+        .unwrap();
+
+    assert_eq!(loan.status, LoanStatus::Closed);
+
+    let PremiumEarnedResponse { premium } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPremiumEarned {},
+        )
+        .unwrap();
+
+    assert!(!premium.amount.is_zero());
+    assert_eq!(premium, interest_due);
+
+    let response = try_to_close_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        amount.amount,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::LoanAlreadyRepaid { loan_id: 1 }.to_string()
+    );
+}
+
+#[test]
+fn loan_cannot_be_closed_early_for_less_than_the_quote() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+    borrow_one_luna(&mut app, &market_contract);
+
+    let EarlyCloseQuoteResponse { amount, .. } = query_early_close_quote(&app, &market_contract, 1);
+
+    let response = try_to_close_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        amount.amount - Uint128::new(1),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::InsufficientRepayment {
+            repayment: amount.amount - Uint128::new(1),
+            amount_due: amount.amount,
+        }
+        .to_string()
+    );
+
+    let response = try_to_close_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        1,
+        amount.amount,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response = try_to_close_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        amount.amount,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string(),
+        "Loans can only be closed early during the AMM phase"
+    );
+}
+
+/// Borrows against 1 LUNA and returns the borrowing terms of the loan.
+fn borrow_one_luna(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> BorrowingTermsResponse {
+    let borrowing_terms =
+        test_env::query_borrowing_terms(app, market_contract, 1_000_000, None, None);
+
+    test_env::borrow_luna(app, market_contract, ADDR_REGULAR_USER, 1_000_000).unwrap();
+
+    borrowing_terms
+}
+
+fn try_to_close_loan(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_borrower: &str,
+    loan_id: u64,
+    amount: Uint128,
+) -> anyhow::Result<AppResponse> {
+    let _ = app.init_bank_balance(
+        &Addr::unchecked(addr_borrower),
+        vec![coin(amount.u128(), "uusd")],
+    );
+
+    app.execute(
+        Addr::unchecked(addr_borrower),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::CloseLoan {
+                    loan_id,
+                    repayment: create_coin_asset(amount.u128(), "uusd"),
+                },
+                Some(vec![coin(amount.u128(), "uusd")]),
+            )
+            .unwrap(),
+    )
+}
+
+fn query_early_close_quote(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    loan_id: u64,
+) -> EarlyCloseQuoteResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetEarlyCloseQuote { loan_id },
+        )
+        .unwrap()
+}
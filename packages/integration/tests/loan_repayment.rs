@@ -0,0 +1,402 @@
+use cosmwasm_std::{coin, Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_option_token::{
+    msg::TokenResponse,
+    state::{Metadata, OptionKind},
+};
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{MarketContract, OptionTokenContract},
+    test_env,
+};
+use cw_zll_std_liquidity_pool::asset::create_coin_asset;
+use cw_zll_std_market::{
+    borrow::BorrowingTermsResponse,
+    response::{LoanResponse, LockedAssetsResponse, OptionTokenResponse},
+    state::{Loan, LoanStatus},
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn borrower_takes_out_a_loan_backed_by_their_collateral() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+
+    let pledged_collateral = create_coin_asset(1_000_000, "uluna"); // 1 LUNA
+
+    let BorrowingTermsResponse {
+        borrow,
+        interest,
+        repayment,
+        ..
+    } = test_env::query_borrowing_terms(&app, &market_contract, 1_000_000, None, None);
+
+    // 5000 UST - 20 LUNA * 5000 UST / 21 LUNA
+    assert_eq!(borrow, create_coin_asset(238_095_239, "uusd"));
+    assert_eq!(repayment, borrow);
+
+    let response = test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        borrow.clone(),
+        pledged_collateral,
+    );
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to borrow during the AMM phase"
+    );
+
+    assert_eq!(
+        query_loan(&app, &market_contract, 1),
+        Loan {
+            id: 1,
+            owner: Addr::unchecked(ADDR_REGULAR_USER),
+            strike: Decimal::from_ratio(238_095_239u128, 1_000_000u128),
+            strike_id: None,
+            notional: borrow.amount,
+            collateral_amount: Uint128::new(1_000_000),
+            interest: interest.amount,
+            status: LoanStatus::Open,
+            parent_id: None,
+            created_at: BLOCK_HEIGHT_LP_ENDS_AT + 1,
+        }
+    );
+
+    // the interest is paid out of the borrowed amount
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER, "uusd")
+            .unwrap()
+            .amount,
+        borrow.amount - interest.amount
+    );
+
+    assert_eq!(
+        query_locked_assets(&app, &market_contract)[0],
+        create_coin_asset(1_000_000, "uluna")
+    );
+}
+
+#[test]
+fn borrower_can_repay_a_part_of_the_loan_during_settlement() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+    let notional = borrow_one_luna(&mut app, &market_contract);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let repayment = notional.multiply_ratio(1u128, 4u128);
+    // rounded down in favour of the pool
+    let released_collateral = Uint128::new(1_000_000).multiply_ratio(repayment, notional);
+
+    let response = try_to_repay_loan(&mut app, &market_contract, ADDR_REGULAR_USER, 1, repayment);
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to repay a part of the loan during the settlement phase"
+    );
+
+    let loan = query_loan(&app, &market_contract, 1);
+    let repaid_loan = query_loan(&app, &market_contract, 2);
+
+    assert_eq!(loan.status, LoanStatus::Open);
+    assert_eq!(loan.notional, notional - repayment);
+    assert_eq!(
+        loan.collateral_amount,
+        Uint128::new(1_000_000) - released_collateral
+    );
+
+    assert_eq!(repaid_loan.status, LoanStatus::Repaid);
+    assert_eq!(repaid_loan.parent_id, Some(1));
+    assert_eq!(repaid_loan.notional, repayment);
+    assert_eq!(repaid_loan.collateral_amount, released_collateral);
+    assert_eq!(repaid_loan.strike, loan.strike);
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER, "uluna")
+            .unwrap()
+            .amount,
+        released_collateral
+    );
+
+    try_to_repay_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        notional - repayment,
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_loan(&app, &market_contract, 1).status,
+        LoanStatus::Repaid
+    );
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER, "uluna")
+            .unwrap()
+            .amount,
+        Uint128::new(1_000_000)
+    );
+
+    assert_eq!(
+        query_locked_assets(&app, &market_contract)[0],
+        create_coin_asset(0, "uluna")
+    );
+
+    let response = try_to_repay_loan(&mut app, &market_contract, ADDR_REGULAR_USER, 1, notional);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::LoanAlreadyRepaid { loan_id: 1 }.to_string()
+    );
+}
+
+#[test]
+fn repayment_must_release_collateral_and_must_not_exceed_the_notional() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+    let notional = borrow_one_luna(&mut app, &market_contract);
+
+    let response = try_to_repay_loan(&mut app, &market_contract, ADDR_REGULAR_USER, 1, notional);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string(),
+        "Loans cannot be repaid during the AMM phase"
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response = try_to_repay_loan(&mut app, &market_contract, ADDR_REGULAR_USER_2, 1, notional);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_repay_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        notional + Uint128::new(1),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::InvalidRepayment {
+            repayment: notional + Uint128::new(1),
+            notional,
+        }
+        .to_string()
+    );
+
+    // 1 uusd is worth less than 1 uluna of the collateral
+    let response = try_to_repay_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        Uint128::new(1),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::InvalidZeroAmount {}.to_string()
+    );
+}
+
+#[test]
+fn collateral_of_unpaid_loans_belongs_to_the_pool_after_settlement() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |_| {});
+    let notional = borrow_one_luna(&mut app, &market_contract);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    assert_eq!(
+        query_locked_assets(&app, &market_contract)[0],
+        create_coin_asset(0, "uluna")
+    );
+
+    assert!(
+        try_to_repay_loan(&mut app, &market_contract, ADDR_REGULAR_USER, 1, notional).is_err(),
+        "Loans cannot be repaid once the settlement phase is over"
+    );
+}
+
+#[test]
+fn option_token_follows_the_remaining_notional_of_the_loan() {
+    let mut app = test_env::mock_app();
+
+    let option_token_code_id = app.store_code(OptionTokenContract::contract_code());
+    let market_contract = test_env::setup_market_in_amm_phase(&mut app, |msg| {
+        msg.option_token_code_id = Some(option_token_code_id);
+    });
+    let notional = borrow_one_luna(&mut app, &market_contract);
+    let strike = query_loan(&app, &market_contract, 1).strike;
+
+    let OptionTokenResponse { option_token } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetOptionToken {},
+        )
+        .unwrap();
+    let option_token = option_token.unwrap();
+
+    assert_eq!(
+        query_option_token(&app, &option_token, "call-1").unwrap(),
+        TokenResponse {
+            owner: Addr::unchecked(ADDR_REGULAR_USER),
+            metadata: Metadata {
+                kind: OptionKind::Call,
+                strike,
+                notional,
+                collateral_amount: Uint128::new(1_000_000),
+            },
+        }
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let repayment = notional.multiply_ratio(1u128, 4u128);
+    try_to_repay_loan(&mut app, &market_contract, ADDR_REGULAR_USER, 1, repayment).unwrap();
+
+    let loan = query_loan(&app, &market_contract, 1);
+
+    assert_eq!(
+        query_option_token(&app, &option_token, "call-1")
+            .unwrap()
+            .metadata,
+        Metadata {
+            kind: OptionKind::Call,
+            strike,
+            notional: notional - repayment,
+            collateral_amount: loan.collateral_amount,
+        },
+        "The option token stands for the remaining notional of a partially repaid loan"
+    );
+
+    try_to_repay_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        notional - repayment,
+    )
+    .unwrap();
+
+    assert!(
+        query_option_token(&app, &option_token, "call-1").is_err(),
+        "The option token of a repaid loan is burned"
+    );
+}
+
+/// Borrows against 1 LUNA and returns the notional of the loan.
+fn borrow_one_luna(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> Uint128 {
+    let BorrowingTermsResponse { borrow, .. } =
+        test_env::query_borrowing_terms(app, market_contract, 1_000_000, None, None);
+
+    test_env::borrow_luna(app, market_contract, ADDR_REGULAR_USER, 1_000_000).unwrap();
+
+    borrow.amount
+}
+
+fn try_to_repay_loan(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_borrower: &str,
+    loan_id: u64,
+    amount: Uint128,
+) -> anyhow::Result<AppResponse> {
+    // top up the borrower's balance, keeping the collateral they have reclaimed so far
+    let uluna = app.wrap().query_balance(addr_borrower, "uluna").unwrap();
+    let _ = app.init_bank_balance(
+        &Addr::unchecked(addr_borrower),
+        vec![uluna, coin(amount.u128(), "uusd")],
+    );
+
+    app.execute(
+        Addr::unchecked(addr_borrower),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::RepayLoan {
+                    loan_id,
+                    repayment: create_coin_asset(amount.u128(), "uusd"),
+                },
+                Some(vec![coin(amount.u128(), "uusd")]),
+            )
+            .unwrap(),
+    )
+}
+
+fn query_loan(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    loan_id: u64,
+) -> Loan {
+    let LoanResponse { loan } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLoan { loan_id },
+        )
+        .unwrap();
+
+    loan
+}
+
+fn query_locked_assets(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> [cw_zll_std_liquidity_pool::ap::Asset; 2] {
+    let LockedAssetsResponse { locked_assets } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLockedAssets {},
+        )
+        .unwrap();
+
+    locked_assets
+}
+
+fn query_option_token(
+    app: &App<Empty, TerraQueryWrapper>,
+    option_token: &Addr,
+    token_id: &str,
+) -> cosmwasm_std::StdResult<TokenResponse> {
+    app.wrap().query_wasm_smart(
+        option_token,
+        &cw_zll_option_token::msg::QueryMsg::Token {
+            token_id: token_id.to_string(),
+        },
+    )
+}
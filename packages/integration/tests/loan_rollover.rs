@@ -0,0 +1,438 @@
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_AMM_ENDS_AT,
+        BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::MarketContract,
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::{Asset, AssetInfo},
+    asset::create_coin_asset,
+};
+use cw_zll_std_market::{
+    borrow::BorrowingTermsResponse,
+    response::{LoanResponse, LockedAssetsResponse},
+    state::{Loan, LoanStatus, MarketPhasesInfo},
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn borrower_rolls_a_loan_over_into_the_successor_market() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app, "uusd", None, [20_000_000, 5_000_000_000]);
+    let MarketSetup {
+        market_contract: successor_market_contract,
+        ..
+    } = setup_market(
+        &mut app,
+        "uusd",
+        Some(&market_contract),
+        [40_000_000, 20_000_000_000],
+    );
+
+    let notional = borrow_one_luna(&mut app, &market_contract);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let BorrowingTermsResponse {
+        borrow, interest, ..
+    } = test_env::query_borrowing_terms(&app, &successor_market_contract, 1_000_000, None, None);
+
+    let uusd_balance = query_balance(&app, ADDR_REGULAR_USER, "uusd");
+    let pool_uusd_balance = query_balance(&app, liquidity_pool_contract.addr().as_str(), "uusd");
+
+    let response = try_to_rollover_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        &successor_market_contract,
+        borrow.clone(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to roll a loan over during the settlement phase"
+    );
+
+    assert_eq!(
+        query_loan(&app, &market_contract, 1).status,
+        LoanStatus::RolledOver
+    );
+
+    assert_eq!(
+        query_loan(&app, &successor_market_contract, 1),
+        Loan {
+            id: 1,
+            owner: Addr::unchecked(ADDR_REGULAR_USER),
+            strike: Decimal::from_ratio(borrow.amount, 1_000_000u128),
+            strike_id: None,
+            notional: borrow.amount,
+            collateral_amount: Uint128::new(1_000_000),
+            interest: interest.amount,
+            status: LoanStatus::Open,
+            parent_id: None,
+            created_at: BLOCK_HEIGHT_AMM_ENDS_AT + 1,
+        }
+    );
+
+    // the collateral never goes back to the borrower, who receives what is left of the proceeds
+    assert_eq!(
+        query_balance(&app, ADDR_REGULAR_USER, "uluna"),
+        Uint128::zero()
+    );
+    assert_eq!(
+        query_balance(&app, ADDR_REGULAR_USER, "uusd"),
+        uusd_balance + borrow.amount - interest.amount - notional
+    );
+
+    assert_eq!(
+        query_balance(&app, liquidity_pool_contract.addr().as_str(), "uusd"),
+        pool_uusd_balance + notional
+    );
+    assert_eq!(
+        query_balance(&app, market_contract.addr().as_str(), "uusd"),
+        Uint128::zero()
+    );
+
+    assert_eq!(
+        query_locked_assets(&app, &market_contract)[0],
+        create_coin_asset(0, "uluna")
+    );
+    assert_eq!(
+        query_locked_assets(&app, &successor_market_contract)[0],
+        create_coin_asset(1_000_000, "uluna")
+    );
+
+    let response = try_to_rollover_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        &successor_market_contract,
+        borrow,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::LoanAlreadyRepaid { loan_id: 1 }.to_string()
+    );
+}
+
+#[test]
+fn rollover_proceeds_must_repay_the_loan() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app, "uusd", None, [20_000_000, 5_000_000_000]);
+    let MarketSetup {
+        market_contract: successor_market_contract,
+        ..
+    } = setup_market(
+        &mut app,
+        "uusd",
+        Some(&market_contract),
+        [20_000_000, 4_000_000_000],
+    );
+
+    let notional = borrow_one_luna(&mut app, &market_contract);
+
+    let response = try_to_rollover_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        &successor_market_contract,
+        create_coin_asset(0, "uusd"),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string(),
+        "Loans cannot be rolled over during the AMM phase"
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response = try_to_rollover_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        &successor_market_contract,
+        create_coin_asset(0, "uusd"),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    // the successor pool is shallower than the pool the loan was taken out from
+    let BorrowingTermsResponse {
+        borrow, interest, ..
+    } = test_env::query_borrowing_terms(&app, &successor_market_contract, 1_000_000, None, None);
+
+    let response = try_to_rollover_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        &successor_market_contract,
+        borrow.clone(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::InsufficientRolloverProceeds {
+            proceeds: borrow.amount - interest.amount,
+            repayment: notional,
+        }
+        .to_string()
+    );
+
+    assert_eq!(
+        query_loan(&app, &market_contract, 1).status,
+        LoanStatus::Open
+    );
+}
+
+#[test]
+fn successor_market_must_lend_the_same_asset() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app, "uusd", None, [20_000_000, 5_000_000_000]);
+    let MarketSetup {
+        market_contract: successor_market_contract,
+        ..
+    } = setup_market(
+        &mut app,
+        "ukrw",
+        Some(&market_contract),
+        [40_000_000, 20_000_000_000],
+    );
+
+    borrow_one_luna(&mut app, &market_contract);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response = try_to_rollover_loan(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        &successor_market_contract,
+        create_coin_asset(0, "ukrw"),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::AssetMismatch {}.to_string()
+    );
+}
+
+#[test]
+fn only_the_predecessor_can_borrow_for_a_rollover() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app, "uusd", None, [20_000_000, 5_000_000_000]);
+    let MarketSetup {
+        market_contract: other_market_contract,
+        ..
+    } = setup_market(&mut app, "uusd", None, [20_000_000, 5_000_000_000]);
+    let MarketSetup {
+        market_contract: successor_market_contract,
+        ..
+    } = setup_market(
+        &mut app,
+        "uusd",
+        Some(&market_contract),
+        [40_000_000, 20_000_000_000],
+    );
+
+    borrow_one_luna(&mut app, &other_market_contract);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let BorrowingTermsResponse { borrow, .. } =
+        test_env::query_borrowing_terms(&app, &successor_market_contract, 1_000_000, None, None);
+
+    let response = try_to_rollover_loan(
+        &mut app,
+        &other_market_contract,
+        ADDR_REGULAR_USER,
+        &successor_market_contract,
+        borrow.clone(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string(),
+        "Markets other than the predecessor cannot roll loans over into the market"
+    );
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        successor_market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::BorrowForRollover {
+                    borrower: ADDR_REGULAR_USER.to_string(),
+                    expected_borrow: borrow,
+                    pledged_collateral: create_coin_asset(1_000_000, "uluna"),
+                    strike_id: None,
+                    repayment: create_coin_asset(0, "uusd"),
+                },
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string(),
+        "Accounts cannot borrow for a rollover"
+    );
+}
+
+/// Creates a LUNA market lending `lending_denom` with a deposit of the given amounts. The
+/// successor of the `predecessor` market is in the AMM phase while its predecessor settles.
+fn setup_market(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    lending_denom: &str,
+    predecessor: Option<&MarketContract>,
+    amounts: [u128; 2],
+) -> MarketSetup {
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: lending_denom.into(),
+        },
+    ];
+
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos, |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(amounts[1], amounts[0]));
+
+        if let Some(predecessor) = predecessor {
+            msg.predecessor = Some(predecessor.addr().to_string());
+            msg.market_phases_info = MarketPhasesInfo {
+                lp_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT,
+                amm_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+                settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1000,
+                ..msg.market_phases_info
+            };
+        }
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(amounts[0], "uluna"),
+            create_coin_asset(amounts[1], lending_denom),
+        ],
+    )
+    .unwrap();
+
+    market_setup
+}
+
+/// Borrows against 1 LUNA during the AMM phase and returns the notional of the loan.
+fn borrow_one_luna(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> Uint128 {
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    let BorrowingTermsResponse { borrow, .. } =
+        test_env::query_borrowing_terms(app, market_contract, 1_000_000, None, None);
+
+    test_env::try_to_borrow(
+        app,
+        market_contract,
+        ADDR_REGULAR_USER,
+        borrow.clone(),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+    .unwrap();
+
+    borrow.amount
+}
+
+fn try_to_rollover_loan(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_borrower: &str,
+    successor_market_contract: &MarketContract,
+    expected_borrow: Asset,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(addr_borrower),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::RolloverLoan {
+                    loan_id: 1,
+                    market: successor_market_contract.addr().to_string(),
+                    expected_borrow,
+                    strike_id: None,
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_balance(app: &App<Empty, TerraQueryWrapper>, address: &str, denom: &str) -> Uint128 {
+    app.wrap().query_balance(address, denom).unwrap().amount
+}
+
+fn query_loan(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    loan_id: u64,
+) -> Loan {
+    let LoanResponse { loan } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLoan { loan_id },
+        )
+        .unwrap();
+
+    loan
+}
+
+fn query_locked_assets(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> [Asset; 2] {
+    let LockedAssetsResponse { locked_assets } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLockedAssets {},
+        )
+        .unwrap();
+
+    locked_assets
+}
@@ -0,0 +1,279 @@
+use cosmwasm_std::{Decimal, Empty, Uint128};
+use cw_multi_test::App;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_REGULAR_USER, BLOCK_HEIGHT_AMM_ENDS_AT,
+        BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    asset::create_coin_asset,
+    response::{HolderShareResponse, NavPerShareResponse, PremiumEarnedResponse},
+};
+use cw_zll_std_market::{response::LoanResponse, state::Loan};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn anyone_can_check_nav_per_share_valued_in_the_lending_asset() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_with_deposit(&mut app, Some(Decimal::from_ratio(250u128, 1u128)));
+
+    let response: NavPerShareResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::NavPerShare {},
+        )
+        .unwrap();
+
+    // 2 LUNA * 250 UST + 500 UST = 1000 UST
+    assert_eq!(
+        response,
+        NavPerShareResponse {
+            nav: create_coin_asset(1_000_000_000, "uusd"),
+            total_share: Uint128::new(31_622_776),
+            nav_per_share: Decimal::from_ratio(1_000_000_000u128, 31_622_776u128),
+            collateral_price: Decimal::from_ratio(250u128, 1u128),
+        }
+    );
+}
+
+#[test]
+fn liquidity_provider_can_check_their_share_of_each_reserve() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_with_deposit(&mut app, Some(Decimal::from_ratio(250u128, 1u128)));
+
+    let response: HolderShareResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::HolderShare {
+                address: ADDR_LIQUIDITY_PROVIDER.into(),
+            },
+        )
+        .unwrap();
+
+    // the minimum liquidity stays locked in the pool
+    let share_ratio = Decimal::from_ratio(31_621_776u128, 31_622_776u128);
+    let uluna = Uint128::new(2_000_000) * share_ratio;
+    let uusd = Uint128::new(500_000_000) * share_ratio;
+
+    assert_eq!(
+        response,
+        HolderShareResponse {
+            share: Uint128::new(31_621_776),
+            assets: vec![
+                create_coin_asset(uluna.u128(), "uluna"),
+                create_coin_asset(uusd.u128(), "uusd"),
+            ],
+            loans: vec![create_coin_asset(0, "uluna"), create_coin_asset(0, "uusd"),],
+            nav: create_coin_asset((uluna * Uint128::new(250) + uusd).u128(), "uusd"),
+            premium_earned: create_coin_asset(0, "uusd"),
+        }
+    );
+}
+
+#[test]
+fn anyone_can_check_premium_earned_since_the_market_started() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_with_deposit(&mut app, None);
+
+    let response: PremiumEarnedResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::PremiumEarned {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        response,
+        PremiumEarnedResponse {
+            premium: create_coin_asset(0, "uusd"),
+            premium_per_share: Decimal::zero(),
+        }
+    );
+}
+
+#[test]
+fn nav_per_share_can_be_checked_in_every_phase() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_with_deposit(&mut app, Some(Decimal::from_ratio(250u128, 1u128)));
+
+    for height in [
+        BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_AMM_ENDS_AT,
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1,
+    ] {
+        app.update_block(|block| {
+            block.height = height;
+        });
+
+        let response: NavPerShareResponse = app
+            .wrap()
+            .query_wasm_smart(
+                liquidity_pool_contract.addr(),
+                &cw_zll_std_liquidity_pool::msg::QueryMsg::NavPerShare {},
+            )
+            .unwrap();
+
+        assert_eq!(response.nav, create_coin_asset(1_000_000_000, "uusd"));
+    }
+}
+
+#[test]
+fn nav_per_share_is_valued_with_the_reserve_price_without_collateral_price() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_with_deposit(&mut app, None);
+
+    let response: NavPerShareResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::NavPerShare {},
+        )
+        .unwrap();
+
+    // the reserves of 2 LUNA & 500 UST imply a price of 250 UST
+    assert_eq!(
+        response.collateral_price,
+        Decimal::from_ratio(250u128, 1u128)
+    );
+    assert_eq!(response.nav, create_coin_asset(1_000_000_000, "uusd"));
+
+    let response: Result<HolderShareResponse, _> = app.wrap().query_wasm_smart(
+        liquidity_pool_contract.addr(),
+        &cw_zll_std_liquidity_pool::msg::QueryMsg::HolderShare {
+            address: ADDR_LIQUIDITY_PROVIDER.into(),
+        },
+    );
+
+    assert!(
+        response.is_ok(),
+        "Holder share can be checked without the collateral price"
+    );
+}
+
+#[test]
+fn nav_per_share_includes_the_outstanding_loans() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market_with_deposit(&mut app, Some(Decimal::from_ratio(250u128, 1u128)));
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(100_000, "uluna"),
+    )
+    .unwrap();
+
+    let LoanResponse {
+        loan: Loan {
+            notional, interest, ..
+        },
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLoan { loan_id: 1 },
+        )
+        .unwrap();
+
+    let response: NavPerShareResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::NavPerShare {},
+        )
+        .unwrap();
+
+    // the loan is worth its notional, which is less than 0.1 LUNA * 250 UST, so the pool gains
+    // the interest kept from the borrowed amount
+    assert!(notional < Uint128::new(25_000_000));
+    assert_eq!(
+        response.nav,
+        create_coin_asset((Uint128::new(1_000_000_000) + interest).u128(), "uusd")
+    );
+
+    // the collateral of the loans left unrepaid becomes part of the reserves
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    let response: NavPerShareResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::NavPerShare {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        response.nav,
+        create_coin_asset(
+            (Uint128::new(1_000_000_000) + interest - notional + Uint128::new(25_000_000)).u128(),
+            "uusd"
+        )
+    );
+}
+
+fn setup_market_with_deposit(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    collateral_price: Option<Decimal>,
+) -> MarketSetup {
+    //provide 500 UST & 2 LUNA
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos, |msg| {
+        msg.collateral_price = collateral_price;
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    market_setup
+}
@@ -0,0 +1,345 @@
+use cosmwasm_std::{Addr, Decimal, Empty};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_factory::ContractError;
+use cw_zll_std_factory::{
+    response::{MarketResponse, MarketsResponse, TemplateResponse, TemplatesResponse},
+    state::{MarketRecord, MarketTemplate},
+};
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_ADMIN, ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER, BLOCK_HEIGHT_AMM_ENDS_AT,
+        BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_MARKET_STARTS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::FactoryContract,
+    test_env,
+};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use cw_zll_std_market::{
+    response::{LiquidityPoolResponse, MarketOperatorResponse, MarketPhase, OptionTokenResponse},
+    state::MarketPhasesInfo,
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+const TEMPLATE: &str = "weekly";
+
+#[test]
+fn factory_owner_can_set_templates() {
+    let mut app = test_env::mock_app();
+
+    let factory_contract = test_env::create_factory(&mut app);
+
+    let response = try_to_set_template(&mut app, &factory_contract, ADDR_REGULAR_USER);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_set_template(&mut app, &factory_contract, ADDR_ADMIN);
+
+    assert!(response.is_ok(), "Factory owner is able to set templates");
+
+    let TemplateResponse { template, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            factory_contract.addr(),
+            &cw_zll_std_factory::msg::QueryMsg::GetTemplate {
+                name: TEMPLATE.to_string(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(template, market_template());
+
+    let TemplatesResponse { names } = app
+        .wrap()
+        .query_wasm_smart(
+            factory_contract.addr(),
+            &cw_zll_std_factory::msg::QueryMsg::GetTemplates {},
+        )
+        .unwrap();
+
+    assert_eq!(names, vec![TEMPLATE.to_string()]);
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_ADMIN),
+        factory_contract
+            .call(
+                &cw_zll_std_factory::msg::ExecuteMsg::RemoveTemplate {
+                    name: "monthly".to_string(),
+                },
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::TemplateNotFound {
+            name: "monthly".to_string()
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn factory_creates_market_stacks_from_templates() {
+    let mut app = test_env::mock_app();
+
+    let factory_contract = setup_factory(&mut app);
+
+    let response = try_to_create_market(&mut app, &factory_contract, "monthly", "uusd");
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::TemplateNotFound {
+            name: "monthly".to_string()
+        }
+        .to_string()
+    );
+
+    let response = try_to_create_market(&mut app, &factory_contract, TEMPLATE, "uusd");
+
+    assert!(
+        response.is_ok(),
+        "Factory owner is able to create a market from a template"
+    );
+
+    let MarketResponse { market } = app
+        .wrap()
+        .query_wasm_smart(
+            factory_contract.addr(),
+            &cw_zll_std_factory::msg::QueryMsg::GetMarket { market_id: 1 },
+        )
+        .unwrap();
+
+    assert_eq!(market.template, TEMPLATE);
+    assert_eq!(market.asset_infos, asset_infos("uusd"));
+    assert_eq!(market.created_at, BLOCK_HEIGHT_MARKET_STARTS_AT);
+
+    let LiquidityPoolResponse { liquidity_pool } = app
+        .wrap()
+        .query_wasm_smart(
+            market.market.clone(),
+            &cw_zll_std_market::msg::QueryMsg::GetLiquidityPool {},
+        )
+        .unwrap();
+
+    assert_eq!(market.liquidity_pool, liquidity_pool);
+
+    let MarketOperatorResponse { market_operator } = app
+        .wrap()
+        .query_wasm_smart(
+            market.market.clone(),
+            &cw_zll_std_market::msg::QueryMsg::GetMarketOperator {},
+        )
+        .unwrap();
+
+    assert_eq!(market_operator, Addr::unchecked(ADDR_MARKET_OPERATOR));
+
+    let OptionTokenResponse { option_token } = app
+        .wrap()
+        .query_wasm_smart(
+            market.market.clone(),
+            &cw_zll_std_market::msg::QueryMsg::GetOptionToken {},
+        )
+        .unwrap();
+
+    assert!(
+        option_token.is_some(),
+        "Markets created by the factory issue option tokens"
+    );
+
+    assert_eq!(
+        app.contract_data(&market.market).unwrap().admin,
+        Some(Addr::unchecked(ADDR_ADMIN))
+    );
+    assert_eq!(
+        app.contract_data(&market.liquidity_pool).unwrap().admin,
+        Some(market.market)
+    );
+}
+
+#[test]
+fn market_registry_can_be_filtered() {
+    let mut app = test_env::mock_app();
+
+    let factory_contract = setup_factory(&mut app);
+
+    try_to_create_market(&mut app, &factory_contract, TEMPLATE, "uusd").unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_MARKET_STARTS_AT + 100;
+    });
+
+    try_to_create_market(&mut app, &factory_contract, TEMPLATE, "ukrw").unwrap();
+
+    assert_eq!(
+        query_market_ids(
+            &app,
+            &factory_contract,
+            Some(asset_infos("ukrw")),
+            None,
+            None
+        ),
+        vec![2]
+    );
+    assert_eq!(
+        query_market_ids(
+            &app,
+            &factory_contract,
+            None,
+            None,
+            Some(BLOCK_HEIGHT_MARKET_STARTS_AT + 1)
+        ),
+        vec![2]
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    assert_eq!(
+        query_market_ids(
+            &app,
+            &factory_contract,
+            None,
+            Some(MarketPhase::AutomatedMarketMaker),
+            None
+        ),
+        vec![1, 2]
+    );
+    assert_eq!(
+        query_market_ids(
+            &app,
+            &factory_contract,
+            Some(asset_infos("uusd")),
+            Some(MarketPhase::Settlement),
+            None
+        ),
+        Vec::<u64>::new()
+    );
+}
+
+/// Creates a factory with a template at the block the markets start at.
+fn setup_factory(app: &mut App<Empty, TerraQueryWrapper>) -> FactoryContract {
+    let factory_contract = test_env::create_factory(app);
+
+    try_to_set_template(app, &factory_contract, ADDR_ADMIN).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_MARKET_STARTS_AT;
+    });
+
+    factory_contract
+}
+
+fn market_template() -> MarketTemplate {
+    MarketTemplate {
+        blocks_per_year: 4_204_800, // assuming one block per 7.5 seconds
+        alpha: 200_000_000_000,
+        twap_window: None,
+        max_price_age: None,
+        max_price_deviation: None,
+        buyback_spread: Some(Decimal::percent(2)),
+        max_buyback_volume: None,
+        protocol_fee_rate: None,
+        treasury: None,
+        guardian: None,
+        timelock_delay: None,
+    }
+}
+
+fn asset_infos(lending_denom: &str) -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: lending_denom.into(),
+        },
+    ]
+}
+
+fn try_to_set_template(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    factory_contract: &FactoryContract,
+    sender: &str,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        factory_contract
+            .call(
+                &cw_zll_std_factory::msg::ExecuteMsg::SetTemplate {
+                    name: TEMPLATE.to_string(),
+                    template: market_template(),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+/// Creates a LUNA market with the given lending asset, starting at the current block.
+fn try_to_create_market(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    factory_contract: &FactoryContract,
+    template: &str,
+    lending_denom: &str,
+) -> anyhow::Result<AppResponse> {
+    let market_started_at = app.block_info().height;
+
+    app.execute(
+        Addr::unchecked(ADDR_ADMIN),
+        factory_contract
+            .call(
+                &cw_zll_std_factory::msg::ExecuteMsg::CreateMarket {
+                    template: template.to_string(),
+                    market_operator: ADDR_MARKET_OPERATOR.to_string(),
+                    asset_infos: asset_infos(lending_denom),
+                    market_phases_info: MarketPhasesInfo {
+                        market_started_at,
+                        lp_phase_ends_at: BLOCK_HEIGHT_LP_ENDS_AT,
+                        amm_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT,
+                        settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+                    },
+                    target_liquidity: None,
+                    minimum_liquidity: None,
+                    collateral_price: Some(Decimal::from_ratio(250u128, 1u128)),
+                    oracle: None,
+                    recurring: None,
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_market_ids(
+    app: &App<Empty, TerraQueryWrapper>,
+    factory_contract: &FactoryContract,
+    asset_infos: Option<[AssetInfo; 2]>,
+    phase: Option<MarketPhase>,
+    created_from: Option<u64>,
+) -> Vec<u64> {
+    let MarketsResponse { markets } = app
+        .wrap()
+        .query_wasm_smart(
+            factory_contract.addr(),
+            &cw_zll_std_factory::msg::QueryMsg::GetMarkets {
+                asset_infos,
+                phase,
+                created_from,
+                created_until: None,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    markets
+        .into_iter()
+        .map(|MarketRecord { id, .. }| id)
+        .collect()
+}
@@ -0,0 +1,645 @@
+use cosmwasm_std::{to_binary, Addr, Decimal, Empty, Uint128};
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_factory::ContractError;
+use cw_zll_liquidity_pool::ContractError as LiquidityPoolContractError;
+use cw_zll_market::ContractError as MarketContractError;
+use cw_zll_std_factory::{
+    response::{MarketResponse, SeriesResponse},
+    state::{MarketRecord, MarketTemplate},
+};
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_ADMIN, ADDR_LIQUIDITY_PROVIDER, ADDR_LIQUIDITY_PROVIDER_2, ADDR_MARKET_OPERATOR,
+        ADDR_REGULAR_USER, BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_MARKET_STARTS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{FactoryContract, LiquidityPoolContract, MarketContract},
+    test_env,
+};
+use cw_zll_std_liquidity_pool::{
+    ap::{AssetInfo, PairInfo, PoolResponse},
+    asset::create_coin_asset,
+    response::{HolderShareResponse, RolloverSharesResponse},
+};
+use cw_zll_std_market::{response::MarketPhase, state::MarketPhasesInfo};
+use terra_cosmwasm::TerraQueryWrapper;
+
+const TEMPLATE: &str = "weekly";
+
+/// The number of blocks the series is rolled after its first market is settled.
+const BLOCKS_AFTER_SETTLEMENT: u64 = 100;
+
+#[test]
+fn lp_can_opt_in_and_out_of_the_rollover() {
+    let mut app = test_env::mock_app();
+
+    let (_, market) = setup_series(&mut app);
+    let liquidity_pool_contract = query_liquidity_pool_contract(&app, &market);
+
+    let balance =
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    let response = try_to_opt_in_rollover(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        balance,
+    );
+
+    assert!(response.is_ok(), "LP is able to opt in to the rollover");
+
+    assert_eq!(
+        query_rollover_shares(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER),
+        RolloverSharesResponse {
+            shares: balance,
+            liquidity_pool: None,
+            claimable: Uint128::zero(),
+        }
+    );
+    assert_eq!(
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER),
+        Uint128::zero()
+    );
+
+    let response =
+        try_to_opt_out_rollover(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert!(response.is_ok(), "LP is able to opt out of the rollover");
+
+    assert_eq!(
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER),
+        balance
+    );
+
+    let response =
+        try_to_opt_out_rollover(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::NoRolloverShares {}.to_string()
+    );
+}
+
+#[test]
+fn series_is_rolled_into_a_market_with_shifted_phases() {
+    let mut app = test_env::mock_app();
+
+    let (factory_contract, market) = setup_series(&mut app);
+    let series_id = market.series_id.unwrap();
+
+    let response = try_to_roll_series(&mut app, &factory_contract, ADDR_MARKET_OPERATOR, series_id);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::SeriesNotReady { series_id }.to_string()
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + BLOCKS_AFTER_SETTLEMENT;
+    });
+
+    let response = try_to_roll_series(&mut app, &factory_contract, ADDR_REGULAR_USER, series_id);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_roll_series(&mut app, &factory_contract, ADDR_MARKET_OPERATOR, series_id);
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to roll the series once its latest market is settled"
+    );
+
+    let SeriesResponse { series } = app
+        .wrap()
+        .query_wasm_smart(
+            factory_contract.addr(),
+            &cw_zll_std_factory::msg::QueryMsg::GetSeries { series_id },
+        )
+        .unwrap();
+
+    assert_eq!(series.market_ids, vec![1, 2]);
+
+    let successor = query_market(&app, &factory_contract, 2);
+    let shift =
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + BLOCKS_AFTER_SETTLEMENT - BLOCK_HEIGHT_MARKET_STARTS_AT;
+
+    assert_eq!(successor.series_id, Some(series_id));
+    assert_eq!(
+        successor.market_phases_info,
+        MarketPhasesInfo {
+            market_started_at: BLOCK_HEIGHT_MARKET_STARTS_AT + shift,
+            lp_phase_ends_at: BLOCK_HEIGHT_LP_ENDS_AT + shift,
+            amm_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT + shift,
+            settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + shift,
+        }
+    );
+    assert_eq!(
+        MarketContract(successor.market)
+            .get_market_phase(&app)
+            .unwrap(),
+        MarketPhase::ProvidingLiquidity
+    );
+
+    let response = try_to_roll_series(&mut app, &factory_contract, ADDR_ADMIN, series_id);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::SeriesNotReady { series_id }.to_string()
+    );
+}
+
+#[test]
+fn series_is_rolled_once_its_latest_market_is_cancelled() {
+    let mut app = test_env::mock_app();
+
+    let (factory_contract, market) = setup_series(&mut app);
+    let liquidity_pool_contract = query_liquidity_pool_contract(&app, &market);
+
+    let balance =
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    try_to_opt_in_rollover(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        balance,
+    )
+    .unwrap();
+
+    app.execute(
+        Addr::unchecked(ADDR_MARKET_OPERATOR),
+        MarketContract(market.market.clone())
+            .call(&cw_zll_std_market::msg::ExecuteMsg::CancelMarket {}, None)
+            .unwrap(),
+    )
+    .unwrap();
+
+    let response = try_to_opt_in_rollover(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        Uint128::new(1_000),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_roll_series(
+        &mut app,
+        &factory_contract,
+        ADDR_ADMIN,
+        market.series_id.unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Owner is able to roll the series once its latest market is cancelled"
+    );
+
+    let RolloverSharesResponse { liquidity_pool, .. } =
+        query_rollover_shares(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert_eq!(
+        liquidity_pool,
+        Some(query_market(&app, &factory_contract, 2).liquidity_pool)
+    );
+}
+
+#[test]
+fn lp_cannot_opt_in_to_the_rollover_once_the_market_is_settled() {
+    let mut app = test_env::mock_app();
+
+    let (_, market) = setup_series(&mut app);
+    let liquidity_pool_contract = query_liquidity_pool_contract(&app, &market);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + BLOCKS_AFTER_SETTLEMENT;
+    });
+
+    let response = try_to_opt_in_rollover(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        Uint128::new(1_000),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::Unauthorized {}.to_string()
+    );
+}
+
+#[test]
+fn escrowed_share_without_one_of_the_assets_stays_escrowed() {
+    let mut app = test_env::mock_app();
+
+    let (factory_contract, market) = setup_series(&mut app);
+    let liquidity_pool_contract = query_liquidity_pool_contract(&app, &market);
+
+    let balance =
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    // a single LP token is worth less than one uluna
+    try_to_opt_in_rollover(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        Uint128::new(1),
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + BLOCKS_AFTER_SETTLEMENT;
+    });
+
+    let response = try_to_roll_series(
+        &mut app,
+        &factory_contract,
+        ADDR_MARKET_OPERATOR,
+        market.series_id.unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Series is rolled when the escrowed share lacks one of the assets"
+    );
+
+    assert_eq!(
+        query_rollover_shares(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER),
+        RolloverSharesResponse {
+            shares: Uint128::new(1),
+            liquidity_pool: None,
+            claimable: Uint128::zero(),
+        }
+    );
+
+    let response =
+        try_to_opt_out_rollover(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert!(
+        response.is_ok(),
+        "LP is able to opt out of the rollover which was left out"
+    );
+
+    assert_eq!(
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER),
+        balance
+    );
+}
+
+#[test]
+fn liquidity_is_only_rolled_over_into_a_successor_of_the_market() {
+    let mut app = test_env::mock_app();
+
+    let (factory_contract, market) = setup_series(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + BLOCKS_AFTER_SETTLEMENT;
+    });
+
+    // the market does not succeed itself
+    let response = app.execute(
+        factory_contract.addr(),
+        MarketContract(market.market.clone())
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::RolloverLiquidity {
+                    market: market.market.to_string(),
+                },
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        MarketContractError::InvalidSuccessor {
+            market: market.market.to_string(),
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn escrowed_liquidity_is_rolled_over_into_the_successor_pool() {
+    let mut app = test_env::mock_app();
+
+    let (factory_contract, market) = setup_series(&mut app);
+    let liquidity_pool_contract = query_liquidity_pool_contract(&app, &market);
+
+    let balance =
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+    let HolderShareResponse { assets, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::HolderShare {
+                address: ADDR_LIQUIDITY_PROVIDER.to_string(),
+            },
+        )
+        .unwrap();
+
+    try_to_opt_in_rollover(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        balance,
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + BLOCKS_AFTER_SETTLEMENT;
+    });
+
+    let response =
+        try_to_claim_rollover_shares(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::RolloverPending {}.to_string()
+    );
+
+    try_to_roll_series(
+        &mut app,
+        &factory_contract,
+        ADDR_MARKET_OPERATOR,
+        market.series_id.unwrap(),
+    )
+    .unwrap();
+
+    let successor = query_market(&app, &factory_contract, 2);
+    let successor_pool_contract = query_liquidity_pool_contract(&app, &successor);
+
+    let PoolResponse {
+        assets: reserves, ..
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            successor_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pool {},
+        )
+        .unwrap();
+
+    assert_eq!(reserves.to_vec(), assets);
+
+    let RolloverSharesResponse {
+        liquidity_pool,
+        claimable,
+        ..
+    } = query_rollover_shares(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert_eq!(liquidity_pool, Some(successor.liquidity_pool));
+
+    let response =
+        try_to_claim_rollover_shares(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert!(
+        response.is_ok(),
+        "LP is able to claim the LP tokens of the successor pool"
+    );
+
+    assert_eq!(
+        test_env::query_lp_token_balance(&app, &successor_pool_contract, ADDR_LIQUIDITY_PROVIDER),
+        claimable
+    );
+
+    let response = try_to_opt_in_rollover(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        Uint128::new(1_000),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::RolloverCompleted {}.to_string()
+    );
+
+    let response = test_env::try_to_withdraw_liquidity_with(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        cw_zll_std_liquidity_pool::msg::Cw20HookMsg::WithdrawLiquidity {
+            recipient: None,
+            min_assets: None,
+            output_asset: None,
+        },
+    );
+
+    assert!(
+        response.is_ok(),
+        "LPs who did not opt in are able to withdraw from the settled market"
+    );
+}
+
+/// Creates a recurring market with deposits of 20 LUNA & 5000 UST from two liquidity providers.
+fn setup_series(app: &mut App<Empty, TerraQueryWrapper>) -> (FactoryContract, MarketRecord) {
+    let factory_contract = test_env::create_factory(app);
+
+    app.execute(
+        Addr::unchecked(ADDR_ADMIN),
+        factory_contract
+            .call(
+                &cw_zll_std_factory::msg::ExecuteMsg::SetTemplate {
+                    name: TEMPLATE.to_string(),
+                    template: market_template(),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_MARKET_STARTS_AT;
+    });
+
+    app.execute(
+        Addr::unchecked(ADDR_ADMIN),
+        factory_contract
+            .call(
+                &cw_zll_std_factory::msg::ExecuteMsg::CreateMarket {
+                    template: TEMPLATE.to_string(),
+                    market_operator: ADDR_MARKET_OPERATOR.to_string(),
+                    asset_infos: [
+                        AssetInfo::NativeToken {
+                            denom: "uluna".into(),
+                        },
+                        AssetInfo::NativeToken {
+                            denom: "uusd".into(),
+                        },
+                    ],
+                    market_phases_info: MarketPhasesInfo {
+                        market_started_at: BLOCK_HEIGHT_MARKET_STARTS_AT,
+                        lp_phase_ends_at: BLOCK_HEIGHT_LP_ENDS_AT,
+                        amm_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT,
+                        settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+                    },
+                    target_liquidity: None,
+                    minimum_liquidity: None,
+                    collateral_price: Some(Decimal::from_ratio(250u128, 1u128)),
+                    oracle: None,
+                    recurring: Some(true),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+    .unwrap();
+
+    let market = query_market(app, &factory_contract, 1);
+    let liquidity_pool_contract = query_liquidity_pool_contract(app, &market);
+
+    for addr_liquidity_provider in [ADDR_LIQUIDITY_PROVIDER, ADDR_LIQUIDITY_PROVIDER_2] {
+        test_env::try_to_deposit_liquidity(
+            app,
+            &liquidity_pool_contract,
+            addr_liquidity_provider,
+            [
+                create_coin_asset(20_000_000, "uluna"),
+                create_coin_asset(5_000_000_000, "uusd"),
+            ],
+        )
+        .unwrap();
+    }
+
+    (factory_contract, market)
+}
+
+fn market_template() -> MarketTemplate {
+    MarketTemplate {
+        blocks_per_year: 4_204_800, // assuming one block per 7.5 seconds
+        alpha: 200_000_000_000,
+        twap_window: None,
+        max_price_age: None,
+        max_price_deviation: None,
+        buyback_spread: None,
+        max_buyback_volume: None,
+        protocol_fee_rate: None,
+        treasury: None,
+        guardian: None,
+        timelock_delay: None,
+    }
+}
+
+fn query_market(
+    app: &App<Empty, TerraQueryWrapper>,
+    factory_contract: &FactoryContract,
+    market_id: u64,
+) -> MarketRecord {
+    let MarketResponse { market } = app
+        .wrap()
+        .query_wasm_smart(
+            factory_contract.addr(),
+            &cw_zll_std_factory::msg::QueryMsg::GetMarket { market_id },
+        )
+        .unwrap();
+
+    market
+}
+
+fn query_liquidity_pool_contract(
+    app: &App<Empty, TerraQueryWrapper>,
+    market: &MarketRecord,
+) -> LiquidityPoolContract {
+    let PairInfo {
+        liquidity_token, ..
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            market.liquidity_pool.clone(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pair {},
+        )
+        .unwrap();
+
+    LiquidityPoolContract(market.liquidity_pool.clone(), liquidity_token)
+}
+
+fn try_to_roll_series(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    factory_contract: &FactoryContract,
+    sender: &str,
+    series_id: u64,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        factory_contract
+            .call(
+                &cw_zll_std_factory::msg::ExecuteMsg::RollSeries { series_id },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_opt_in_rollover(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+    amount: Uint128,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(addr_liquidity_provider),
+        cw20::Cw20Contract(liquidity_pool_contract.1.clone())
+            .call(Cw20ExecuteMsg::Send {
+                contract: liquidity_pool_contract.addr().to_string(),
+                amount,
+                msg: to_binary(&cw_zll_std_liquidity_pool::msg::Cw20HookMsg::OptInRollover {})
+                    .unwrap(),
+            })
+            .unwrap(),
+    )
+}
+
+fn try_to_opt_out_rollover(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(addr_liquidity_provider),
+        liquidity_pool_contract
+            .call(
+                &cw_zll_std_liquidity_pool::msg::ExecuteMsg::OptOutRollover {},
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_claim_rollover_shares(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(addr_liquidity_provider),
+        liquidity_pool_contract
+            .call(
+                &cw_zll_std_liquidity_pool::msg::ExecuteMsg::ClaimRolloverShares {},
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_rollover_shares(
+    app: &App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    address: &str,
+) -> RolloverSharesResponse {
+    app.wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::RolloverShares {
+                address: address.to_string(),
+            },
+        )
+        .unwrap()
+}
@@ -0,0 +1,641 @@
+use cosmwasm_std::{coin, to_binary, Addr, Decimal, Empty, Uint128};
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_meta_pool::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_META_POOL_MAINTAINER, ADDR_REGULAR_USER, ADDR_REGULAR_USER_2,
+        BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{LiquidityPoolContract, MetaPoolContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::{Asset, AssetInfo},
+    asset::create_coin_asset,
+    response::HolderShareResponse,
+};
+use cw_zll_std_meta_pool::response::{ConfigResponse, HoldingsResponse, NavPerShareResponse};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn deposits_mint_shares_by_value() {
+    let mut app = test_env::mock_app();
+
+    let meta_pool_contract = test_env::create_meta_pool(
+        &mut app,
+        asset_infos("uusd"),
+        Decimal::from_ratio(250u128, 1u128),
+    );
+
+    // 1 LUNA * 250 UST per LUNA + 250 UST
+    let response = try_to_deposit(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_REGULAR_USER,
+        [
+            create_coin_asset(1_000_000, "uluna"),
+            create_coin_asset(250_000_000, "uusd"),
+        ],
+    );
+
+    assert!(response.is_ok(), "Depositor is able to deposit both assets");
+
+    assert_eq!(
+        query_share_balance(&app, &meta_pool_contract, ADDR_REGULAR_USER),
+        Uint128::new(500_000_000) - cw_zll_meta_pool::contract::MINIMUM_LIQUIDITY_AMOUNT
+    );
+
+    let response = try_to_deposit(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_REGULAR_USER_2,
+        [
+            create_coin_asset(0, "uluna"),
+            create_coin_asset(500_000_000, "uusd"),
+        ],
+    );
+
+    assert!(
+        response.is_ok(),
+        "Depositor is able to deposit a single asset"
+    );
+
+    assert_eq!(
+        query_share_balance(&app, &meta_pool_contract, ADDR_REGULAR_USER_2),
+        Uint128::new(500_000_000)
+    );
+
+    let NavPerShareResponse {
+        nav,
+        total_share,
+        nav_per_share,
+        ..
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            meta_pool_contract.addr(),
+            &cw_zll_std_meta_pool::msg::QueryMsg::NavPerShare {},
+        )
+        .unwrap();
+
+    assert_eq!(nav, create_coin_asset(1_000_000_000, "uusd"));
+    assert_eq!(total_share, Uint128::new(1_000_000_000));
+    assert_eq!(nav_per_share, Decimal::one());
+    // the minimum liquidity is only locked by the first deposit
+    assert_eq!(
+        query_share_balance(
+            &app,
+            &meta_pool_contract,
+            meta_pool_contract.addr().as_str()
+        ),
+        cw_zll_meta_pool::contract::MINIMUM_LIQUIDITY_AMOUNT
+    );
+
+    let response = try_to_deposit(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_REGULAR_USER,
+        [
+            create_coin_asset(1_000_000, "uluna"),
+            create_coin_asset(250_000_000, "ukrw"),
+        ],
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::AssetMismatch {}.to_string()
+    );
+}
+
+#[test]
+fn maintainer_allocates_liquidity_across_pools() {
+    let mut app = test_env::mock_app();
+
+    let liquidity_pool_contract = setup_liquidity_pool(&mut app, "uusd");
+    let other_liquidity_pool_contract = setup_liquidity_pool(&mut app, "ukrw");
+    let meta_pool_contract = setup_meta_pool(&mut app);
+
+    let response = try_to_add_liquidity_pool(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_REGULAR_USER,
+        &liquidity_pool_contract,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_add_liquidity_pool(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_META_POOL_MAINTAINER,
+        &other_liquidity_pool_contract,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::AssetMismatch {}.to_string()
+    );
+
+    let response = try_to_add_liquidity_pool(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_META_POOL_MAINTAINER,
+        &liquidity_pool_contract,
+    );
+
+    assert!(
+        response.is_ok(),
+        "Maintainer is able to add a liquidity pool with the assets of the meta pool"
+    );
+
+    let response =
+        try_to_provide_liquidity(&mut app, &meta_pool_contract, &liquidity_pool_contract);
+
+    assert!(
+        response.is_ok(),
+        "Maintainer is able to provide liquidity to the liquidity pool"
+    );
+
+    let HoldingsResponse {
+        assets,
+        liquidity_pools,
+        nav,
+    } = query_holdings(&app, &meta_pool_contract);
+
+    let share = test_env::query_lp_token_balance(
+        &app,
+        &liquidity_pool_contract,
+        meta_pool_contract.addr().as_str(),
+    );
+
+    assert_eq!(
+        assets,
+        [
+            create_coin_asset(10_000_000, "uluna"),
+            create_coin_asset(2_500_000_000, "uusd"),
+        ]
+    );
+    assert_eq!(liquidity_pools.len(), 1);
+    assert_eq!(
+        liquidity_pools[0].liquidity_pool,
+        liquidity_pool_contract.addr()
+    );
+    assert_eq!(liquidity_pools[0].share, share);
+    // 10 LUNA * 250 UST per LUNA + 2500 UST held by the meta pool itself
+    assert_eq!(
+        nav.amount,
+        Uint128::new(5_000_000_000) + liquidity_pools[0].nav.amount
+    );
+
+    let response =
+        try_to_remove_liquidity_pool(&mut app, &meta_pool_contract, &liquidity_pool_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::LiquidityPoolNotEmpty {
+            liquidity_pool: liquidity_pool_contract.addr().to_string()
+        }
+        .to_string()
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_META_POOL_MAINTAINER),
+        meta_pool_contract
+            .call(
+                &cw_zll_std_meta_pool::msg::ExecuteMsg::WithdrawLiquidity {
+                    liquidity_pool: liquidity_pool_contract.addr().to_string(),
+                    amount: share,
+                },
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Maintainer is able to withdraw liquidity from the liquidity pool"
+    );
+
+    let HoldingsResponse {
+        liquidity_pools, ..
+    } = query_holdings(&app, &meta_pool_contract);
+
+    assert_eq!(liquidity_pools[0].share, Uint128::zero());
+
+    let response =
+        try_to_remove_liquidity_pool(&mut app, &meta_pool_contract, &liquidity_pool_contract);
+
+    assert!(
+        response.is_ok(),
+        "Maintainer is able to remove a liquidity pool once the meta pool holds no LP tokens"
+    );
+
+    let ConfigResponse {
+        liquidity_pools, ..
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            meta_pool_contract.addr(),
+            &cw_zll_std_meta_pool::msg::QueryMsg::Config {},
+        )
+        .unwrap();
+
+    assert!(liquidity_pools.is_empty());
+}
+
+#[test]
+fn liquidity_pools_are_valued_at_the_collateral_price_of_the_meta_pool() {
+    let mut app = test_env::mock_app();
+
+    // the market of the liquidity pool prices LUNA at 500 UST, the meta pool at 250 UST
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup_with(&mut app, asset_infos("uusd"), |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(500u128, 1u128));
+    });
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    let meta_pool_contract = setup_meta_pool(&mut app);
+
+    try_to_add_liquidity_pool(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_META_POOL_MAINTAINER,
+        &liquidity_pool_contract,
+    )
+    .unwrap();
+    try_to_provide_liquidity(&mut app, &meta_pool_contract, &liquidity_pool_contract).unwrap();
+
+    let HolderShareResponse { assets, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::HolderShare {
+                address: meta_pool_contract.addr().to_string(),
+            },
+        )
+        .unwrap();
+    let HoldingsResponse {
+        liquidity_pools,
+        nav,
+        ..
+    } = query_holdings(&app, &meta_pool_contract);
+
+    let liquidity_pool_value = assets[0].amount * Uint128::new(250) + assets[1].amount;
+
+    assert_eq!(
+        liquidity_pools[0].nav,
+        create_coin_asset(liquidity_pool_value.u128(), "uusd")
+    );
+    // 10 LUNA * 250 UST per LUNA + 2500 UST held by the meta pool itself
+    assert_eq!(
+        nav.amount,
+        Uint128::new(5_000_000_000) + liquidity_pool_value
+    );
+}
+
+#[test]
+fn outstanding_loans_are_part_of_the_value_of_liquidity_pools() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = test_env::create_martket_setup_with(&mut app, asset_infos("uusd"), |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+    });
+
+    let meta_pool_contract = setup_meta_pool(&mut app);
+
+    try_to_add_liquidity_pool(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_META_POOL_MAINTAINER,
+        &liquidity_pool_contract,
+    )
+    .unwrap();
+    try_to_provide_liquidity(&mut app, &meta_pool_contract, &liquidity_pool_contract).unwrap();
+
+    let HoldingsResponse { nav, .. } = query_holdings(&app, &meta_pool_contract);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+    .unwrap();
+
+    let HolderShareResponse { assets, loans, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::HolderShare {
+                address: meta_pool_contract.addr().to_string(),
+            },
+        )
+        .unwrap();
+    let HoldingsResponse {
+        liquidity_pools,
+        nav: nav_with_loan,
+        ..
+    } = query_holdings(&app, &meta_pool_contract);
+
+    // the loan is worth its notional, which is less than its collateral of 1 LUNA * 250 UST
+    assert!(loans[1].amount < loans[0].amount * Uint128::new(250));
+    assert_eq!(
+        liquidity_pools[0].nav,
+        create_coin_asset(
+            (assets[0].amount * Uint128::new(250) + assets[1].amount + loans[1].amount).u128(),
+            "uusd"
+        )
+    );
+    // the pool keeps the interest, so the meta pool does not lose the borrowed amount
+    assert!(nav_with_loan.amount > nav.amount);
+
+    // a later deposit does not buy into the loan at a discount
+    try_to_deposit(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_REGULAR_USER_2,
+        [
+            create_coin_asset(0, "uluna"),
+            create_coin_asset(nav_with_loan.amount.u128(), "uusd"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_share_balance(&app, &meta_pool_contract, ADDR_REGULAR_USER_2),
+        Uint128::new(10_000_000_000)
+    );
+}
+
+#[test]
+fn shares_are_withdrawn_in_kind() {
+    let mut app = test_env::mock_app();
+
+    let liquidity_pool_contract = setup_liquidity_pool(&mut app, "uusd");
+    let meta_pool_contract = setup_meta_pool(&mut app);
+
+    try_to_deposit(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_REGULAR_USER_2,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    try_to_add_liquidity_pool(
+        &mut app,
+        &meta_pool_contract,
+        ADDR_META_POOL_MAINTAINER,
+        &liquidity_pool_contract,
+    )
+    .unwrap();
+    try_to_provide_liquidity(&mut app, &meta_pool_contract, &liquidity_pool_contract).unwrap();
+
+    let share = test_env::query_lp_token_balance(
+        &app,
+        &liquidity_pool_contract,
+        meta_pool_contract.addr().as_str(),
+    );
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER_2),
+        cw20::Cw20Contract(meta_pool_contract.1.clone())
+            .call(Cw20ExecuteMsg::Send {
+                contract: meta_pool_contract.addr().to_string(),
+                amount: query_share_balance(&app, &meta_pool_contract, ADDR_REGULAR_USER_2),
+                msg: to_binary(&cw_zll_std_meta_pool::msg::Cw20HookMsg::Withdraw {
+                    recipient: None,
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Share holder is able to withdraw from the meta pool"
+    );
+
+    // half of the assets and of the LP tokens held by the meta pool
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER_2, "uluna")
+            .unwrap(),
+        coin(15_000_000, "uluna")
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER_2, "uusd")
+            .unwrap(),
+        coin(3_750_000_000, "uusd")
+    );
+    assert_eq!(
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_REGULAR_USER_2),
+        share.multiply_ratio(1u128, 2u128)
+    );
+    assert_eq!(
+        query_share_balance(&app, &meta_pool_contract, ADDR_REGULAR_USER_2),
+        Uint128::zero()
+    );
+}
+
+fn asset_infos(lending_denom: &str) -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: lending_denom.into(),
+        },
+    ]
+}
+
+/// Creates a market in the LP phase with a deposit of 20 LUNA & 5000 UST and returns its pool.
+fn setup_liquidity_pool(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    lending_denom: &str,
+) -> LiquidityPoolContract {
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup_with(app, asset_infos(lending_denom), |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, lending_denom),
+        ],
+    )
+    .unwrap();
+
+    liquidity_pool_contract
+}
+
+/// Creates a meta pool with a deposit of 20 LUNA & 5000 UST.
+fn setup_meta_pool(app: &mut App<Empty, TerraQueryWrapper>) -> MetaPoolContract {
+    let meta_pool_contract = test_env::create_meta_pool(
+        app,
+        asset_infos("uusd"),
+        Decimal::from_ratio(250u128, 1u128),
+    );
+
+    try_to_deposit(
+        app,
+        &meta_pool_contract,
+        ADDR_REGULAR_USER,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    meta_pool_contract
+}
+
+fn try_to_deposit(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    meta_pool_contract: &MetaPoolContract,
+    addr_depositor: &str,
+    assets: [Asset; 2],
+) -> anyhow::Result<AppResponse> {
+    let coins = assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| coin(asset.amount.u128(), asset.info.to_string()))
+        .collect::<Vec<_>>();
+
+    let _ = app.init_bank_balance(&Addr::unchecked(addr_depositor), coins.clone());
+
+    app.execute(
+        Addr::unchecked(addr_depositor),
+        meta_pool_contract
+            .call(
+                &cw_zll_std_meta_pool::msg::ExecuteMsg::Deposit {
+                    assets,
+                    min_share_out: None,
+                },
+                Some(coins),
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_add_liquidity_pool(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    meta_pool_contract: &MetaPoolContract,
+    sender: &str,
+    liquidity_pool_contract: &LiquidityPoolContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        meta_pool_contract
+            .call(
+                &cw_zll_std_meta_pool::msg::ExecuteMsg::AddLiquidityPool {
+                    liquidity_pool: liquidity_pool_contract.addr().to_string(),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_remove_liquidity_pool(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    meta_pool_contract: &MetaPoolContract,
+    liquidity_pool_contract: &LiquidityPoolContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_META_POOL_MAINTAINER),
+        meta_pool_contract
+            .call(
+                &cw_zll_std_meta_pool::msg::ExecuteMsg::RemoveLiquidityPool {
+                    liquidity_pool: liquidity_pool_contract.addr().to_string(),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+/// Provides 10 LUNA & 2500 UST of the meta pool to the liquidity pool.
+fn try_to_provide_liquidity(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    meta_pool_contract: &MetaPoolContract,
+    liquidity_pool_contract: &LiquidityPoolContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_META_POOL_MAINTAINER),
+        meta_pool_contract
+            .call(
+                &cw_zll_std_meta_pool::msg::ExecuteMsg::ProvideLiquidity {
+                    liquidity_pool: liquidity_pool_contract.addr().to_string(),
+                    assets: [
+                        create_coin_asset(10_000_000, "uluna"),
+                        create_coin_asset(2_500_000_000, "uusd"),
+                    ],
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_holdings(
+    app: &App<Empty, TerraQueryWrapper>,
+    meta_pool_contract: &MetaPoolContract,
+) -> HoldingsResponse {
+    app.wrap()
+        .query_wasm_smart(
+            meta_pool_contract.addr(),
+            &cw_zll_std_meta_pool::msg::QueryMsg::Holdings {},
+        )
+        .unwrap()
+}
+
+fn query_share_balance(
+    app: &App<Empty, TerraQueryWrapper>,
+    meta_pool_contract: &MetaPoolContract,
+    address: &str,
+) -> Uint128 {
+    cw20::Cw20Contract(meta_pool_contract.1.clone())
+        .balance(app, address)
+        .unwrap()
+}
@@ -0,0 +1,819 @@
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_liquidity_pool::ContractError as LiquidityPoolContractError;
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_LIQUIDITY_PROVIDER_2, ADDR_MARKET_OPERATOR,
+        ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_MARKET_STARTS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{LiquidityPoolContract, MarketContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::{AssetInfo, PoolResponse},
+    asset::create_coin_asset,
+    msg::Cw20HookMsg,
+};
+use cw_zll_std_market::{
+    response::{
+        CapacityLimitsResponse, CollateralPriceResponse, CommittedCollateralResponse,
+        LockedAssetsResponse, MarketPhasesInfoResponse, PendingUpdateResponse,
+    },
+    state::{CapacityLimits, MarketPhasesInfo, MarketUpdate, PendingUpdate},
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+const MIN_TIMELOCK_DELAY: u64 = 100;
+
+const TIMELOCK_DELAY: u64 = 100;
+
+#[test]
+fn operator_applies_queued_update_after_the_timelock() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app);
+
+    let update = MarketUpdate {
+        market_phases_info: Some(MarketPhasesInfo {
+            market_started_at: BLOCK_HEIGHT_MARKET_STARTS_AT,
+            lp_phase_ends_at: BLOCK_HEIGHT_LP_ENDS_AT,
+            amm_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT + 1000,
+            settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1000,
+        }),
+        collateral_price: Some(Decimal::from_ratio(200u128, 1u128)),
+        ..MarketUpdate::default()
+    };
+
+    let response = try_to_queue_update(&mut app, &market_contract, ADDR_REGULAR_USER, &update);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_queue_update(&mut app, &market_contract, ADDR_MARKET_OPERATOR, &update);
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to queue an update"
+    );
+
+    assert_eq!(
+        query_pending_update(&app, &market_contract),
+        PendingUpdateResponse {
+            timelock_delay: TIMELOCK_DELAY,
+            pending_update: Some(PendingUpdate {
+                update: update.clone(),
+                queued_at: BLOCK_HEIGHT_MARKET_STARTS_AT,
+                executable_at: BLOCK_HEIGHT_MARKET_STARTS_AT + TIMELOCK_DELAY,
+            }),
+        }
+    );
+
+    let response = try_to_queue_update(&mut app, &market_contract, ADDR_MARKET_OPERATOR, &update);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::UpdateAlreadyQueued {}.to_string()
+    );
+
+    let response = try_to_apply_update(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::UpdateTimelocked {
+            executable_at: BLOCK_HEIGHT_MARKET_STARTS_AT + TIMELOCK_DELAY
+        }
+        .to_string()
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_MARKET_STARTS_AT + TIMELOCK_DELAY;
+    });
+
+    let response = try_to_apply_update(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to apply the update once the timelock delay has passed"
+    );
+
+    let CollateralPriceResponse { price, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCollateralPrice {},
+        )
+        .unwrap();
+
+    assert_eq!(price, Decimal::from_ratio(200u128, 1u128));
+
+    let MarketPhasesInfoResponse {
+        amm_phase_ends_at,
+        settlement_phase_ends_at,
+        ..
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetMarketPhasesInfo {},
+        )
+        .unwrap();
+
+    assert_eq!(amm_phase_ends_at, BLOCK_HEIGHT_AMM_ENDS_AT + 1000);
+    assert_eq!(
+        settlement_phase_ends_at,
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1000
+    );
+
+    assert_eq!(
+        query_pending_update(&app, &market_contract).pending_update,
+        None
+    );
+
+    let response = try_to_apply_update(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::NoPendingUpdate {}.to_string()
+    );
+}
+
+#[test]
+fn liquidity_providers_withdraw_pro_rata_while_an_update_is_queued() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        [
+            create_coin_asset(10_000_000, "uluna"),
+            create_coin_asset(2_500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+    .unwrap();
+
+    let response = try_to_withdraw_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::Unauthorized {}.to_string()
+    );
+
+    try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &MarketUpdate {
+            alpha: Some(2),
+            ..MarketUpdate::default()
+        },
+    )
+    .unwrap();
+
+    let response = try_to_withdraw_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::Unauthorized {}.to_string(),
+        "Only an update of the phase schedule or the fees lets the liquidity providers exit"
+    );
+
+    try_to_cancel_update(&mut app, &market_contract).unwrap();
+    try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &update_buyback_spread(),
+    )
+    .unwrap();
+
+    let PoolResponse {
+        assets,
+        total_share,
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pool {},
+        )
+        .unwrap();
+    let share_ratio = Decimal::from_ratio(
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER_2),
+        total_share,
+    );
+
+    let uluna_balance = query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uluna");
+    let uusd_balance = query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uusd");
+
+    let response = try_to_withdraw_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+    );
+
+    assert!(
+        response.is_ok(),
+        "Liquidity provider is able to withdraw while an update is queued"
+    );
+
+    // the locked collateral stays in the pool for the remaining liquidity providers
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uluna"),
+        uluna_balance + assets[0].amount * share_ratio
+    );
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uusd"),
+        uusd_balance + assets[1].amount * share_ratio
+    );
+
+    let response = try_to_cancel_update(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to cancel the update"
+    );
+
+    let response =
+        try_to_withdraw_liquidity(&mut app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        LiquidityPoolContractError::Unauthorized {}.to_string()
+    );
+}
+
+#[test]
+fn liquidity_providers_exiting_early_leave_the_committed_collateral() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        [
+            create_coin_asset(10_000_000, "uluna"),
+            create_coin_asset(2_500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(1_000_000_000, "uusd"),
+    )
+    .unwrap();
+
+    let committed_collateral = query_committed_collateral(&app, &market_contract);
+
+    assert!(!committed_collateral.is_zero());
+
+    try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &update_buyback_spread(),
+    )
+    .unwrap();
+
+    let PoolResponse {
+        assets,
+        total_share,
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pool {},
+        )
+        .unwrap();
+    let share_ratio = Decimal::from_ratio(
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER_2),
+        total_share,
+    );
+
+    let uluna_balance = query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uluna");
+
+    let response = try_to_withdraw_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+    );
+
+    assert!(
+        response.is_ok(),
+        "Liquidity provider is able to withdraw while an update is queued"
+    );
+
+    // the collateral the pool may deliver for the PUT option stays for the remaining LPs
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uluna"),
+        uluna_balance + (assets[0].amount - committed_collateral) * share_ratio
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::SettlePuts { limit: None },
+                None,
+            )
+            .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_committed_collateral(&app, &market_contract),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn liquidity_providers_exiting_for_an_update_leave_the_locked_and_committed_assets() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market(&mut app);
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        [
+            create_coin_asset(10_000_000, "uluna"),
+            create_coin_asset(2_500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+    .unwrap();
+    test_env::try_to_lend(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        create_coin_asset(1_000_000_000, "uusd"),
+    )
+    .unwrap();
+
+    let LockedAssetsResponse { locked_assets } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLockedAssets {},
+        )
+        .unwrap();
+    let committed_collateral = query_committed_collateral(&app, &market_contract);
+
+    assert!(!locked_assets[0].amount.is_zero());
+    assert!(!committed_collateral.is_zero());
+
+    try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &update_phase_schedule(BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_AMM_ENDS_AT + 500),
+    )
+    .unwrap();
+
+    let pool = liquidity_pool_contract.addr().to_string();
+    let share =
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER_2);
+    let PoolResponse { total_share, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pool {},
+        )
+        .unwrap();
+    let share_ratio = Decimal::from_ratio(share, total_share);
+
+    let free_uluna =
+        query_balance(&app, &pool, "uluna") - locked_assets[0].amount - committed_collateral;
+    let free_uusd = query_balance(&app, &pool, "uusd") - locked_assets[1].amount;
+    let uluna_balance = query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uluna");
+    let uusd_balance = query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uusd");
+
+    let response = try_to_withdraw_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+    );
+
+    assert!(
+        response.is_ok(),
+        "Liquidity provider is able to withdraw while a phase schedule update is queued"
+    );
+
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uluna"),
+        uluna_balance + free_uluna * share_ratio
+    );
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER_2, "uusd"),
+        uusd_balance + free_uusd * share_ratio
+    );
+    assert!(
+        query_balance(&app, &pool, "uluna") >= locked_assets[0].amount + committed_collateral,
+        "The locked and the committed collateral stays in the pool"
+    );
+}
+
+#[test]
+fn timelock_delay_cannot_be_lower_than_the_minimum() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = test_env::create_martket_setup_with(
+        &mut app,
+        [
+            AssetInfo::NativeToken {
+                denom: "uluna".into(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".into(),
+            },
+        ],
+        |_| {},
+    );
+
+    assert_eq!(
+        query_pending_update(&app, &market_contract).timelock_delay,
+        MIN_TIMELOCK_DELAY
+    );
+
+    let response = try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &MarketUpdate {
+            timelock_delay: Some(0),
+            ..MarketUpdate::default()
+        },
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        format!(
+            "Generic error: `timelock_delay` = 0 must not be lower than {}",
+            MIN_TIMELOCK_DELAY
+        )
+    );
+}
+
+#[test]
+fn capacity_limits_are_loosened_only_by_a_queued_update() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app);
+
+    let capacity_limits = CapacityLimits {
+        max_position_size: Some(Uint128::new(200_000_000)),
+        max_account_exposure: None,
+        max_utilization: Some(Decimal::percent(50)),
+    };
+
+    let response = try_to_set_capacity_limits(&mut app, &market_contract, capacity_limits.clone());
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to tighten the capacity limits at once"
+    );
+
+    let looser_capacity_limits = CapacityLimits {
+        max_utilization: Some(Decimal::percent(80)),
+        ..capacity_limits.clone()
+    };
+
+    let response =
+        try_to_set_capacity_limits(&mut app, &market_contract, looser_capacity_limits.clone());
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::CapacityLimitsLoosened {}.to_string()
+    );
+
+    let response = try_to_set_capacity_limits(
+        &mut app,
+        &market_contract,
+        CapacityLimits {
+            max_position_size: None,
+            ..capacity_limits
+        },
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::CapacityLimitsLoosened {}.to_string()
+    );
+
+    try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &MarketUpdate {
+            capacity_limits: Some(looser_capacity_limits.clone()),
+            ..MarketUpdate::default()
+        },
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_MARKET_STARTS_AT + TIMELOCK_DELAY;
+    });
+
+    let response = try_to_apply_update(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to loosen the capacity limits with a queued update"
+    );
+
+    let CapacityLimitsResponse { capacity_limits } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCapacityLimits {},
+        )
+        .unwrap();
+
+    assert_eq!(capacity_limits, looser_capacity_limits);
+}
+
+#[test]
+fn phase_schedule_update_keeps_the_passed_boundaries() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT - 50;
+    });
+
+    let response = try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &update_phase_schedule(BLOCK_HEIGHT_LP_ENDS_AT + 10, BLOCK_HEIGHT_AMM_ENDS_AT),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        format!(
+            "Generic error: `lp_phase_ends_at` = {} cannot be changed to {} at block {}",
+            BLOCK_HEIGHT_LP_ENDS_AT,
+            BLOCK_HEIGHT_LP_ENDS_AT + 10,
+            BLOCK_HEIGHT_AMM_ENDS_AT - 50
+        )
+    );
+
+    let response = try_to_queue_update(
+        &mut app,
+        &market_contract,
+        ADDR_MARKET_OPERATOR,
+        &update_phase_schedule(BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_AMM_ENDS_AT + 500),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to queue an extension of the current phase"
+    );
+
+    // the current phase ends before the timelock delay passes
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 50;
+    });
+
+    let response = try_to_apply_update(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        format!(
+            "Generic error: `amm_phase_ends_at` = {} cannot be changed to {} at block {}",
+            BLOCK_HEIGHT_AMM_ENDS_AT,
+            BLOCK_HEIGHT_AMM_ENDS_AT + 500,
+            BLOCK_HEIGHT_AMM_ENDS_AT + 50
+        )
+    );
+}
+
+/// Creates a market with a timelock delay and a deposit of 20 LUNA & 5000 UST.
+fn setup_market(app: &mut App<Empty, TerraQueryWrapper>) -> MarketSetup {
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ];
+
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos, |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+        msg.timelock_delay = Some(TIMELOCK_DELAY);
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(20_000_000, "uluna"),
+            create_coin_asset(5_000_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    market_setup
+}
+
+fn update_phase_schedule(lp_phase_ends_at: u64, amm_phase_ends_at: u64) -> MarketUpdate {
+    MarketUpdate {
+        market_phases_info: Some(MarketPhasesInfo {
+            market_started_at: BLOCK_HEIGHT_MARKET_STARTS_AT,
+            lp_phase_ends_at,
+            amm_phase_ends_at,
+            settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+        }),
+        ..MarketUpdate::default()
+    }
+}
+
+fn update_buyback_spread() -> MarketUpdate {
+    MarketUpdate {
+        buyback_spread: Some(Decimal::percent(1)),
+        ..MarketUpdate::default()
+    }
+}
+
+fn try_to_queue_update(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    update: &MarketUpdate,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::QueueUpdate {
+                    update: update.clone(),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_set_capacity_limits(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    capacity_limits: CapacityLimits,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_MARKET_OPERATOR),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::SetCapacityLimits { capacity_limits },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_apply_update(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_MARKET_OPERATOR),
+        market_contract
+            .call(&cw_zll_std_market::msg::ExecuteMsg::ApplyUpdate {}, None)
+            .unwrap(),
+    )
+}
+
+fn try_to_cancel_update(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_MARKET_OPERATOR),
+        market_contract
+            .call(&cw_zll_std_market::msg::ExecuteMsg::CancelUpdate {}, None)
+            .unwrap(),
+    )
+}
+
+fn try_to_withdraw_liquidity(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+) -> anyhow::Result<AppResponse> {
+    test_env::try_to_withdraw_liquidity_with(
+        app,
+        liquidity_pool_contract,
+        addr_liquidity_provider,
+        Cw20HookMsg::WithdrawLiquidity {
+            recipient: None,
+            min_assets: None,
+            output_asset: None,
+        },
+    )
+}
+
+fn query_pending_update(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> PendingUpdateResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPendingUpdate {},
+        )
+        .unwrap()
+}
+
+fn query_committed_collateral(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> Uint128 {
+    let CommittedCollateralResponse {
+        committed_collateral,
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCommittedCollateral {},
+        )
+        .unwrap();
+
+    committed_collateral.amount
+}
+
+fn query_balance(app: &App<Empty, TerraQueryWrapper>, address: &str, denom: &str) -> Uint128 {
+    app.wrap().query_balance(address, denom).unwrap().amount
+}
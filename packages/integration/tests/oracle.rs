@@ -0,0 +1,231 @@
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_multi_test::Executor;
+use cw_zll_std_integration::{
+    consts::{ADDR_ADMIN, ADDR_LIQUIDITY_PROVIDER},
+    contract_helpers::ContractBase,
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::AssetInfo, asset::create_coin_asset, response::NavPerShareResponse,
+};
+use cw_zll_std_market::response::{CollateralPriceResponse, PriceSource};
+use cw_zll_std_oracle::msg::PriceResponse;
+
+fn asset_infos() -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ]
+}
+
+#[test]
+fn oracle_reports_the_price_set_by_tests() {
+    let mut app = test_env::mock_app();
+
+    let oracle_contract = test_env::create_oracle(&mut app);
+    let [luna, ust] = asset_infos();
+
+    let set_price_msg = oracle_contract
+        .call(
+            &cw_zll_std_integration::mock_oracle::ExecuteMsg::SetPrice {
+                base: luna.clone(),
+                quote: ust.clone(),
+                price: Decimal::from_ratio(95u128, 1u128),
+                publish_time: Some(1_650_000_000),
+                confidence: Some(Decimal::percent(50)),
+            },
+            None,
+        )
+        .unwrap();
+
+    app.execute(Addr::unchecked(ADDR_ADMIN), set_price_msg)
+        .unwrap();
+
+    let response: PriceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            oracle_contract.addr(),
+            &cw_zll_std_oracle::msg::QueryMsg::Price {
+                base: luna.clone(),
+                quote: ust.clone(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        response,
+        PriceResponse {
+            price: Decimal::from_ratio(95u128, 1u128),
+            publish_time: 1_650_000_000,
+            confidence: Decimal::percent(50),
+        }
+    );
+
+    let response: Result<PriceResponse, _> = app.wrap().query_wasm_smart(
+        oracle_contract.addr(),
+        &cw_zll_std_oracle::msg::QueryMsg::Price {
+            base: ust,
+            quote: luna,
+        },
+    );
+
+    assert!(
+        response.is_err(),
+        "Oracle must not report a price which has not been set"
+    );
+}
+
+#[test]
+fn market_reports_configured_collateral_price_without_oracle() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = test_env::create_martket_setup_with(&mut app, asset_infos(), |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+    });
+
+    let response: CollateralPriceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCollateralPrice {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        response,
+        CollateralPriceResponse {
+            price: Decimal::from_ratio(250u128, 1u128),
+            source: PriceSource::Config,
+            publish_time: None,
+            confidence: None,
+        }
+    );
+}
+
+#[test]
+fn market_prefers_oracle_price_over_configured_price() {
+    let mut app = test_env::mock_app();
+
+    let oracle_contract = test_env::create_oracle(&mut app);
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        &mut app,
+        &oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(95u128, 1u128),
+    )
+    .unwrap();
+
+    let MarketSetup {
+        market_contract, ..
+    } = test_env::create_martket_setup_with(&mut app, asset_infos(), |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+        msg.oracle = Some(oracle_contract.addr().to_string());
+    });
+
+    let response: CollateralPriceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCollateralPrice {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        response,
+        CollateralPriceResponse {
+            price: Decimal::from_ratio(95u128, 1u128),
+            source: PriceSource::Oracle,
+            publish_time: Some(app.block_info().time.seconds()),
+            confidence: Some(Decimal::zero()),
+        }
+    );
+}
+
+#[test]
+fn market_cannot_report_collateral_price_missing_in_oracle() {
+    let mut app = test_env::mock_app();
+
+    let oracle_contract = test_env::create_oracle(&mut app);
+
+    let MarketSetup {
+        market_contract, ..
+    } = test_env::create_martket_setup_with(&mut app, asset_infos(), |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+        msg.oracle = Some(oracle_contract.addr().to_string());
+    });
+
+    let response: Result<CollateralPriceResponse, _> = app.wrap().query_wasm_smart(
+        market_contract.addr(),
+        &cw_zll_std_market::msg::QueryMsg::GetCollateralPrice {},
+    );
+
+    assert!(
+        response.is_err(),
+        "Market must not fall back to the configured price when its oracle has no price"
+    );
+}
+
+#[test]
+fn nav_per_share_follows_oracle_price() {
+    let mut app = test_env::mock_app();
+
+    let oracle_contract = test_env::create_oracle(&mut app);
+    let [luna, ust] = asset_infos();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup_with(&mut app, asset_infos(), |msg| {
+        msg.oracle = Some(oracle_contract.addr().to_string());
+    });
+
+    //provide 500 UST & 2 LUNA
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(2_000_000, "uluna"),
+            create_coin_asset(500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    for (price, nav) in [(250u128, 1_000_000_000u128), (100u128, 700_000_000u128)] {
+        test_env::set_oracle_price(
+            &mut app,
+            &oracle_contract,
+            luna.clone(),
+            ust.clone(),
+            Decimal::from_ratio(price, 1u128),
+        )
+        .unwrap();
+
+        let response: NavPerShareResponse = app
+            .wrap()
+            .query_wasm_smart(
+                liquidity_pool_contract.addr(),
+                &cw_zll_std_liquidity_pool::msg::QueryMsg::NavPerShare {},
+            )
+            .unwrap();
+
+        assert_eq!(
+            response,
+            NavPerShareResponse {
+                nav: create_coin_asset(nav, "uusd"),
+                total_share: Uint128::new(31_622_776),
+                nav_per_share: Decimal::from_ratio(nav, 31_622_776u128),
+                collateral_price: Decimal::from_ratio(price, 1u128),
+            }
+        );
+    }
+}
@@ -1,13 +1,23 @@
+use cosmwasm_std::{Addr, Uint128};
+use cw_multi_test::Executor;
+use cw_zll_liquidity_pool::contract::MINIMUM_LIQUIDITY_AMOUNT;
 use cw_zll_std_integration::{
-    consts::{ADDR_LIQUIDITY_PROVIDER, ADDR_MARKET_OPERATOR},
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_LIQUIDITY_PROVIDER_2, ADDR_MARKET_OPERATOR,
+        ADDR_REGULAR_USER, BLOCK_HEIGHT_AMM_ENDS_AT, BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_MARKET_STARTS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
     contract_helpers::ContractBase,
     test_env::{self, MarketSetup},
 };
 use cw_zll_std_liquidity_pool::{
     ap::AssetInfo,
     asset::{create_coin_asset, create_token_asset},
+    response::LiquidityProgressResponse,
+};
+use cw_zll_std_market::response::{
+    LiquidityPoolResponse, MarketOperatorResponse, MarketPhase, MarketPhasesInfoResponse,
 };
-use cw_zll_std_market::response::{LiquidityPoolResponse, MarketOperatorResponse};
 
 #[test]
 fn admin_can_create_a_new_market_with_designated_market_operator() {
@@ -160,3 +170,372 @@ fn liquidity_pool_provider_can_provide_assets_to_the_pool_only_with_correct_rati
 
     assert_eq!(response.is_ok(), true);
 }
+
+#[test]
+fn first_liquidity_provider_receives_shares_reduced_by_permanently_locked_minimum_liquidity() {
+    let mut app = test_env::mock_app();
+
+    //provide 500 UST & 2 LUNA
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup(&mut app, asset_infos);
+
+    let response = test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    );
+
+    assert!(response.is_ok(), "deposit should be accepted");
+
+    // sqrt(2_000_000 * 500_000_000) = 31_622_776
+    assert_eq!(
+        test_env::query_lp_token_balance(&app, &liquidity_pool_contract, ADDR_LIQUIDITY_PROVIDER),
+        Uint128::new(31_622_776) - MINIMUM_LIQUIDITY_AMOUNT
+    );
+    assert_eq!(
+        test_env::query_lp_token_balance(
+            &app,
+            &liquidity_pool_contract,
+            liquidity_pool_contract.addr().as_str()
+        ),
+        MINIMUM_LIQUIDITY_AMOUNT
+    );
+}
+
+#[test]
+fn first_liquidity_provider_cannot_deposit_less_than_the_minimum_liquidity() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(1_000, "uluna"),
+        create_coin_asset(1_000, "uusd"),
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup(&mut app, asset_infos);
+
+    let response = test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    );
+
+    assert!(
+        response.is_err(),
+        "the first deposit has to mint more than the locked minimum liquidity"
+    );
+}
+
+#[test]
+fn liquidity_provider_cannot_deposit_an_amount_that_mints_zero_shares() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup(&mut app, asset_infos);
+
+    let response = test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    );
+
+    assert!(response.is_ok(), "first deposit should be accepted");
+
+    let response = test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        [create_coin_asset(1, "uluna"), create_coin_asset(1, "uusd")],
+    );
+
+    assert!(response.is_err(), "a deposit minting zero shares must fail");
+}
+
+#[test]
+fn liquidity_provider_can_require_a_minimum_amount_of_shares_for_a_deposit() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup(&mut app, asset_infos);
+
+    let expected_share = Uint128::new(31_622_776) - MINIMUM_LIQUIDITY_AMOUNT;
+
+    let response = test_env::try_to_deposit_liquidity_with_min_share_out(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity.clone(),
+        Some(expected_share + Uint128::new(1)),
+    );
+
+    assert!(
+        response.is_err(),
+        "deposit minting less than the minimum shares out must fail"
+    );
+
+    let response = test_env::try_to_deposit_liquidity_with_min_share_out(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+        Some(expected_share),
+    );
+
+    assert!(
+        response.is_ok(),
+        "deposit minting the minimum shares out should be accepted"
+    );
+}
+
+#[test]
+fn liquidity_pool_reports_progress_towards_the_target_liquidity() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup_with(&mut app, asset_infos, |msg| {
+        msg.target_liquidity = Some(Uint128::new(1_000_000_000)); // 1000 UST
+    });
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    let response: LiquidityProgressResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::LiquidityProgress {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        response,
+        LiquidityProgressResponse {
+            liquidity: create_coin_asset(500_000_000, "uusd"),
+            target_liquidity: Some(create_coin_asset(1_000_000_000, "uusd")),
+            is_target_reached: false,
+        }
+    );
+}
+
+#[test]
+fn market_enters_amm_phase_early_once_the_target_liquidity_is_reached() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = test_env::create_martket_setup_with(&mut app, asset_infos, |msg| {
+        msg.target_liquidity = Some(Uint128::new(500_000_000)); // 500 UST
+    });
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = 1300;
+    });
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::EnterAutomatedMarketMakerPhase {},
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "market can enter the AMM phase once the target liquidity is reached"
+    );
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::AutomatedMarketMaker
+    );
+
+    // all later boundaries are shifted by the same number of blocks
+    let shift = BLOCK_HEIGHT_LP_ENDS_AT - 1299;
+
+    assert_eq!(
+        market_contract.get_market_phases_info(&app).unwrap(),
+        MarketPhasesInfoResponse {
+            market_started_at: BLOCK_HEIGHT_MARKET_STARTS_AT,
+            lp_phase_ends_at: 1299,
+            amm_phase_ends_at: BLOCK_HEIGHT_AMM_ENDS_AT - shift,
+            settlement_phase_ends_at: BLOCK_HEIGHT_SETTLEMENT_ENDS_AT - shift,
+        }
+    );
+}
+
+#[test]
+fn market_cannot_enter_amm_phase_early_before_the_target_liquidity_is_reached() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = test_env::create_martket_setup_with(&mut app, asset_infos, |msg| {
+        msg.target_liquidity = Some(Uint128::new(1_000_000_000)); // 1000 UST
+    });
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = 1300;
+    });
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::EnterAutomatedMarketMakerPhase {},
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_err(),
+        "market cannot enter the AMM phase before the target liquidity is reached"
+    );
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::ProvidingLiquidity
+    );
+}
+
+#[test]
+fn market_without_target_liquidity_cannot_enter_amm_phase_early() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = test_env::create_martket_setup(&mut app, asset_infos);
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = 1300;
+    });
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::EnterAutomatedMarketMakerPhase {},
+                None,
+            )
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_err(),
+        "market without target liquidity follows its configured phases"
+    );
+}
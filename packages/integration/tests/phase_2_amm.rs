@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, Empty};
+use cosmwasm_std::{coins, Addr, Decimal, Empty};
 use cw_multi_test::{App, Executor};
 use cw_zll_std_integration::{
     consts::{ADDR_LIQUIDITY_PROVIDER, ADDR_REGULAR_USER, BLOCK_HEIGHT_LP_ENDS_AT},
@@ -6,7 +6,7 @@ use cw_zll_std_integration::{
     test_env::{self, MarketSetup},
 };
 use cw_zll_std_liquidity_pool::asset::create_coin_asset;
-use cw_zll_std_market::borrow::BorrowingTermsResponse;
+use cw_zll_std_market::{borrow::BorrowingTermsResponse, lend::LendingTermsResponse};
 use terra_cosmwasm::TerraQueryWrapper;
 
 #[test]
@@ -25,21 +25,18 @@ fn borrower_can_borrow() {
             market_contract.addr(),
             &cw_zll_std_market::msg::QueryMsg::GetBorrowingTerms {
                 pledged_collateral: pledged_collateral.clone(),
+                strike_id: None,
+                account: None,
             },
         )
         .unwrap();
 
-    let response = app.execute(
-        Addr::unchecked(ADDR_REGULAR_USER),
-        market_contract
-            .call(
-                &cw_zll_std_market::msg::ExecuteMsg::Borrow {
-                    expected_borrow: borrow,
-                    pledged_collateral,
-                },
-                None,
-            )
-            .unwrap(),
+    let response = test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        borrow,
+        pledged_collateral,
     );
 
     println!("{:?}", &response);
@@ -58,9 +55,49 @@ fn borrower_can_check_borrowing_terms() {
 }
 
 #[test]
-#[ignore]
 fn lender_can_lend() {
-    todo!()
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market_past_providing_liquidity_phase(&mut app);
+
+    let lent_asset = create_coin_asset(100_000_000, "uusd");
+
+    let LendingTermsResponse { premium, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLendingTerms {
+                lent_asset: lent_asset.clone(),
+                account: None,
+            },
+        )
+        .unwrap();
+
+    app.init_bank_balance(
+        &Addr::unchecked(ADDR_REGULAR_USER),
+        coins(lent_asset.amount.u128(), "uusd"),
+    )
+    .unwrap();
+
+    let response = app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::Lend {
+                    expected_premium: premium,
+                    lent_asset: lent_asset.clone(),
+                },
+                Some(coins(lent_asset.amount.u128(), "uusd")),
+            )
+            .unwrap(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Lender is able to lend during the AMM phase"
+    );
 }
 
 #[test]
@@ -115,7 +152,9 @@ fn setup_market_past_providing_liquidity_phase(
         .clone()
         .map(|asset| asset.info);
 
-    let market_setup = test_env::create_martket_setup(&mut app, asset_infos);
+    let market_setup = test_env::create_martket_setup_with(&mut app, asset_infos, |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+    });
 
     for assets_to_provide_as_liquidity in list_of_assets_to_provide_as_liquidity {
         test_env::try_to_deposit_liquidity(
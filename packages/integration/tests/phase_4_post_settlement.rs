@@ -1,3 +1,16 @@
+use cosmwasm_std::{Decimal, Empty, Uint128};
+use cw_multi_test::App;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_REGULAR_USER, BLOCK_HEIGHT_LP_ENDS_AT,
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{ap::AssetInfo, asset::create_coin_asset, msg::Cw20HookMsg};
+use cw_zll_std_market::response::MarketPhase;
+use terra_cosmwasm::TerraQueryWrapper;
+
 #[test]
 #[ignore]
 fn all_handlers_from_other_phases_are_not_avaialable() {
@@ -5,9 +18,211 @@ fn all_handlers_from_other_phases_are_not_avaialable() {
 }
 
 #[test]
-#[ignore]
 fn liquidity_providers_can_withdraw_pro_rated_liquditiy_by_burning_their_lp_tokens() {
-    todo!()
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = setup_market_in_post_settlement_phase(&mut app);
+
+    let response =
+        test_env::try_to_withdraw_liquidity(&mut app, &market_contract, &liquidity_pool_contract);
+
+    assert!(
+        response.is_ok(),
+        "withdrawing liquidity must be possible in the post-settlement phase"
+    );
+
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER, "uluna"),
+        Uint128::new(2_000_000) * provider_share_ratio()
+    );
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER, "uusd"),
+        Uint128::new(500_000_000) * provider_share_ratio()
+    );
+}
+
+#[test]
+fn liquidity_providers_can_withdraw_liquidity_to_another_recipient() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_in_post_settlement_phase(&mut app);
+
+    let response = test_env::try_to_withdraw_liquidity_with(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        Cw20HookMsg::WithdrawLiquidity {
+            recipient: Some(ADDR_REGULAR_USER.into()),
+            min_assets: None,
+            output_asset: None,
+        },
+    );
+
+    assert!(
+        response.is_ok(),
+        "withdrawing liquidity to another recipient must be possible"
+    );
+
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER, "uusd"),
+        Uint128::zero()
+    );
+    assert_eq!(
+        query_balance(&app, ADDR_REGULAR_USER, "uluna"),
+        Uint128::new(2_000_000) * provider_share_ratio()
+    );
+    assert_eq!(
+        query_balance(&app, ADDR_REGULAR_USER, "uusd"),
+        Uint128::new(500_000_000) * provider_share_ratio()
+    );
+}
+
+#[test]
+fn liquidity_providers_withdrawal_fails_when_refund_is_below_minimum_amounts() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_in_post_settlement_phase(&mut app);
+
+    let refund_amount = Uint128::new(500_000_000) * provider_share_ratio();
+
+    let response = test_env::try_to_withdraw_liquidity_with(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        Cw20HookMsg::WithdrawLiquidity {
+            recipient: None,
+            min_assets: Some(vec![create_coin_asset(refund_amount.u128() + 1, "uusd")]),
+            output_asset: None,
+        },
+    );
+
+    assert!(
+        response.is_err(),
+        "withdrawal must fail when the refund is below the minimum amounts"
+    );
+
+    let response = test_env::try_to_withdraw_liquidity_with(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        Cw20HookMsg::WithdrawLiquidity {
+            recipient: None,
+            min_assets: Some(vec![create_coin_asset(refund_amount.u128(), "uusd")]),
+            output_asset: None,
+        },
+    );
+
+    assert!(
+        response.is_ok(),
+        "withdrawal must succeed when the refund meets the minimum amounts"
+    );
+}
+
+#[test]
+fn liquidity_providers_can_withdraw_liquidity_in_a_single_asset() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_in_post_settlement_phase(&mut app);
+
+    let response = test_env::try_to_withdraw_liquidity_with(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        Cw20HookMsg::WithdrawLiquidity {
+            recipient: None,
+            min_assets: None,
+            output_asset: Some(AssetInfo::NativeToken {
+                denom: "uusd".into(),
+            }),
+        },
+    );
+
+    assert!(
+        response.is_ok(),
+        "withdrawing liquidity in a single asset must be possible in the post-settlement phase"
+    );
+
+    let refund_uluna = Uint128::new(2_000_000) * provider_share_ratio();
+    let refund_uusd = Uint128::new(500_000_000) * provider_share_ratio();
+    let swapped_uusd = (Uint128::new(500_000_000) - refund_uusd)
+        .multiply_ratio(refund_uluna, Uint128::new(2_000_000));
+
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER, "uluna"),
+        Uint128::zero()
+    );
+    assert_eq!(
+        query_balance(&app, ADDR_LIQUIDITY_PROVIDER, "uusd"),
+        refund_uusd + swapped_uusd
+    );
+}
+
+#[test]
+fn liquidity_providers_cannot_withdraw_liquidity_in_a_single_asset_before_post_settlement() {
+    let mut app = test_env::mock_app();
+
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = test_env::create_martket_setup_with(&mut app, asset_infos, |msg| {
+        msg.minimum_liquidity = Some(Uint128::new(1_000_000_000)); // 1000 UST
+    });
+
+    test_env::try_to_deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    assert_eq!(
+        market_contract.get_market_phase(&app).unwrap(),
+        MarketPhase::Cancelled
+    );
+
+    let response = test_env::try_to_withdraw_liquidity_with(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        Cw20HookMsg::WithdrawLiquidity {
+            recipient: None,
+            min_assets: None,
+            output_asset: Some(AssetInfo::NativeToken {
+                denom: "uusd".into(),
+            }),
+        },
+    );
+
+    assert!(
+        response.is_err(),
+        "withdrawing liquidity in a single asset is only possible in the post-settlement phase"
+    );
 }
 
 #[test]
@@ -15,3 +230,46 @@ fn liquidity_providers_can_withdraw_pro_rated_liquditiy_by_burning_their_lp_toke
 fn lenders_can_claim_collateral_only_for_put_options_that_were_not_exercised() {
     todo!()
 }
+
+fn setup_market_in_post_settlement_phase(app: &mut App<Empty, TerraQueryWrapper>) -> MarketSetup {
+    //provide 500 UST & 2 LUNA
+    let assets_to_provide_as_liquidity = [
+        create_coin_asset(2_000_000, "uluna"),  // 2 LUNA
+        create_coin_asset(500_000_000, "uusd"), // 500 UST
+    ];
+
+    let asset_infos = assets_to_provide_as_liquidity
+        .clone()
+        .map(|asset| asset.info);
+
+    let market_setup = test_env::create_martket_setup(app, asset_infos);
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        assets_to_provide_as_liquidity,
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    assert_eq!(
+        market_setup.market_contract.get_market_phase(app).unwrap(),
+        MarketPhase::PostSettlement
+    );
+
+    market_setup
+}
+
+/// The share of the only liquidity provider, the minimum liquidity stays locked in the pool
+fn provider_share_ratio() -> Decimal {
+    // sqrt(2_000_000 * 500_000_000) = 31_622_776
+    Decimal::from_ratio(31_621_776u128, 31_622_776u128)
+}
+
+fn query_balance(app: &App<Empty, TerraQueryWrapper>, address: &str, denom: &str) -> Uint128 {
+    app.wrap().query_balance(address, denom).unwrap().amount
+}
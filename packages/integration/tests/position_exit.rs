@@ -0,0 +1,266 @@
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_AMM_ENDS_AT},
+    contract_helpers::ContractBase,
+    contract_mocks::MarketContract,
+    test_env,
+};
+use cw_zll_std_liquidity_pool::asset::create_coin_asset;
+use cw_zll_std_market::{
+    response::{ExitQuoteResponse, LockedAssetsResponse, PositionResponse},
+    state::PositionStatus,
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn lender_can_check_the_exit_quote() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_buyback_spread(&mut app, None);
+    lend_100_ust(&mut app, &market_contract, ADDR_REGULAR_USER).unwrap();
+
+    let ExitQuoteResponse {
+        lent,
+        option_value,
+        spread,
+        amount,
+        remaining_buyback_volume,
+    } = query_exit_quote(&app, &market_contract, 1);
+
+    assert_eq!(lent, create_coin_asset(100_000_000, "uusd"));
+    // 0.4 LUNA * 250 UST * 20% * sqrt(1110 / 4_204_800 years) / sqrt(2 * pi)
+    assert_eq!(option_value, create_coin_asset(129_636, "uusd"));
+    // the pool keeps 2% of the lent asset netted against the option value, rounded in its favour
+    let netted_amount = lent.amount - option_value.amount;
+    assert_eq!(
+        amount,
+        create_coin_asset((netted_amount * Decimal::percent(98)).u128(), "uusd")
+    );
+    assert_eq!(
+        spread,
+        create_coin_asset((netted_amount - amount.amount).u128(), "uusd")
+    );
+    assert_eq!(remaining_buyback_volume, None);
+}
+
+#[test]
+fn lender_can_exit_the_position_during_amm_phase() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_buyback_spread(&mut app, None);
+    lend_100_ust(&mut app, &market_contract, ADDR_REGULAR_USER).unwrap();
+
+    let ExitQuoteResponse { amount, .. } = query_exit_quote(&app, &market_contract, 1);
+
+    let response = try_to_exit_position(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        amount.amount + Uint128::new(1),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        format!(
+            "Generic error: Expected return ({}) is higher than calculated return ({})",
+            amount.amount + Uint128::new(1),
+            amount.amount
+        )
+    );
+
+    let response = try_to_exit_position(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        1,
+        amount.amount,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let balance_before = query_uusd_balance(&app, ADDR_REGULAR_USER);
+
+    let response = try_to_exit_position(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        amount.amount,
+    );
+
+    assert!(
+        response.is_ok(),
+        "Lender is able to exit the position during the AMM phase"
+    );
+
+    assert_eq!(
+        query_uusd_balance(&app, ADDR_REGULAR_USER),
+        balance_before + amount.amount
+    );
+
+    let PositionResponse { position } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPosition { position_id: 1 },
+        )
+        .unwrap();
+
+    assert_eq!(position.status, PositionStatus::Exited);
+
+    let LockedAssetsResponse { locked_assets } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLockedAssets {},
+        )
+        .unwrap();
+
+    assert_eq!(locked_assets[1], create_coin_asset(0, "uusd"));
+
+    let response = try_to_exit_position(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        amount.amount,
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::PositionNotOpen { position_id: 1 }.to_string()
+    );
+}
+
+#[test]
+fn buybacks_are_capped_per_market() {
+    let mut app = test_env::mock_app();
+
+    let market_contract =
+        setup_market_with_buyback_spread(&mut app, Some(Uint128::new(150_000_000)));
+    lend_100_ust(&mut app, &market_contract, ADDR_REGULAR_USER).unwrap();
+    lend_100_ust(&mut app, &market_contract, ADDR_REGULAR_USER_2).unwrap();
+
+    let response = try_to_exit_position(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        1,
+        Uint128::zero(),
+    );
+
+    assert!(
+        response.is_ok(),
+        "Lender is able to exit while the buyback cap is not reached"
+    );
+
+    let ExitQuoteResponse {
+        remaining_buyback_volume,
+        ..
+    } = query_exit_quote(&app, &market_contract, 2);
+
+    assert_eq!(remaining_buyback_volume, Some(Uint128::new(50_000_000)));
+
+    let response = try_to_exit_position(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        2,
+        Uint128::zero(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::BuybackCapExceeded {
+            lent_amount: Uint128::new(100_000_000),
+            remaining_buyback_volume: Uint128::new(50_000_000),
+        }
+        .to_string()
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response = try_to_exit_position(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        2,
+        Uint128::zero(),
+    );
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string(),
+        "Positions can only be exited during the AMM phase"
+    );
+}
+
+/// Creates a market in the AMM phase with a deposit of 20 LUNA & 5000 UST and a 2% buyback spread.
+fn setup_market_with_buyback_spread(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    max_buyback_volume: Option<Uint128>,
+) -> MarketContract {
+    test_env::setup_market_in_amm_phase(app, |msg| {
+        msg.buyback_spread = Some(Decimal::percent(2));
+        msg.max_buyback_volume = max_buyback_volume;
+    })
+}
+
+fn lend_100_ust(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_lender: &str,
+) -> anyhow::Result<AppResponse> {
+    test_env::try_to_lend(
+        app,
+        market_contract,
+        addr_lender,
+        create_coin_asset(100_000_000, "uusd"),
+    )
+}
+
+fn try_to_exit_position(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_lender: &str,
+    position_id: u64,
+    expected_return: Uint128,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(addr_lender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::ExitPosition {
+                    position_id,
+                    expected_return: create_coin_asset(expected_return.u128(), "uusd"),
+                },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_exit_quote(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    position_id: u64,
+) -> ExitQuoteResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetExitQuote { position_id },
+        )
+        .unwrap()
+}
+
+fn query_uusd_balance(app: &App<Empty, TerraQueryWrapper>, addr: &str) -> Uint128 {
+    app.wrap().query_balance(addr, "uusd").unwrap().amount
+}
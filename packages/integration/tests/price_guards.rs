@@ -0,0 +1,248 @@
+use cosmwasm_std::{Decimal, Empty};
+use cw_multi_test::{App, AppResponse};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{ADDR_LIQUIDITY_PROVIDER, ADDR_REGULAR_USER, BLOCK_HEIGHT_LP_ENDS_AT},
+    contract_helpers::ContractBase,
+    contract_mocks::{MarketContract, OracleContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{ap::AssetInfo, asset::create_coin_asset};
+use cw_zll_std_market::response::PriceGuardsResponse;
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn borrowing_is_rejected_when_oracle_price_is_stale() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        _,
+    ) = setup_market_with_oracle_price(&mut app, Decimal::from_ratio(250u128, 1u128));
+
+    // the oracle price was published 120 seconds ago
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+        block.time = block.time.plus_seconds(120);
+    });
+
+    let response = try_to_borrow(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::StalePrice {
+            price_age: 120,
+            max_price_age: 60,
+        }
+        .to_string()
+    );
+
+    let response = try_to_lend(&mut app, &market_contract);
+
+    assert!(
+        response.is_err(),
+        "Lending must be rejected when the oracle price is stale"
+    );
+}
+
+#[test]
+fn borrowing_is_rejected_when_oracle_price_deviates_from_pool_price() {
+    let mut app = test_env::mock_app();
+
+    // the pool implies a price of 250 UST per LUNA
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        _,
+    ) = setup_market_with_oracle_price(&mut app, Decimal::from_ratio(200u128, 1u128));
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    let response = try_to_borrow(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::PriceDeviationTooHigh {
+            price: Decimal::from_ratio(200u128, 1u128),
+            pool_price: Decimal::from_ratio(250u128, 1u128),
+            price_deviation: Decimal::percent(20),
+            max_price_deviation: Decimal::percent(10),
+        }
+        .to_string()
+    );
+
+    let response = try_to_lend(&mut app, &market_contract);
+
+    assert!(
+        response.is_err(),
+        "Lending must be rejected when the oracle price deviates too much from the pool price"
+    );
+}
+
+#[test]
+fn borrowing_is_accepted_when_oracle_price_passes_the_guards() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        _,
+    ) = setup_market_with_oracle_price(&mut app, Decimal::from_ratio(240u128, 1u128));
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+        block.time = block.time.plus_seconds(60);
+    });
+
+    let response = try_to_borrow(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to borrow when the oracle price is fresh and close to the pool price"
+    );
+
+    let response = try_to_lend(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Lender is able to lend when the oracle price is fresh and close to the pool price"
+    );
+}
+
+#[test]
+fn anyone_can_check_the_price_guards() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        oracle_contract,
+    ) = setup_market_with_oracle_price(&mut app, Decimal::from_ratio(275u128, 1u128));
+
+    app.update_block(|block| {
+        block.time = block.time.plus_seconds(90);
+    });
+
+    let response: PriceGuardsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPriceGuards {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        response,
+        PriceGuardsResponse {
+            max_price_age: Some(60),
+            max_price_deviation: Some(Decimal::percent(10)),
+            price: Some(Decimal::from_ratio(275u128, 1u128)),
+            price_age: Some(90),
+            pool_price: Some(Decimal::from_ratio(250u128, 1u128)),
+            price_deviation: Some(Decimal::percent(10)),
+            is_price_stale: true,
+            is_price_deviation_too_high: false,
+        }
+    );
+
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        &mut app,
+        &oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(300u128, 1u128),
+    )
+    .unwrap();
+
+    let response: PriceGuardsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPriceGuards {},
+        )
+        .unwrap();
+
+    assert_eq!(response.price_age, Some(0));
+    assert_eq!(response.price_deviation, Some(Decimal::percent(20)));
+    assert!(
+        !response.is_price_stale && response.is_price_deviation_too_high,
+        "A fresh price deviating by 20% only trips the deviation guard"
+    );
+}
+
+fn asset_infos() -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ]
+}
+
+/// Creates a market accepting oracle prices up to 60 seconds old and deviating
+/// up to 10% from the pool price of 250 UST per LUNA.
+fn setup_market_with_oracle_price(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    price: Decimal,
+) -> (MarketSetup, OracleContract) {
+    let oracle_contract = test_env::create_oracle(app);
+    let [luna, ust] = asset_infos();
+
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos(), |msg| {
+        msg.oracle = Some(oracle_contract.addr().to_string());
+        msg.max_price_age = Some(60);
+        msg.max_price_deviation = Some(Decimal::percent(10));
+    });
+
+    test_env::set_oracle_price(app, &oracle_contract, luna, ust, price).unwrap();
+
+    //provide 500 UST & 2 LUNA
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(2_000_000, "uluna"),
+            create_coin_asset(500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    (market_setup, oracle_contract)
+}
+
+fn try_to_borrow(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    test_env::try_to_borrow(
+        app,
+        market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(10_000, "uluna"), // 0.01 LUNA
+    )
+}
+
+fn try_to_lend(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    test_env::try_to_lend(
+        app,
+        market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(100_000_000, "uusd"),
+    )
+}
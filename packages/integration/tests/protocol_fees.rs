@@ -0,0 +1,220 @@
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, ADDR_TREASURY},
+    contract_helpers::ContractBase,
+    contract_mocks::MarketContract,
+    test_env,
+};
+use cw_zll_std_liquidity_pool::asset::create_coin_asset;
+use cw_zll_std_market::{
+    borrow::BorrowingTermsResponse, lend::LendingTermsResponse, response::ProtocolFeesResponse,
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn protocol_fee_is_taken_from_premiums_paid_at_borrow_and_lend_time() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_treasury(&mut app, Some(ADDR_TREASURY));
+
+    assert_eq!(
+        query_protocol_fees(&app, &market_contract),
+        ProtocolFeesResponse {
+            protocol_fee_rate: Decimal::percent(10),
+            treasury: Some(Addr::unchecked(ADDR_TREASURY)),
+            accrued_fees: create_coin_asset(0, "uusd"),
+            collected_fees: create_coin_asset(0, "uusd"),
+        }
+    );
+
+    let lent_asset = create_coin_asset(100_000_000, "uusd");
+    let LendingTermsResponse {
+        premium,
+        protocol_fee: lending_fee,
+        ..
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLendingTerms {
+                lent_asset: lent_asset.clone(),
+                account: None,
+            },
+        )
+        .unwrap();
+
+    // 0.4 LUNA * 250 UST * 20% * sqrt(1110 / 4_204_800 years) / sqrt(2 * pi)
+    assert_eq!(premium.amount + lending_fee.amount, Uint128::new(129_636));
+
+    // the lender is paid the premium net of the protocol fee
+    assert_eq!(
+        lending_fee.amount,
+        (premium.amount + lending_fee.amount) * Decimal::percent(10)
+    );
+
+    let response =
+        test_env::try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER, lent_asset).unwrap();
+
+    assert_eq!(
+        attribute(&response, "protocol_fee"),
+        Some(lending_fee.to_string())
+    );
+
+    let pledged_collateral = create_coin_asset(1_000_000, "uluna");
+    let BorrowingTermsResponse {
+        borrow,
+        interest,
+        protocol_fee: borrowing_fee,
+        ..
+    } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetBorrowingTerms {
+                pledged_collateral: pledged_collateral.clone(),
+                strike_id: None,
+                account: None,
+            },
+        )
+        .unwrap();
+
+    // 1 LUNA * 250 UST * 20% * sqrt(1110 / 4_204_800 years) / sqrt(2 * pi)
+    assert_eq!(interest.amount, Uint128::new(324_092));
+    assert_eq!(borrowing_fee.amount, interest.amount * Decimal::percent(10));
+
+    let response = test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER_2,
+        borrow,
+        pledged_collateral,
+    )
+    .unwrap();
+
+    assert_eq!(
+        attribute(&response, "protocol_fee"),
+        Some(borrowing_fee.to_string())
+    );
+
+    assert_eq!(
+        query_protocol_fees(&app, &market_contract).accrued_fees,
+        create_coin_asset((lending_fee.amount + borrowing_fee.amount).u128(), "uusd")
+    );
+}
+
+#[test]
+fn fees_can_only_be_collected_for_a_treasury() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_treasury(&mut app, None);
+
+    let response = try_to_collect_fees(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::TreasuryNotSet {}.to_string()
+    );
+
+    let market_contract = setup_market_with_treasury(&mut app, Some(ADDR_TREASURY));
+
+    let response = try_to_collect_fees(&mut app, &market_contract);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::NoFeesToCollect {}.to_string()
+    );
+}
+
+#[test]
+fn accrued_fees_are_collected_for_the_treasury() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_with_treasury(&mut app, Some(ADDR_TREASURY));
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+    .unwrap();
+
+    let accrued_fees = query_protocol_fees(&app, &market_contract).accrued_fees;
+
+    assert_eq!(accrued_fees, create_coin_asset(32_409, "uusd"));
+
+    let response = try_to_collect_fees(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Anyone can collect the fees for the treasury"
+    );
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_TREASURY, "uusd")
+            .unwrap()
+            .amount,
+        accrued_fees.amount
+    );
+    assert_eq!(
+        query_protocol_fees(&app, &market_contract),
+        ProtocolFeesResponse {
+            protocol_fee_rate: Decimal::percent(10),
+            treasury: Some(Addr::unchecked(ADDR_TREASURY)),
+            accrued_fees: create_coin_asset(0, "uusd"),
+            collected_fees: accrued_fees,
+        }
+    );
+}
+
+/// Creates a market in the AMM phase with a deposit of 20 LUNA & 5000 UST. Markets with a
+/// treasury take a protocol fee of 10%.
+fn setup_market_with_treasury(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    treasury: Option<&str>,
+) -> MarketContract {
+    test_env::setup_market_in_amm_phase(app, |msg| {
+        if let Some(treasury) = treasury {
+            msg.protocol_fee_rate = Some(Decimal::percent(10));
+            msg.treasury = Some(treasury.to_string());
+        }
+    })
+}
+
+fn try_to_collect_fees(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(&cw_zll_std_market::msg::ExecuteMsg::CollectFees {}, None)
+            .unwrap(),
+    )
+}
+
+fn query_protocol_fees(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> ProtocolFeesResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetProtocolFees {},
+        )
+        .unwrap()
+}
+
+/// Returns the value of the first attribute with the given key emitted by the contracts.
+fn attribute(response: &AppResponse, key: &str) -> Option<String> {
+    response
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.clone())
+}
@@ -0,0 +1,463 @@
+use cosmwasm_std::{Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_AMM_ENDS_AT,
+        BLOCK_HEIGHT_LP_ENDS_AT, BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{MarketContract, OracleContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::{AssetInfo, PoolResponse},
+    asset::create_coin_asset,
+};
+use cw_zll_std_market::{
+    lend::LendingTermsResponse,
+    response::{LockedAssetsResponse, PositionResponse, SettlementProgressResponse},
+    state::{Position, PositionStatus},
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn lender_opens_a_put_position_backed_by_the_pool() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract,
+            liquidity_pool_contract,
+        },
+        _,
+    ) = setup_market_with_oracle(&mut app);
+
+    let LendingTermsResponse { premium, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLendingTerms {
+                lent_asset: create_coin_asset(100_000_000, "uusd"),
+                account: None,
+            },
+        )
+        .unwrap();
+
+    // 0.4 LUNA * 250 UST * 20% * sqrt(1110 / 4_204_800 years) / sqrt(2 * pi)
+    assert_eq!(premium, create_coin_asset(129_636, "uusd"));
+
+    let response = try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER);
+
+    assert!(
+        response.is_ok(),
+        "Lender is able to lend during the AMM phase"
+    );
+
+    // the premium is paid to the lender when the position is opened
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER, "uusd")
+            .unwrap()
+            .amount,
+        premium.amount
+    );
+
+    // 100 UST / 250 UST per LUNA = 0.4 LUNA
+    assert_eq!(
+        query_position(&app, &market_contract, 1),
+        Position {
+            id: 1,
+            owner: Addr::unchecked(ADDR_REGULAR_USER),
+            strike: Decimal::from_ratio(250u128, 1u128),
+            lent_amount: Uint128::new(100_000_000),
+            collateral_amount: Uint128::new(400_000),
+            premium: premium.amount,
+            status: PositionStatus::Open,
+            is_claimed: false,
+            created_at: BLOCK_HEIGHT_LP_ENDS_AT + 1,
+        }
+    );
+
+    assert_eq!(
+        query_locked_assets(&app, &market_contract),
+        [
+            create_coin_asset(0, "uluna"),
+            create_coin_asset(100_000_000, "uusd"),
+        ]
+    );
+
+    let response: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pool {},
+        )
+        .unwrap();
+
+    // the lent asset is locked, only the premium is paid out of the pool reserves
+    assert_eq!(
+        response.assets[1],
+        create_coin_asset(500_000_000 - premium.amount.u128(), "uusd")
+    );
+}
+
+#[test]
+fn anyone_can_settle_in_the_money_puts_in_batches() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        oracle_contract,
+    ) = setup_market_with_oracle(&mut app);
+
+    for lender in [ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, ADDR_REGULAR_USER] {
+        try_to_lend(&mut app, &market_contract, lender).unwrap();
+    }
+
+    set_luna_price(&mut app, &oracle_contract, 200);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response = try_to_settle_puts(&mut app, &market_contract, Some(2));
+
+    assert!(
+        response.is_ok(),
+        "Anyone can settle puts in the settlement phase"
+    );
+
+    assert_eq!(
+        query_settlement_progress(&app, &market_contract),
+        SettlementProgressResponse {
+            last_position_id: Some(2),
+            positions_count: 3,
+            exercised_count: 2,
+            expired_count: 0,
+            is_complete: false,
+        }
+    );
+
+    try_to_settle_puts(&mut app, &market_contract, Some(2)).unwrap();
+
+    let settlement_progress = query_settlement_progress(&app, &market_contract);
+
+    assert_eq!(
+        settlement_progress,
+        SettlementProgressResponse {
+            last_position_id: Some(3),
+            positions_count: 3,
+            exercised_count: 3,
+            expired_count: 0,
+            is_complete: true,
+        }
+    );
+
+    assert!(
+        try_to_settle_puts(&mut app, &market_contract, None).is_ok(),
+        "Settling puts again is a no-op"
+    );
+
+    assert_eq!(
+        query_settlement_progress(&app, &market_contract),
+        settlement_progress
+    );
+
+    assert_eq!(
+        query_position(&app, &market_contract, 3).status,
+        PositionStatus::Exercised
+    );
+
+    // 3 * 0.4 LUNA were delivered for 3 * 100 UST
+    assert_eq!(
+        query_locked_assets(&app, &market_contract),
+        [
+            create_coin_asset(1_200_000, "uluna"),
+            create_coin_asset(0, "uusd"),
+        ]
+    );
+}
+
+#[test]
+fn out_of_the_money_puts_expire() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        oracle_contract,
+    ) = setup_market_with_oracle(&mut app);
+
+    try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER).unwrap();
+
+    set_luna_price(&mut app, &oracle_contract, 300);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    try_to_settle_puts(&mut app, &market_contract, None).unwrap();
+
+    assert_eq!(
+        query_position(&app, &market_contract, 1).status,
+        PositionStatus::Expired
+    );
+
+    assert_eq!(
+        query_locked_assets(&app, &market_contract),
+        [
+            create_coin_asset(0, "uluna"),
+            create_coin_asset(100_000_000, "uusd"),
+        ]
+    );
+}
+
+#[test]
+fn puts_cannot_be_settled_during_amm_phase() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        _,
+    ) = setup_market_with_oracle(&mut app);
+
+    try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER).unwrap();
+
+    let response = try_to_settle_puts(&mut app, &market_contract, None);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+}
+
+#[test]
+fn lender_can_claim_their_settled_position_once() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        oracle_contract,
+    ) = setup_market_with_oracle(&mut app);
+
+    try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER).unwrap();
+    try_to_lend(&mut app, &market_contract, ADDR_REGULAR_USER_2).unwrap();
+
+    set_luna_price(&mut app, &oracle_contract, 200);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    try_to_settle_puts(&mut app, &market_contract, Some(1)).unwrap();
+
+    assert!(
+        try_to_claim_collateral(&mut app, &market_contract, ADDR_REGULAR_USER, 1).is_err(),
+        "Positions cannot be claimed before the settlement phase is over"
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    let response = try_to_claim_collateral(&mut app, &market_contract, ADDR_REGULAR_USER_2, 1);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_claim_collateral(&mut app, &market_contract, ADDR_REGULAR_USER, 1);
+
+    assert!(
+        response.is_ok(),
+        "Lender can claim the collateral of an exercised put"
+    );
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER, "uluna")
+            .unwrap()
+            .amount,
+        Uint128::new(400_000)
+    );
+
+    let response = try_to_claim_collateral(&mut app, &market_contract, ADDR_REGULAR_USER, 1);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::PositionAlreadyClaimed { position_id: 1 }.to_string()
+    );
+
+    // the second position was not reached by the settlement and gets settled on claim
+    try_to_claim_collateral(&mut app, &market_contract, ADDR_REGULAR_USER_2, 2).unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER_2, "uluna")
+            .unwrap()
+            .amount,
+        Uint128::new(400_000)
+    );
+
+    assert_eq!(
+        query_locked_assets(&app, &market_contract),
+        [create_coin_asset(0, "uluna"), create_coin_asset(0, "uusd"),]
+    );
+}
+
+fn asset_infos() -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ]
+}
+
+/// Creates a market in the AMM phase with an oracle pricing LUNA at 250 UST and a deposit of
+/// 2 LUNA & 500 UST.
+fn setup_market_with_oracle(
+    app: &mut App<Empty, TerraQueryWrapper>,
+) -> (MarketSetup, OracleContract) {
+    let oracle_contract = test_env::create_oracle(app);
+
+    set_luna_price(app, &oracle_contract, 250);
+
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos(), |msg| {
+        msg.oracle = Some(oracle_contract.addr().to_string());
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(2_000_000, "uluna"),
+            create_coin_asset(500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    (market_setup, oracle_contract)
+}
+
+fn set_luna_price(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    oracle_contract: &OracleContract,
+    price: u128,
+) {
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        app,
+        oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(price, 1u128),
+    )
+    .unwrap();
+}
+
+fn try_to_lend(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_lender: &str,
+) -> anyhow::Result<AppResponse> {
+    test_env::try_to_lend(
+        app,
+        market_contract,
+        addr_lender,
+        create_coin_asset(100_000_000, "uusd"),
+    )
+}
+
+fn try_to_settle_puts(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    limit: Option<u32>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::SettlePuts { limit },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_claim_collateral(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_lender: &str,
+    position_id: u64,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(addr_lender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::ClaimCollateral { position_id },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_position(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    position_id: u64,
+) -> Position {
+    let PositionResponse { position } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetPosition { position_id },
+        )
+        .unwrap();
+
+    position
+}
+
+fn query_settlement_progress(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> SettlementProgressResponse {
+    app.wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetSettlementProgress {},
+        )
+        .unwrap()
+}
+
+fn query_locked_assets(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> [cw_zll_std_liquidity_pool::ap::Asset; 2] {
+    let LockedAssetsResponse { locked_assets } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLockedAssets {},
+        )
+        .unwrap();
+
+    locked_assets
+}
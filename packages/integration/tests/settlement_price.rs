@@ -0,0 +1,397 @@
+use cosmwasm_std::{coins, Addr, BankMsg, Decimal, Empty};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_REGULAR_USER, BLOCK_HEIGHT_AMM_ENDS_AT,
+        BLOCK_HEIGHT_SETTLEMENT_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::{LiquidityPoolContract, MarketContract, OracleContract},
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::AssetInfo, asset::create_coin_asset, response::NavPerShareResponse,
+};
+use cw_zll_std_market::{
+    response::{CollateralPriceResponse, PriceSource, SettlementPriceResponse},
+    state::SettlementPrice,
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn anyone_can_capture_settlement_price_once_amm_phase_is_over() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        _,
+    ) = setup_market_with_oracle(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT;
+    });
+
+    assert!(
+        try_to_capture_settlement_price(&mut app, &market_contract).is_err(),
+        "Settlement price cannot be captured before the settlement phase"
+    );
+
+    assert_eq!(query_settlement_price(&app, &market_contract), None);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    let response = try_to_capture_settlement_price(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Anyone can capture the settlement price in the settlement phase"
+    );
+
+    assert_eq!(
+        query_settlement_price(&app, &market_contract),
+        Some(SettlementPrice {
+            price: Decimal::from_ratio(250u128, 1u128),
+            source: PriceSource::Oracle,
+            publish_time: Some(app.block_info().time.seconds()),
+            confidence: Some(Decimal::zero()),
+            observed_at: app.block_info().time.seconds(),
+            captured_at: BLOCK_HEIGHT_AMM_ENDS_AT + 1,
+        })
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    assert!(
+        try_to_capture_settlement_price(&mut app, &market_contract).is_err(),
+        "Settlement price can only be captured once"
+    );
+}
+
+#[test]
+fn settlement_price_is_captured_in_post_settlement_if_settlement_passed_by() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract, ..
+        },
+        _,
+    ) = setup_market_with_oracle(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1;
+    });
+
+    try_to_capture_settlement_price(&mut app, &market_contract).unwrap();
+
+    assert_eq!(
+        query_settlement_price(&app, &market_contract).map(|price| price.captured_at),
+        Some(BLOCK_HEIGHT_SETTLEMENT_ENDS_AT + 1)
+    );
+}
+
+#[test]
+fn collateral_price_stays_at_settlement_price_once_captured() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract,
+            liquidity_pool_contract,
+        },
+        oracle_contract,
+    ) = setup_market_with_oracle(&mut app);
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    try_to_capture_settlement_price(&mut app, &market_contract).unwrap();
+
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        &mut app,
+        &oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(100u128, 1u128),
+    )
+    .unwrap();
+
+    let response: CollateralPriceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCollateralPrice {},
+        )
+        .unwrap();
+
+    assert_eq!(response.price, Decimal::from_ratio(250u128, 1u128));
+    assert_eq!(response.source, PriceSource::Settlement);
+
+    let response: NavPerShareResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::NavPerShare {},
+        )
+        .unwrap();
+
+    // 2 LUNA * 250 UST + 500 UST = 1000 UST
+    assert_eq!(response.nav, create_coin_asset(1_000_000_000, "uusd"));
+}
+
+#[test]
+fn settlement_price_is_the_oracle_price_observed_by_the_end_of_the_amm_phase() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract,
+            liquidity_pool_contract,
+        },
+        oracle_contract,
+    ) = setup_market_with_oracle_and(&mut app, |msg| {
+        msg.max_price_deviation = Some(Decimal::percent(5));
+    });
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT;
+    });
+
+    let amm_observed_at = app.block_info().time.seconds();
+    check_minimum_liquidity(&mut app, &market_contract).unwrap();
+
+    advance_time(&mut app, 60);
+
+    // neither the oracle price nor the pool price after the end of the AMM phase count
+    donate_to_pool(&mut app, &liquidity_pool_contract, 2_000_000);
+
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        &mut app,
+        &oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(100u128, 1u128),
+    )
+    .unwrap();
+
+    let response = try_to_capture_settlement_price(&mut app, &market_contract);
+
+    assert!(
+        response.is_ok(),
+        "Settlement price is captured from the oracle price observed during the AMM phase"
+    );
+
+    assert_eq!(
+        query_settlement_price(&app, &market_contract),
+        Some(SettlementPrice {
+            price: Decimal::from_ratio(250u128, 1u128),
+            source: PriceSource::Oracle,
+            publish_time: Some(amm_observed_at),
+            confidence: Some(Decimal::zero()),
+            observed_at: amm_observed_at,
+            captured_at: app.block_info().height,
+        })
+    );
+}
+
+#[test]
+fn settlement_price_falls_back_to_the_twap_until_the_end_of_the_amm_phase() {
+    let mut app = test_env::mock_app();
+
+    let (
+        MarketSetup {
+            market_contract,
+            liquidity_pool_contract,
+        },
+        oracle_contract,
+    ) = setup_market_with_oracle_and(&mut app, |msg| {
+        msg.twap_window = Some(100);
+        msg.max_price_age = Some(50);
+    });
+
+    // the oracle price published when the market was created is stale by the end of the AMM phase
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT;
+        block.time = block.time.plus_seconds(100);
+    });
+
+    let amm_observed_at = app.block_info().time.seconds();
+    check_minimum_liquidity(&mut app, &market_contract).unwrap();
+
+    advance_time(&mut app, 100);
+
+    // a donation recorded after the end of the AMM phase halves the pool price
+    donate_to_pool(&mut app, &liquidity_pool_contract, 2_000_000);
+
+    advance_time(&mut app, 100);
+
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        &mut app,
+        &oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(100u128, 1u128),
+    )
+    .unwrap();
+
+    try_to_capture_settlement_price(&mut app, &market_contract).unwrap();
+
+    assert_eq!(
+        query_settlement_price(&app, &market_contract),
+        Some(SettlementPrice {
+            price: Decimal::from_ratio(250u128, 1u128),
+            source: PriceSource::Twap,
+            publish_time: None,
+            confidence: None,
+            observed_at: amm_observed_at,
+            captured_at: app.block_info().height,
+        })
+    );
+}
+
+fn asset_infos() -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ]
+}
+
+fn setup_market_with_oracle(
+    app: &mut App<Empty, TerraQueryWrapper>,
+) -> (MarketSetup, OracleContract) {
+    setup_market_with_oracle_and(app, |_| {})
+}
+
+/// Creates a market with an oracle pricing LUNA at 250 UST and a deposit of 2 LUNA & 500 UST.
+fn setup_market_with_oracle_and(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    customize_msg: impl FnOnce(&mut cw_zll_std_market::msg::InstantiateMsg),
+) -> (MarketSetup, OracleContract) {
+    let oracle_contract = test_env::create_oracle(app);
+    let [luna, ust] = asset_infos();
+
+    test_env::set_oracle_price(
+        app,
+        &oracle_contract,
+        luna,
+        ust,
+        Decimal::from_ratio(250u128, 1u128),
+    )
+    .unwrap();
+
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos(), |msg| {
+        msg.oracle = Some(oracle_contract.addr().to_string());
+        customize_msg(msg);
+    });
+
+    test_env::try_to_deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        [
+            create_coin_asset(2_000_000, "uluna"),
+            create_coin_asset(500_000_000, "uusd"),
+        ],
+    )
+    .unwrap();
+
+    (market_setup, oracle_contract)
+}
+
+fn try_to_capture_settlement_price(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::CaptureSettlementPrice {},
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+fn query_settlement_price(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> Option<SettlementPrice> {
+    let response: SettlementPriceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetSettlementPrice {},
+        )
+        .unwrap();
+
+    response.settlement_price
+}
+
+fn check_minimum_liquidity(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::CheckMinimumLiquidity {},
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+/// Sends LUNA to the pool and records its reserves, which halves the pool price for 2 LUNA.
+fn donate_to_pool(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    uluna: u128,
+) {
+    app.init_bank_balance(&Addr::unchecked(ADDR_REGULAR_USER), coins(uluna, "uluna"))
+        .unwrap();
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        BankMsg::Send {
+            to_address: liquidity_pool_contract.addr().to_string(),
+            amount: coins(uluna, "uluna"),
+        }
+        .into(),
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        liquidity_pool_contract
+            .call(
+                &cw_zll_std_liquidity_pool::msg::ExecuteMsg::SyncReserves {},
+                None,
+            )
+            .unwrap(),
+    )
+    .unwrap();
+}
+
+fn advance_time(app: &mut App<Empty, TerraQueryWrapper>, seconds: u64) {
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(seconds);
+    });
+}
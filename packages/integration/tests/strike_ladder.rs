@@ -0,0 +1,354 @@
+use cosmwasm_std::{coin, Addr, Decimal, Empty, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use cw_zll_market::ContractError;
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_MARKET_OPERATOR, ADDR_REGULAR_USER, ADDR_REGULAR_USER_2, BLOCK_HEIGHT_AMM_ENDS_AT,
+        BLOCK_HEIGHT_LP_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::MarketContract,
+    test_env,
+};
+use cw_zll_std_liquidity_pool::asset::create_coin_asset;
+use cw_zll_std_market::{
+    borrow::BorrowingTermsResponse,
+    msg::StrikeInfo,
+    response::{LoanResponse, StrikesResponse},
+    state::Strike,
+};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn market_operator_can_set_strikes_during_lp_phase() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = test_env::setup_market_with_liquidity(&mut app, |_| {});
+
+    let response = try_to_set_strikes(&mut app, &market_contract, ADDR_REGULAR_USER, strikes());
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string()
+    );
+
+    let response = try_to_set_strikes(&mut app, &market_contract, ADDR_MARKET_OPERATOR, strikes());
+
+    assert!(
+        response.is_ok(),
+        "Market operator is able to set the strikes during the LP phase"
+    );
+
+    assert_eq!(
+        query_strikes(&app, &market_contract),
+        vec![
+            Strike {
+                id: 1,
+                price: Decimal::from_ratio(200u128, 1u128),
+                capacity: Some(Uint128::new(300_000_000)),
+                borrowed: Uint128::zero(),
+            },
+            Strike {
+                id: 2,
+                price: Decimal::from_ratio(150u128, 1u128),
+                capacity: None,
+                borrowed: Uint128::zero(),
+            },
+            Strike {
+                id: 3,
+                price: Decimal::from_ratio(300u128, 1u128),
+                capacity: None,
+                borrowed: Uint128::zero(),
+            },
+        ]
+    );
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    let response = try_to_set_strikes(&mut app, &market_contract, ADDR_MARKET_OPERATOR, vec![]);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::Unauthorized {}.to_string(),
+        "Strikes cannot be changed once the AMM phase has started"
+    );
+}
+
+#[test]
+fn borrower_can_borrow_at_a_selected_strike() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_in_amm_phase_with_strikes(&mut app);
+
+    let BorrowingTermsResponse {
+        borrow,
+        interest,
+        strike,
+        strike_id,
+        ..
+    } = test_env::query_borrowing_terms(&app, &market_contract, 1_000_000, Some(1), None);
+
+    // 1 LUNA * 200 UST per LUNA
+    assert_eq!(borrow, create_coin_asset(200_000_000, "uusd"));
+    assert_eq!(strike, Decimal::from_ratio(200u128, 1u128));
+    assert_eq!(strike_id, Some(1));
+
+    let response = try_to_borrow_at_strike(&mut app, &market_contract, ADDR_REGULAR_USER, 1);
+
+    assert!(
+        response.is_ok(),
+        "Borrower is able to borrow at a strike of the strike ladder"
+    );
+
+    let LoanResponse { loan } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetLoan { loan_id: 1 },
+        )
+        .unwrap();
+
+    assert_eq!(loan.strike, Decimal::from_ratio(200u128, 1u128));
+    assert_eq!(loan.strike_id, Some(1));
+    assert_eq!(loan.notional, Uint128::new(200_000_000));
+
+    assert_eq!(
+        app.wrap()
+            .query_balance(ADDR_REGULAR_USER, "uusd")
+            .unwrap()
+            .amount,
+        borrow.amount - interest.amount
+    );
+
+    assert_eq!(
+        query_strikes(&app, &market_contract)[0].borrowed,
+        Uint128::new(200_000_000)
+    );
+
+    // the pool would pay ~238 UST for 1 LUNA
+    let response = try_to_borrow_at_strike(&mut app, &market_contract, ADDR_REGULAR_USER, 3);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::StrikeAboveMarket {
+            strike_id: 3,
+            strike: Decimal::from_ratio(300u128, 1u128),
+        }
+        .to_string()
+    );
+
+    let response = try_to_borrow_at_strike(&mut app, &market_contract, ADDR_REGULAR_USER, 4);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::StrikeNotFound { strike_id: 4 }.to_string()
+    );
+}
+
+#[test]
+fn strikes_are_priced_at_their_moneyness() {
+    let mut app = test_env::mock_app();
+
+    // the options expire in a quarter of a year once the AMM phase starts
+    let market_contract = test_env::setup_market_with_liquidity(&mut app, |msg| {
+        msg.blocks_per_year = (BLOCK_HEIGHT_AMM_ENDS_AT - BLOCK_HEIGHT_LP_ENDS_AT - 1) * 4;
+    });
+
+    try_to_set_strikes(&mut app, &market_contract, ADDR_MARKET_OPERATOR, strikes()).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    // a standard deviation of 250 UST * 20% * sqrt(0.25) = 25 UST
+    let at_the_money =
+        test_env::query_borrowing_terms(&app, &market_contract, 1_000_000, None, None).interest;
+    // 25 UST / sqrt(2 * pi)
+    assert_eq!(at_the_money, create_coin_asset(9_973_557, "uusd"));
+
+    // 2 standard deviations out of the money
+    let strike_200 =
+        test_env::query_borrowing_terms(&app, &market_contract, 1_000_000, Some(1), None).interest;
+    assert_eq!(strike_200, create_coin_asset(212_267, "uusd"));
+
+    // 4 standard deviations out of the money
+    let strike_150 =
+        test_env::query_borrowing_terms(&app, &market_contract, 1_000_000, Some(2), None).interest;
+    assert_eq!(strike_150, create_coin_asset(178, "uusd"));
+}
+
+#[test]
+fn borrowing_is_limited_by_the_strike_capacity() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_in_amm_phase_with_strikes(&mut app);
+
+    try_to_borrow_at_strike(&mut app, &market_contract, ADDR_REGULAR_USER, 1).unwrap();
+
+    let response = try_to_borrow_at_strike(&mut app, &market_contract, ADDR_REGULAR_USER_2, 1);
+
+    assert_eq!(
+        response.unwrap_err().root_cause().to_string(),
+        ContractError::StrikeCapacityExceeded {
+            strike_id: 1,
+            borrow: Uint128::new(200_000_000),
+            remaining_capacity: Uint128::new(100_000_000),
+        }
+        .to_string()
+    );
+
+    let response = try_to_borrow_at_strike(&mut app, &market_contract, ADDR_REGULAR_USER_2, 2);
+
+    assert!(
+        response.is_ok(),
+        "Strikes without capacity accept any amount of borrowing"
+    );
+}
+
+#[test]
+fn repaid_loans_release_the_strike_capacity() {
+    let mut app = test_env::mock_app();
+
+    let market_contract = setup_market_in_amm_phase_with_strikes(&mut app);
+
+    try_to_borrow_at_strike(&mut app, &market_contract, ADDR_REGULAR_USER, 1).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_AMM_ENDS_AT + 1;
+    });
+
+    try_to_repay_loan(&mut app, &market_contract, 1, 50_000_000).unwrap();
+
+    assert_eq!(
+        query_strikes(&app, &market_contract)[0].borrowed,
+        Uint128::new(150_000_000)
+    );
+
+    try_to_repay_loan(&mut app, &market_contract, 1, 150_000_000).unwrap();
+
+    assert_eq!(
+        query_strikes(&app, &market_contract)[0].borrowed,
+        Uint128::zero()
+    );
+}
+
+fn strikes() -> Vec<StrikeInfo> {
+    vec![
+        StrikeInfo {
+            price: Decimal::from_ratio(200u128, 1u128),
+            capacity: Some(Uint128::new(300_000_000)),
+        },
+        StrikeInfo {
+            price: Decimal::from_ratio(150u128, 1u128),
+            capacity: None,
+        },
+        StrikeInfo {
+            price: Decimal::from_ratio(300u128, 1u128),
+            capacity: None,
+        },
+    ]
+}
+
+fn setup_market_in_amm_phase_with_strikes(
+    app: &mut App<Empty, TerraQueryWrapper>,
+) -> MarketContract {
+    let market_contract = test_env::setup_market_with_liquidity(app, |_| {});
+
+    try_to_set_strikes(app, &market_contract, ADDR_MARKET_OPERATOR, strikes()).unwrap();
+
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    market_contract
+}
+
+fn try_to_set_strikes(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    sender: &str,
+    strikes: Vec<StrikeInfo>,
+) -> anyhow::Result<AppResponse> {
+    app.execute(
+        Addr::unchecked(sender),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::SetStrikes { strikes },
+                None,
+            )
+            .unwrap(),
+    )
+}
+
+/// Borrows against 1 LUNA at the given strike.
+fn try_to_borrow_at_strike(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    addr_borrower: &str,
+    strike_id: u64,
+) -> anyhow::Result<AppResponse> {
+    let _ = app.init_bank_balance(
+        &Addr::unchecked(addr_borrower),
+        vec![coin(1_000_000, "uluna")],
+    );
+
+    app.execute(
+        Addr::unchecked(addr_borrower),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::Borrow {
+                    expected_borrow: create_coin_asset(0, "uusd"),
+                    pledged_collateral: create_coin_asset(1_000_000, "uluna"),
+                    strike_id: Some(strike_id),
+                },
+                Some(vec![coin(1_000_000, "uluna")]),
+            )
+            .unwrap(),
+    )
+}
+
+fn try_to_repay_loan(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+    loan_id: u64,
+    uusd: u128,
+) -> anyhow::Result<AppResponse> {
+    let uluna = app
+        .wrap()
+        .query_balance(ADDR_REGULAR_USER, "uluna")
+        .unwrap();
+    let _ = app.init_bank_balance(
+        &Addr::unchecked(ADDR_REGULAR_USER),
+        vec![uluna, coin(uusd, "uusd")],
+    );
+
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        market_contract
+            .call(
+                &cw_zll_std_market::msg::ExecuteMsg::RepayLoan {
+                    loan_id,
+                    repayment: create_coin_asset(uusd, "uusd"),
+                },
+                Some(vec![coin(uusd, "uusd")]),
+            )
+            .unwrap(),
+    )
+}
+
+fn query_strikes(
+    app: &App<Empty, TerraQueryWrapper>,
+    market_contract: &MarketContract,
+) -> Vec<Strike> {
+    let StrikesResponse { strikes } = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetStrikes {},
+        )
+        .unwrap();
+
+    strikes
+}
@@ -0,0 +1,325 @@
+use cosmwasm_std::{coins, Addr, BankMsg, Decimal, Empty, Uint128};
+use cw_multi_test::{App, Executor};
+use cw_zll_std_integration::{
+    consts::{
+        ADDR_LIQUIDITY_PROVIDER, ADDR_LIQUIDITY_PROVIDER_2, ADDR_REGULAR_USER,
+        BLOCK_HEIGHT_LP_ENDS_AT,
+    },
+    contract_helpers::ContractBase,
+    contract_mocks::LiquidityPoolContract,
+    test_env::{self, MarketSetup},
+};
+use cw_zll_std_liquidity_pool::{
+    ap::{pair::CumulativePricesResponse, AssetInfo, PoolResponse},
+    asset::create_coin_asset,
+    response::TwapResponse,
+};
+use cw_zll_std_market::response::{CollateralPriceResponse, PriceSource};
+use terra_cosmwasm::TerraQueryWrapper;
+
+#[test]
+fn pool_accumulates_prices_over_time() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = test_env::create_martket_setup(&mut app, asset_infos());
+
+    deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        2_000_000,
+        500_000_000,
+    );
+
+    advance_time(&mut app, 100);
+
+    let response: CumulativePricesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::CumulativePrices {},
+        )
+        .unwrap();
+
+    // 100 seconds at 250 UST per LUNA and 0.004 LUNA per UST, scaled by 10^6
+    assert_eq!(
+        response.price0_cumulative_last,
+        Uint128::new(25_000_000_000)
+    );
+    assert_eq!(response.price1_cumulative_last, Uint128::new(400_000));
+}
+
+#[test]
+fn anyone_can_check_twap_over_a_window() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_with_price_history(&mut app, |_| {});
+
+    // 100 seconds at 250 UST per LUNA, then 100 seconds at 500 UST per LUNA
+    assert_eq!(
+        query_twap(&app, &liquidity_pool_contract, 200).unwrap(),
+        TwapResponse {
+            price0_average: Decimal::from_ratio(375u128, 1u128),
+            price1_average: Decimal::from_ratio(3u128, 1_000u128),
+            window: 200,
+        }
+    );
+
+    assert_eq!(
+        query_twap(&app, &liquidity_pool_contract, 100).unwrap(),
+        TwapResponse {
+            price0_average: Decimal::from_ratio(500u128, 1u128),
+            price1_average: Decimal::from_ratio(2u128, 1_000u128),
+            window: 100,
+        }
+    );
+
+    // The average starts at the latest observation made at least 150 seconds ago
+    assert_eq!(
+        query_twap(&app, &liquidity_pool_contract, 150)
+            .unwrap()
+            .window,
+        200
+    );
+
+    assert!(
+        query_twap(&app, &liquidity_pool_contract, 300).is_err(),
+        "TWAP cannot be calculated over a window longer than the price history"
+    );
+}
+
+#[test]
+fn twap_can_be_averaged_until_a_past_time() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        liquidity_pool_contract,
+        ..
+    } = setup_market_with_price_history(&mut app, |_| {});
+
+    let now = app.block_info().time.seconds();
+
+    // 100 seconds at 250 UST per LUNA until the second deposit
+    assert_eq!(
+        query_twap_until(&app, &liquidity_pool_contract, 100, now - 100).unwrap(),
+        TwapResponse {
+            price0_average: Decimal::from_ratio(250u128, 1u128),
+            price1_average: Decimal::from_ratio(4u128, 1_000u128),
+            window: 100,
+        }
+    );
+
+    // 50 seconds at 500 UST per LUNA after the second deposit
+    assert_eq!(
+        query_twap_until(&app, &liquidity_pool_contract, 50, now - 50).unwrap(),
+        TwapResponse {
+            price0_average: Decimal::from_ratio(500u128, 1u128),
+            price1_average: Decimal::from_ratio(2u128, 1_000u128),
+            window: 50,
+        }
+    );
+
+    assert!(
+        query_twap_until(&app, &liquidity_pool_contract, 100, now + 1).is_err(),
+        "TWAP cannot be averaged until a time in the future"
+    );
+}
+
+#[test]
+fn twap_weights_each_interval_by_the_reserves_recorded_before_it() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract,
+        liquidity_pool_contract,
+    } = test_env::create_martket_setup(&mut app, asset_infos());
+
+    deposit_liquidity(
+        &mut app,
+        &liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        2_000_000,
+        500_000_000,
+    );
+
+    advance_time(&mut app, 100);
+    app.update_block(|block| {
+        block.height = BLOCK_HEIGHT_LP_ENDS_AT + 1;
+    });
+
+    test_env::try_to_borrow(
+        &mut app,
+        &market_contract,
+        ADDR_REGULAR_USER,
+        create_coin_asset(0, "uusd"),
+        create_coin_asset(1_000_000, "uluna"),
+    )
+    .unwrap();
+
+    let PoolResponse { assets, .. } = app
+        .wrap()
+        .query_wasm_smart(
+            liquidity_pool_contract.addr(),
+            &cw_zll_std_liquidity_pool::msg::QueryMsg::Pool {},
+        )
+        .unwrap();
+
+    // a donation changes nothing until the reserves are recorded again
+    app.init_bank_balance(
+        &Addr::unchecked(ADDR_REGULAR_USER),
+        coins(1_000_000_000, "uusd"),
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked(ADDR_REGULAR_USER),
+        BankMsg::Send {
+            to_address: liquidity_pool_contract.addr().to_string(),
+            amount: coins(1_000_000_000, "uusd"),
+        }
+        .into(),
+    )
+    .unwrap();
+
+    advance_time(&mut app, 100);
+
+    // 100 seconds at 250 UST per LUNA, then 100 seconds at the price after the borrowing
+    let price0_cumulative = Uint128::new(25_000_000_000)
+        + Uint128::new(100_000_000).multiply_ratio(assets[1].amount, assets[0].amount);
+
+    assert_eq!(
+        query_twap(&app, &liquidity_pool_contract, 200)
+            .unwrap()
+            .price0_average,
+        Decimal::from_ratio(price0_cumulative, 200_000_000u128)
+    );
+}
+
+#[test]
+fn market_uses_pool_twap_as_collateral_price_without_oracle() {
+    let mut app = test_env::mock_app();
+
+    let MarketSetup {
+        market_contract, ..
+    } = setup_market_with_price_history(&mut app, |msg| {
+        msg.collateral_price = Some(Decimal::from_ratio(250u128, 1u128));
+        msg.twap_window = Some(200);
+    });
+
+    let response: CollateralPriceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            market_contract.addr(),
+            &cw_zll_std_market::msg::QueryMsg::GetCollateralPrice {},
+        )
+        .unwrap();
+
+    assert_eq!(
+        response,
+        CollateralPriceResponse {
+            price: Decimal::from_ratio(375u128, 1u128),
+            source: PriceSource::Twap,
+            publish_time: None,
+            confidence: None,
+        }
+    );
+}
+
+fn asset_infos() -> [AssetInfo; 2] {
+    [
+        AssetInfo::NativeToken {
+            denom: "uluna".into(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".into(),
+        },
+    ]
+}
+
+/// Creates a market whose pool prices LUNA at 250 UST for 100 seconds and at 500 UST
+/// for the following 100 seconds.
+fn setup_market_with_price_history(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    customize_msg: impl FnOnce(&mut cw_zll_std_market::msg::InstantiateMsg),
+) -> MarketSetup {
+    let market_setup = test_env::create_martket_setup_with(app, asset_infos(), customize_msg);
+
+    deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER,
+        2_000_000,
+        500_000_000,
+    );
+
+    advance_time(app, 100);
+
+    deposit_liquidity(
+        app,
+        &market_setup.liquidity_pool_contract,
+        ADDR_LIQUIDITY_PROVIDER_2,
+        2_000_000,
+        1_500_000_000,
+    );
+
+    advance_time(app, 100);
+
+    market_setup
+}
+
+fn deposit_liquidity(
+    app: &mut App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    addr_liquidity_provider: &str,
+    uluna: u128,
+    uusd: u128,
+) {
+    test_env::try_to_deposit_liquidity(
+        app,
+        liquidity_pool_contract,
+        addr_liquidity_provider,
+        [
+            create_coin_asset(uluna, "uluna"),
+            create_coin_asset(uusd, "uusd"),
+        ],
+    )
+    .unwrap();
+}
+
+fn advance_time(app: &mut App<Empty, TerraQueryWrapper>, seconds: u64) {
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(seconds);
+    });
+}
+
+fn query_twap(
+    app: &App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    window: u64,
+) -> cosmwasm_std::StdResult<TwapResponse> {
+    app.wrap().query_wasm_smart(
+        liquidity_pool_contract.addr(),
+        &cw_zll_std_liquidity_pool::msg::QueryMsg::Twap { window, end: None },
+    )
+}
+
+fn query_twap_until(
+    app: &App<Empty, TerraQueryWrapper>,
+    liquidity_pool_contract: &LiquidityPoolContract,
+    window: u64,
+    end: u64,
+) -> cosmwasm_std::StdResult<TwapResponse> {
+    app.wrap().query_wasm_smart(
+        liquidity_pool_contract.addr(),
+        &cw_zll_std_liquidity_pool::msg::QueryMsg::Twap {
+            window,
+            end: Some(end),
+        },
+    )
+}
@@ -2,3 +2,4 @@ pub mod ap;
 pub mod asset;
 pub mod liquidity;
 pub mod msg;
+pub mod response;
@@ -1,4 +1,117 @@
-pub use astroport::{
-    pair::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg},
-    token::InstantiateMsg as TokenInstantiateMsg,
-};
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::ap::{Asset, AssetInfo};
+
+pub use astroport::{pair::InstantiateMsg, token::InstantiateMsg as TokenInstantiateMsg};
+
+/// ## Description
+/// This structure describes the execute messages available in the liquidity pool contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// ProvideLiquidity allows someone to provide liquidity in the pool
+    ProvideLiquidity {
+        /// The assets available in the pool
+        assets: [Asset; 2],
+        /// The slippage tolerance that allows liquidity provision only if the price in the pool doesn't move too much
+        slippage_tolerance: Option<Decimal>,
+        /// Determines whether the LP tokens minted for the user is auto_staked in the Generator contract
+        auto_stake: Option<bool>,
+        /// The receiver of LP tokens
+        receiver: Option<String>,
+        /// The minimum amount of LP tokens the provider accepts to receive for the deposit
+        min_share_out: Option<Uint128>,
+    },
+    /// Transfers assets held by the pool to the recipient. Only the market can transfer assets.
+    TransferAssets {
+        /// The assets to transfer
+        assets: Vec<Asset>,
+        /// The receiver of the assets
+        recipient: String,
+    },
+    /// Returns the LP tokens the sender escrowed for the rollover. This is only possible until
+    /// the escrowed liquidity is rolled over.
+    OptOutRollover {},
+    /// Moves the liquidity of the escrowed LP tokens into the pool of the successor market.
+    /// Only the market can roll the liquidity over.
+    RolloverLiquidity {
+        /// The liquidity pool of the successor market
+        liquidity_pool: String,
+    },
+    /// Sends the sender their share of the LP tokens received from the successor pool
+    ClaimRolloverShares {},
+    /// Accumulates the prices for the reserves recorded at the last update, then records the
+    /// current reserves. The pool and the market send it after every change of the reserves.
+    SyncReserves {},
+}
+
+/// ## Description
+/// This structure describes the messages that can be attached to LP tokens sent to the pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Withdraws liquidity from the pool by burning the received LP tokens
+    WithdrawLiquidity {
+        /// The receiver of the withdrawn assets, defaults to the sender of the LP tokens
+        recipient: Option<String>,
+        /// The minimum amounts of the pool assets to receive
+        min_assets: Option<Vec<Asset>>,
+        /// The single asset to receive, the rest of the refund is swapped into it by the pool.
+        /// This is only available in the post-settlement phase of the market.
+        output_asset: Option<AssetInfo>,
+    },
+    /// Escrows the received LP tokens, so their liquidity is moved into the pool of the successor
+    /// market once the market is settled or cancelled. LP tokens can be escrowed until then.
+    OptInRollover {},
+}
+
+/// ## Description
+/// This structure describes the query messages available in the liquidity pool contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns information about the pair in an object of type [`PairInfo`](crate::ap::PairInfo).
+    Pair {},
+    /// Returns information about the pool in an object of type [`PoolResponse`](crate::ap::PoolResponse).
+    Pool {},
+    /// Returns the lending asset liquidity collected so far and the market's target liquidity
+    /// in an object of type [`LiquidityProgressResponse`](crate::response::LiquidityProgressResponse).
+    LiquidityProgress {},
+    /// Returns the net asset value of the pool per LP token, valued in the lending asset, in an
+    /// object of type [`NavPerShareResponse`](crate::response::NavPerShareResponse).
+    NavPerShare {},
+    /// Returns the holder's share of each reserve and its value in an object of type
+    /// [`HolderShareResponse`](crate::response::HolderShareResponse).
+    HolderShare { address: String },
+    /// Returns the premium earned by the pool since the market started in an object of type
+    /// [`PremiumEarnedResponse`](crate::response::PremiumEarnedResponse).
+    PremiumEarned {},
+    /// Returns the cumulative prices of the pool assets in an object of type
+    /// [`CumulativePricesResponse`](crate::ap::pair::CumulativePricesResponse).
+    CumulativePrices {},
+    /// Returns the time-weighted average prices of the pool assets over the `window` seconds
+    /// before `end`, which defaults to the current block time, in an object of type
+    /// [`TwapResponse`](crate::response::TwapResponse).
+    Twap { window: u64, end: Option<u64> },
+    /// Returns the LP tokens the holder escrowed for the rollover and the LP tokens of the
+    /// successor pool they can claim in an object of type
+    /// [`RolloverSharesResponse`](crate::response::RolloverSharesResponse).
+    RolloverShares { address: String },
+}
+
+/// ## Description
+/// This structure describes the migrate message of the liquidity pool contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
+
+/// ## Description
+/// This structure describes the migrate message of the LP token contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenMigrateMsg {}
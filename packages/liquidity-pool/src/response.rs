@@ -0,0 +1,88 @@
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::ap::Asset;
+
+/// ## Description
+/// This structure describes the progress of the pool towards the market's target liquidity.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LiquidityProgressResponse {
+    /// The amount of the lending asset currently held by the pool
+    pub liquidity: Asset,
+    /// The target liquidity configured for the market, if any
+    pub target_liquidity: Option<Asset>,
+    /// Whether the liquidity has reached the target
+    pub is_target_reached: bool,
+}
+
+/// ## Description
+/// This structure describes the net asset value (NAV) of the pool valued in the lending asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct NavPerShareResponse {
+    /// The value of all the pool reserves and of the outstanding loans
+    pub nav: Asset,
+    /// The total amount of LP tokens currently minted
+    pub total_share: Uint128,
+    /// The value of a single LP token
+    pub nav_per_share: Decimal,
+    /// The price of one unit of the collateral asset used for the valuation
+    pub collateral_price: Decimal,
+}
+
+/// ## Description
+/// This structure describes the part of the pool owned by a single LP token holder.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HolderShareResponse {
+    /// The amount of LP tokens held
+    pub share: Uint128,
+    /// The holder's share of each reserve
+    pub assets: Vec<Asset>,
+    /// The holder's share of the collateral pledged for and the notional owed by the outstanding
+    /// loans
+    pub loans: Vec<Asset>,
+    /// The value of the holder's share of the reserves and of the outstanding loans
+    pub nav: Asset,
+    /// The holder's share of the premium earned by the pool since the market started
+    pub premium_earned: Asset,
+}
+
+/// ## Description
+/// This structure describes the premium earned by the pool since the market started.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PremiumEarnedResponse {
+    /// The total premium earned by the pool
+    pub premium: Asset,
+    /// The premium earned per LP token
+    pub premium_per_share: Decimal,
+}
+
+/// ## Description
+/// This structure describes the time-weighted average prices (TWAP) of the pool assets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TwapResponse {
+    /// The average price of the first asset in units of the second asset
+    pub price0_average: Decimal,
+    /// The average price of the second asset in units of the first asset
+    pub price1_average: Decimal,
+    /// The number of seconds the prices were averaged over, never less than the requested window
+    pub window: u64,
+}
+
+/// ## Description
+/// This structure describes the LP tokens a holder escrowed for the rollover into the successor market.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RolloverSharesResponse {
+    /// The amount of LP tokens escrowed by the holder
+    pub shares: Uint128,
+    /// The liquidity pool of the successor market, once the liquidity is rolled over
+    pub liquidity_pool: Option<Addr>,
+    /// The amount of LP tokens of the successor pool the holder can claim
+    pub claimable: Uint128,
+}
@@ -1,3 +1,4 @@
+use cosmwasm_std::{Decimal, Uint128};
 use cw_zll_std_liquidity_pool::ap::Asset;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -8,12 +9,28 @@ pub struct BorrowingTermsResponse {
     pub borrow: Asset,
     pub interest: Asset,
     pub repayment: Asset,
+    /// The strike price of the CALL option held by the borrower
+    pub strike: Decimal,
+    /// The selected strike of the strike ladder, if any
+    pub strike_id: Option<u64>,
+    /// The part of the interest taken as the protocol fee
+    pub protocol_fee: Asset,
+    /// The notional which can still be borrowed under the capacity limits of the market
+    pub remaining_capacity: Option<Uint128>,
 }
 
 pub struct BorrowingTerms {
     pub borrow: Asset,
     pub interest: Asset,
     pub repayment: Asset,
+    /// The strike price of the CALL option held by the borrower
+    pub strike: Decimal,
+    /// The selected strike of the strike ladder, if any
+    pub strike_id: Option<u64>,
+    /// The part of the interest taken as the protocol fee
+    pub protocol_fee: Asset,
+    /// The notional which can still be borrowed under the capacity limits of the market
+    pub remaining_capacity: Option<Uint128>,
 }
 
 impl From<BorrowingTerms> for BorrowingTermsResponse {
@@ -22,6 +39,10 @@ impl From<BorrowingTerms> for BorrowingTermsResponse {
             borrow: borrowing_terms.borrow,
             interest: borrowing_terms.interest,
             repayment: borrowing_terms.repayment,
+            strike: borrowing_terms.strike,
+            strike_id: borrowing_terms.strike_id,
+            protocol_fee: borrowing_terms.protocol_fee,
+            remaining_capacity: borrowing_terms.remaining_capacity,
         }
     }
 }
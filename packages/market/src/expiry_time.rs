@@ -1,6 +1,7 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::Decimal;
 
 pub struct ExpiryTime {
-    pub time_to_expiry: Uint128,
-    pub sqrt_time_to_expiry: Uint128,
+    /// The time to the expiry of the options, in years
+    pub time_to_expiry: Decimal,
+    pub sqrt_time_to_expiry: Decimal,
 }
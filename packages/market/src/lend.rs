@@ -0,0 +1,43 @@
+use cosmwasm_std::{Decimal, Uint128};
+use cw_zll_std_liquidity_pool::ap::Asset;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LendingTermsResponse {
+    pub lend: Asset,
+    pub premium: Asset,
+    pub collateral: Asset,
+    /// The strike price of the PUT option written by the lender
+    pub strike: Decimal,
+    /// The part of the option premium taken as the protocol fee, which is not paid to the lender
+    pub protocol_fee: Asset,
+    /// The amount which can still be lent under the capacity limits of the market
+    pub remaining_capacity: Option<Uint128>,
+}
+
+pub struct LendingTerms {
+    pub lend: Asset,
+    pub premium: Asset,
+    pub collateral: Asset,
+    /// The strike price of the PUT option written by the lender
+    pub strike: Decimal,
+    /// The part of the option premium taken as the protocol fee, which is not paid to the lender
+    pub protocol_fee: Asset,
+    /// The amount which can still be lent under the capacity limits of the market
+    pub remaining_capacity: Option<Uint128>,
+}
+
+impl From<LendingTerms> for LendingTermsResponse {
+    fn from(lending_terms: LendingTerms) -> Self {
+        Self {
+            lend: lending_terms.lend,
+            premium: lending_terms.premium,
+            collateral: lending_terms.collateral,
+            strike: lending_terms.strike,
+            protocol_fee: lending_terms.protocol_fee,
+            remaining_capacity: lending_terms.remaining_capacity,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 pub mod borrow;
 pub mod expiry_time;
+pub mod lend;
 pub mod msg;
 pub mod response;
 pub mod state;
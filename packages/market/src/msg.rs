@@ -1,9 +1,11 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw_zll_std_liquidity_pool::ap::{Asset, AssetInfo};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::MarketPhasesInfo;
+use crate::state::{
+    AllowlistRole, AllowlistRoles, CapacityLimits, MarketPhasesInfo, MarketUpdate, PausableAction,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -11,19 +13,202 @@ pub struct InstantiateMsg {
     pub market_operator: Addr,
     pub liquidity_pool_code_id: u64,
     pub liquidity_pool_token_code_id: u64,
+    /// The code id of the option token contract the market issues the CALL options of its loans
+    /// as tokens of; markets without one do not issue option tokens
+    pub option_token_code_id: Option<u64>,
+    /// The collateral asset (first) and the lending asset (second) of the market
     pub asset_infos: [AssetInfo; 2],
     pub market_phases_info: MarketPhasesInfo,
     pub blocks_per_year: u64,
+    /// The annualized volatility of the collateral price the options are priced with, scaled by
+    /// 10^12, e.g. 200_000_000_000 for 20%
     pub alpha: u64,
+    /// The amount of the lending asset the pool has to collect to let the market
+    /// enter the AMM phase before `lp_phase_ends_at`
+    pub target_liquidity: Option<Uint128>,
+    /// The amount of the lending asset the pool has to collect by the end of the LP phase,
+    /// otherwise the market is cancelled
+    pub minimum_liquidity: Option<Uint128>,
+    /// The price of one unit of the collateral asset in units of the lending asset
+    pub collateral_price: Option<Decimal>,
+    /// The price oracle to query for the collateral price; when set, it takes precedence
+    /// over `collateral_price`
+    pub oracle: Option<String>,
+    /// The window (in seconds) of the pool TWAP to use as the collateral price when the market
+    /// has no oracle; when set, it takes precedence over `collateral_price`
+    pub twap_window: Option<u64>,
+    /// The maximum age (in seconds) of an oracle price accepted by the market
+    pub max_price_age: Option<u64>,
+    /// The maximum relative deviation of an oracle price from the price implied by the pool
+    /// reserves, e.g. `0.05` for 5%
+    pub max_price_deviation: Option<Decimal>,
+    /// The share of the returned lent asset the pool keeps when it buys back a PUT option during
+    /// the AMM phase, e.g. `0.02` for 2%; defaults to zero
+    pub buyback_spread: Option<Decimal>,
+    /// The maximum total lent amount of the positions the pool buys back early
+    pub max_buyback_volume: Option<Uint128>,
+    /// The share of every option premium taken as the protocol fee, e.g. `0.1` for 10%; defaults
+    /// to zero
+    pub protocol_fee_rate: Option<Decimal>,
+    /// The recipient of the protocol fees, required when the protocol fee rate is not zero
+    pub treasury: Option<String>,
+    /// The address which can pause and unpause actions in an emergency
+    pub guardian: Option<String>,
+    /// The number of blocks a parameter update queued by the market operator waits before it can
+    /// be applied; defaults to and must not be lower than 100 blocks
+    pub timelock_delay: Option<u64>,
+    /// The market of the same series this market succeeds, registered by the creator. Only the
+    /// predecessor can roll loans over into the market.
+    pub predecessor: Option<String>,
+}
+
+/// A strike of the strike ladder configured by the market operator
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StrikeInfo {
+    /// The strike price in units of the lending asset per unit of the collateral asset
+    pub price: Decimal,
+    /// The maximum total notional in the lending asset which can be borrowed at the strike
+    pub capacity: Option<Uint128>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
+    /// Borrows the lending asset against the pledged collateral. Without `strike_id` the pool
+    /// lends the amount it would pay for the collateral in a swap; with it, the collateral is
+    /// valued at the selected strike of the strike ladder.
     Borrow {
         expected_borrow: Asset,
         pledged_collateral: Asset,
+        strike_id: Option<u64>,
+    },
+    Lend {
+        expected_premium: Asset,
+        lent_asset: Asset,
+    },
+    /// Ends the providing liquidity phase early once the target liquidity is reached.
+    /// All later phase boundaries are shifted by the same number of blocks.
+    EnterAutomatedMarketMakerPhase {},
+    /// Cancels the market before any borrowing happened, so liquidity providers can withdraw
+    /// their liquidity immediately. It takes effect without a timelock since it only returns the
+    /// liquidity to the LPs. Only the market operator can cancel the market.
+    CancelMarket {},
+    /// Records whether the market is cancelled for lacking the minimum liquidity at the end of
+    /// the LP phase. Every other message of the market records it too; the liquidity pool sends
+    /// this one before it pays out a withdrawal, so the payout cannot change the outcome.
+    CheckMinimumLiquidity {},
+    /// Captures the collateral price used by every settlement action of the market. Anyone can
+    /// capture it once the AMM phase is over; the first settlement action captures it otherwise.
+    CaptureSettlementPrice {},
+    /// Settles up to `limit` open PUT options with the settlement price, continuing after the
+    /// last position settled by the previous call. The pool exercises every option it benefits
+    /// from and lets the rest expire. Anyone can settle the options once the AMM phase is over.
+    SettlePuts { limit: Option<u32> },
+    /// Sends the lender the assets owed for their PUT option: the collateral asset if the pool
+    /// exercised the option, otherwise the lent asset. Only available in the post-settlement phase.
+    ClaimCollateral { position_id: u64 },
+    /// Settles the given open PUT options with the settlement price. Positions that are already
+    /// settled or do not exist are skipped. Only the market operator can settle a batch.
+    BatchSettlePuts { position_ids: Vec<u64> },
+    /// Sends the lenders of the given positions the assets owed for their PUT options. Positions
+    /// that are already claimed or do not exist are skipped. Only the market operator can process
+    /// a batch, and only in the post-settlement phase.
+    BatchClaimCollateral { position_ids: Vec<u64> },
+    /// Repays the whole or a part of the notional of a loan during the settlement phase and sends
+    /// the borrower the proportional share of the pledged collateral. A partial repayment splits
+    /// the repaid part off into a new loan. Only the borrower can repay their loan.
+    RepayLoan { loan_id: u64, repayment: Asset },
+    /// Repays a loan during the settlement phase with the proceeds of a new loan in the successor
+    /// `market`, which has to be in the AMM phase and trade the same assets. The pledged
+    /// collateral moves from the liquidity pool through both markets into the liquidity pool of
+    /// the successor market without going back to the borrower, who receives the proceeds left
+    /// after the repayment. Only the borrower can roll their loan over.
+    RolloverLoan {
+        loan_id: u64,
+        market: String,
+        expected_borrow: Asset,
+        strike_id: Option<u64>,
+    },
+    /// Takes out a loan for `borrower` against the collateral sent along by a market rolling a
+    /// loan of the borrower over. The proceeds pay `repayment` to the sending market and the rest
+    /// to the borrower. Only the predecessor of the market can roll loans over into it.
+    BorrowForRollover {
+        borrower: String,
+        expected_borrow: Asset,
+        pledged_collateral: Asset,
+        strike_id: Option<u64>,
+        repayment: Asset,
+    },
+    /// Closes a loan during the AMM phase and sends the borrower the pledged collateral. The
+    /// borrower pays the amount quoted by `GetEarlyCloseQuote`; any excess of the repayment is
+    /// refunded. Only the borrower can close their loan.
+    CloseLoan { loan_id: u64, repayment: Asset },
+    /// Sells a PUT option back to the pool during the AMM phase. The lender receives the amount
+    /// quoted by `GetExitQuote`, which must not be lower than `expected_return`. Only the lender
+    /// can exit their position.
+    ExitPosition {
+        position_id: u64,
+        expected_return: Asset,
+    },
+    /// Replaces the strike ladder of the market. The strikes get the ids 1, 2, ... in the given
+    /// order. It takes effect without a timelock since nothing is borrowed yet and a strike never
+    /// lends more against the collateral than the pool would without it. Only the market operator
+    /// can set the strikes, and only during the providing liquidity phase.
+    SetStrikes { strikes: Vec<StrikeInfo> },
+    /// Sends the protocol fees accrued by the market to the treasury. Anyone can collect the fees.
+    CollectFees {},
+    /// Replaces the capacity limits of the market with tighter ones. Loosening or removing a limit
+    /// commits more of the pool, so it has to be queued with `QueueUpdate`. Only the market
+    /// operator can set the limits.
+    SetCapacityLimits { capacity_limits: CapacityLimits },
+    /// Pauses the given actions in the market and its liquidity pool. Only the guardian can pause
+    /// actions.
+    Pause { actions: Vec<PausableAction> },
+    /// Resumes the given paused actions. Only the guardian can unpause actions.
+    Unpause { actions: Vec<PausableAction> },
+    /// Sets the allowlist mode of the market. The restricted roles are open only to the accounts
+    /// allowlisted by `allowlist_contract` when it is given, otherwise to the accounts allowlisted
+    /// by the market operator. Contracts providing liquidity on behalf of others, e.g. a meta pool
+    /// or a pool rolling its liquidity over, need the liquidity provider role themselves. The
+    /// allowlist restricts borrowing, lending, providing liquidity and opting in to the rollover;
+    /// accounts removed from it can still repay, close and roll over loans, exit positions, claim
+    /// collateral and withdraw liquidity, so their assets are never locked in the market. A loan
+    /// rolled over is checked against the allowlist of the successor market. Only the market
+    /// operator can set the allowlist mode.
+    SetAllowlist {
+        restricted_roles: AllowlistRoles,
+        allowlist_contract: Option<String>,
     },
+    /// Allowlists the `add` accounts for the role and removes the `remove` accounts from it.
+    /// Only the market operator can update the allowlist.
+    UpdateAllowlist {
+        role: AllowlistRole,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Migrates the liquidity pool and its LP token, which are administered by the market, to
+    /// new code ids. A contract is left as is when its code id is not given. Only the market
+    /// operator can migrate them.
+    MigrateLiquidityPool {
+        liquidity_pool_code_id: Option<u64>,
+        liquidity_pool_token_code_id: Option<u64>,
+    },
+    /// Queues an update of the pricing parameters, the phase schedule or the capacity limits,
+    /// which can be applied once the timelock delay has passed. Liquidity providers objecting to
+    /// an update of the phase schedule or the fees can withdraw their share of the reserves not
+    /// committed to open PUT options until it is applied. A phase schedule update must not change the phases that have already
+    /// started. Only the market operator can queue an update, and only one at a time.
+    QueueUpdate { update: MarketUpdate },
+    /// Applies the queued update once the timelock delay has passed. Only the market operator
+    /// can apply the update.
+    ApplyUpdate {},
+    /// Drops the queued update. Only the market operator can cancel the update.
+    CancelUpdate {},
+    /// Moves the liquidity the LPs escrowed for the rollover into the liquidity pool of the
+    /// successor `market`, which has to name this market as its predecessor. Only the creator of
+    /// the market can roll the liquidity over once the market is settled or cancelled.
+    RolloverLiquidity { market: String },
 }
 
 /// This structure describes the query messages available in the contract.
@@ -32,7 +217,90 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     GetMarketOperator {},
     GetLiquidityPool {},
+    /// Returns the market of the same series this market succeeds
+    GetPredecessor {},
+    /// Returns the option token contract the CALL options of the loans are issued as tokens of
+    GetOptionToken {},
     GetMarketPhase {},
     GetMarketPhasesInfo {},
-    GetBorrowingTerms { pledged_collateral: Asset },
+    /// Returns the terms of a loan against the pledged collateral. The remaining capacity takes
+    /// the exposure of `account` into account when it is given.
+    GetBorrowingTerms {
+        pledged_collateral: Asset,
+        strike_id: Option<u64>,
+        account: Option<String>,
+    },
+    /// Returns the terms of writing a PUT option for the lent asset. The remaining capacity takes
+    /// the exposure of `account` into account when it is given.
+    GetLendingTerms {
+        lent_asset: Asset,
+        account: Option<String>,
+    },
+    GetTargetLiquidity {},
+    GetMinimumLiquidity {},
+    GetCollateralPrice {},
+    GetPremiumEarned {},
+    /// Returns the oracle price guards of the market and whether the current price passes them
+    GetPriceGuards {},
+    GetSettlementPrice {},
+    GetPosition {
+        position_id: u64,
+    },
+    GetSettlementProgress {},
+    /// Returns the assets held by the pool on behalf of option holders
+    GetLockedAssets {},
+    /// Returns the collateral the pool may still have to deliver for the open PUT options
+    GetCommittedCollateral {},
+    /// Returns the collateral pledged for and the notional owed by the outstanding loans
+    GetOutstandingLoans {},
+    GetLoan {
+        loan_id: u64,
+    },
+    /// Returns the amount the borrower has to pay to close the loan at the current block
+    GetEarlyCloseQuote {
+        loan_id: u64,
+    },
+    /// Returns the amount the lender receives for selling the PUT option back to the pool at the
+    /// current block
+    GetExitQuote {
+        position_id: u64,
+    },
+    /// Returns the strike ladder of the market
+    GetStrikes {},
+    /// Returns the protocol fee configuration and the fees accrued and collected so far
+    GetProtocolFees {},
+    GetCapacityLimits {},
+    /// Returns the guardian of the market and the actions it has paused
+    GetPausedActions {},
+    /// Returns the update queued by the market operator, if any, along with the timelock delay
+    GetPendingUpdate {},
+    /// Returns the allowlist mode of the market
+    GetAllowlist {},
+    /// Returns the accounts allowlisted by the market operator in the order of their addresses
+    GetAllowlistedAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns whether the account may act in the role, taking the allowlist mode into account
+    IsAllowed {
+        role: AllowlistRole,
+        address: String,
+    },
 }
+
+/// The query an allowlist contract of a market has to answer with an [`IsAllowedResponse`].
+///
+/// [`IsAllowedResponse`]: crate::response::IsAllowedResponse
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowlistQueryMsg {
+    IsAllowed {
+        role: AllowlistRole,
+        address: String,
+    },
+}
+
+/// This structure describes the migrate message of the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
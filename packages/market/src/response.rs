@@ -1,8 +1,12 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_zll_std_liquidity_pool::ap::Asset;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::MarketPhasesInfo;
+use crate::state::{
+    Allowlist, AllowlistRoles, CapacityLimits, Loan, MarketPhasesInfo, PausedActions,
+    PendingUpdate, Position, SettlementPrice, Strike,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -16,6 +20,232 @@ pub struct LiquidityPoolResponse {
     pub liquidity_pool: Addr,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PredecessorResponse {
+    pub predecessor: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OptionTokenResponse {
+    pub option_token: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TargetLiquidityResponse {
+    pub target_liquidity: Option<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MinimumLiquidityResponse {
+    pub minimum_liquidity: Option<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CollateralPriceResponse {
+    /// The price of one unit of the collateral asset in units of the lending asset
+    pub price: Decimal,
+    /// Where the price comes from
+    pub source: PriceSource,
+    /// The time (in seconds since the UNIX epoch) the oracle published the price at
+    pub publish_time: Option<u64>,
+    /// The confidence interval around the oracle price, in units of the lending asset
+    pub confidence: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    /// The price is reported by the price oracle of the market
+    Oracle,
+    /// The price is the time-weighted average price of the pool
+    Twap,
+    /// The price is set in the market config
+    Config,
+    /// The price was captured at the start of the settlement
+    Settlement,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SettlementPriceResponse {
+    /// The settlement price, unless it has not been captured yet
+    pub settlement_price: Option<SettlementPrice>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PriceGuardsResponse {
+    /// The maximum age (in seconds) of an oracle price accepted by the market
+    pub max_price_age: Option<u64>,
+    /// The maximum relative deviation of an oracle price from the pool implied price
+    pub max_price_deviation: Option<Decimal>,
+    /// The current oracle price, if the market has an oracle reporting a price
+    pub price: Option<Decimal>,
+    /// The age (in seconds) of the current oracle price
+    pub price_age: Option<u64>,
+    /// The price of the collateral asset implied by the pool reserves
+    pub pool_price: Option<Decimal>,
+    /// The relative deviation of the current oracle price from the pool implied price
+    pub price_deviation: Option<Decimal>,
+    pub is_price_stale: bool,
+    pub is_price_deviation_too_high: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PositionResponse {
+    pub position: Position,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LoanResponse {
+    pub loan: Loan,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct EarlyCloseQuoteResponse {
+    /// The amount paid out to the borrower when the loan was taken out
+    pub principal: Asset,
+    /// The share of the loan interest the borrower owes for the time the loan was open, i.e. the
+    /// interest less the price the pool buys the rest of the option back at
+    pub interest_due: Asset,
+    /// The amount the borrower has to pay to close the loan
+    pub amount: Asset,
+    /// The collateral sent back to the borrower
+    pub collateral: Asset,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PausedActionsResponse {
+    pub guardian: Option<Addr>,
+    pub paused_actions: PausedActions,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PendingUpdateResponse {
+    pub timelock_delay: u64,
+    pub pending_update: Option<PendingUpdate>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllowlistResponse {
+    pub allowlist: Allowlist,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllowlistedAccount {
+    pub address: Addr,
+    pub roles: AllowlistRoles,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllowlistedAccountsResponse {
+    pub accounts: Vec<AllowlistedAccount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct IsAllowedResponse {
+    pub is_allowed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CapacityLimitsResponse {
+    pub capacity_limits: CapacityLimits,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProtocolFeesResponse {
+    pub protocol_fee_rate: Decimal,
+    pub treasury: Option<Addr>,
+    /// The fees waiting to be collected
+    pub accrued_fees: Asset,
+    /// The fees sent to the treasury so far
+    pub collected_fees: Asset,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StrikesResponse {
+    pub strikes: Vec<Strike>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ExitQuoteResponse {
+    /// The amount lent to the pool
+    pub lent: Asset,
+    /// The current model value of the PUT option the lender has written
+    pub option_value: Asset,
+    /// The share of the netted amount kept by the pool
+    pub spread: Asset,
+    /// The amount returned to the lender
+    pub amount: Asset,
+    /// The lent amount of the positions the pool can still buy back before reaching the buyback
+    /// cap of the market
+    pub remaining_buyback_volume: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SettlementProgressResponse {
+    /// The id of the last position walked by the settlement
+    pub last_position_id: Option<u64>,
+    /// The number of positions opened in the market
+    pub positions_count: u64,
+    /// The number of PUT options exercised by the pool
+    pub exercised_count: u64,
+    /// The number of PUT options the pool let expire
+    pub expired_count: u64,
+    /// Whether the settlement has walked all the positions
+    pub is_complete: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LockedAssetsResponse {
+    /// The collateral asset (first) and the lending asset (second) owed to option holders
+    pub locked_assets: [Asset; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CommittedCollateralResponse {
+    /// The collateral asset the pool may have to deliver for the PUT options which have been
+    /// neither exited nor settled yet
+    pub committed_collateral: Asset,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OutstandingLoansResponse {
+    /// The collateral asset pledged for the loans which have been neither repaid, closed nor
+    /// rolled over, and which the pool does not hold as part of its reserves yet
+    pub collateral: Asset,
+    /// The lending asset the borrowers repay to reclaim that collateral
+    pub notional: Asset,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PremiumEarnedResponse {
+    pub premium: Asset,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct MarketPhaseResponse {
@@ -29,6 +259,9 @@ pub enum MarketPhase {
     AutomatedMarketMaker,
     Settlement,
     PostSettlement,
+    /// The market has been cancelled, either by the market operator or because the minimum
+    /// liquidity was not reached by the end of the LP phase
+    Cancelled,
 }
 
 impl MarketPhase {
@@ -37,12 +270,50 @@ impl MarketPhase {
     }
 
     pub fn can_lp_accept_withdrawals(self) -> bool {
-        self == Self::PostSettlement
+        self == Self::PostSettlement || self == Self::Cancelled
     }
 
     pub fn can_amm_accept_borrowing(self) -> bool {
         self == Self::AutomatedMarketMaker
     }
+
+    pub fn can_amm_accept_lending(self) -> bool {
+        self == Self::AutomatedMarketMaker
+    }
+
+    pub fn can_capture_settlement_price(self) -> bool {
+        self == Self::Settlement || self == Self::PostSettlement
+    }
+
+    pub fn can_settle_options(self) -> bool {
+        self == Self::Settlement || self == Self::PostSettlement
+    }
+
+    pub fn can_claim_collateral(self) -> bool {
+        self == Self::PostSettlement
+    }
+
+    pub fn can_repay_loans(self) -> bool {
+        self == Self::Settlement
+    }
+
+    pub fn can_close_loans_early(self) -> bool {
+        self == Self::AutomatedMarketMaker
+    }
+
+    pub fn can_exit_positions(self) -> bool {
+        self == Self::AutomatedMarketMaker
+    }
+
+    pub fn can_opt_in_rollover(self) -> bool {
+        self == Self::ProvidingLiquidity
+            || self == Self::AutomatedMarketMaker
+            || self == Self::Settlement
+    }
+
+    pub fn can_roll_over_liquidity(self) -> bool {
+        self == Self::PostSettlement || self == Self::Cancelled
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
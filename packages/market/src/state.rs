@@ -1,16 +1,56 @@
-use cosmwasm_std::{Addr, StdResult, Storage};
+use std::fmt;
+
+use cosmwasm_std::{Addr, Decimal, Order, StdError, StdResult, Storage, Uint128};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use crate::response::PriceSource;
+
+use cw_storage_plus::{Bound, Item, Map, U64Key};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub market_operator: Addr,
+    /// The address which instantiated the market, e.g. the market factory
+    pub creator: Addr,
     pub liquidity_pool: Addr,
     pub blocks_per_year: u64,
+    /// The annualized volatility of the collateral price the options are priced with, scaled by
+    /// 10^12, e.g. 200_000_000_000 for 20%
     pub alpha: u64,
     pub market_phases_info: MarketPhasesInfo,
+    /// The collateral asset (first) and the lending asset (second) of the market
+    pub asset_infos: [AssetInfo; 2],
+    /// The amount of the lending asset which lets the market enter the AMM phase early
+    pub target_liquidity: Option<Uint128>,
+    /// The amount of the lending asset required at the end of the LP phase, otherwise the market is cancelled
+    pub minimum_liquidity: Option<Uint128>,
+    /// The price of one unit of the collateral asset in units of the lending asset
+    pub collateral_price: Option<Decimal>,
+    /// The price oracle queried for the collateral price, preferred over `collateral_price`
+    pub oracle: Option<Addr>,
+    /// The window (in seconds) of the pool TWAP used as the collateral price when there is no oracle
+    pub twap_window: Option<u64>,
+    /// The maximum age (in seconds) of an oracle price accepted by the market
+    pub max_price_age: Option<u64>,
+    /// The maximum relative deviation of an oracle price from the price implied by the pool reserves
+    pub max_price_deviation: Option<Decimal>,
+    /// The share of the returned lent asset the pool keeps when it buys back a PUT option early
+    pub buyback_spread: Decimal,
+    /// The maximum total lent amount of the positions the pool buys back early
+    pub max_buyback_volume: Option<Uint128>,
+    /// The share of every option premium taken as the protocol fee
+    pub protocol_fee_rate: Decimal,
+    /// The recipient of the collected protocol fees
+    pub treasury: Option<Addr>,
+    /// The address which can pause and unpause actions in the market and its liquidity pool
+    pub guardian: Option<Addr>,
+    /// The number of blocks a parameter update queued by the market operator waits before it can
+    /// be applied
+    pub timelock_delay: u64,
+    /// The market of the same series this market succeeds
+    pub predecessor: Option<Addr>,
 }
 
 const CONFIG: Item<Config> = Item::new("config");
@@ -19,6 +59,66 @@ pub fn set_config(storage: &mut dyn Storage, config: Config) -> StdResult<()> {
     CONFIG.save(storage, &config)
 }
 
+/// The config of the markets released before the config held their assets, price sources, fees
+/// and operator timelock
+#[derive(Serialize, Deserialize)]
+struct ConfigV1 {
+    market_operator: Addr,
+    liquidity_pool: Addr,
+    blocks_per_year: u64,
+    alpha: u64,
+    market_phases_info: MarketPhasesInfo,
+}
+
+const CONFIG_V1: Item<ConfigV1> = Item::new("config");
+
+/// Migrates a config stored with the released layout. Such markets were instantiated by their
+/// market operator, which becomes their creator, trade the assets `query_asset_infos` returns for
+/// their liquidity pool and apply updates after `timelock_delay` blocks. They charge neither
+/// protocol fees nor a buyback spread, and have no price source, limits or roles configured.
+/// Returns whether the config was migrated.
+pub fn migrate_config(
+    storage: &mut dyn Storage,
+    timelock_delay: u64,
+    query_asset_infos: impl FnOnce(&Addr) -> StdResult<[AssetInfo; 2]>,
+) -> StdResult<bool> {
+    if CONFIG.load(storage).is_ok() {
+        return Ok(false);
+    }
+
+    let config = CONFIG_V1.load(storage)?;
+    let asset_infos = query_asset_infos(&config.liquidity_pool)?;
+
+    CONFIG.save(
+        storage,
+        &Config {
+            creator: config.market_operator.clone(),
+            market_operator: config.market_operator,
+            liquidity_pool: config.liquidity_pool,
+            blocks_per_year: config.blocks_per_year,
+            alpha: config.alpha,
+            market_phases_info: config.market_phases_info,
+            asset_infos,
+            target_liquidity: None,
+            minimum_liquidity: None,
+            collateral_price: None,
+            oracle: None,
+            twap_window: None,
+            max_price_age: None,
+            max_price_deviation: None,
+            buyback_spread: Decimal::zero(),
+            max_buyback_volume: None,
+            protocol_fee_rate: Decimal::zero(),
+            treasury: None,
+            guardian: None,
+            timelock_delay,
+            predecessor: None,
+        },
+    )?;
+
+    Ok(true)
+}
+
 pub fn set_market_operator(storage: &mut dyn Storage, market_operator: Addr) -> StdResult<Config> {
     CONFIG.update(storage, |mut config| {
         config.market_operator = market_operator;
@@ -80,3 +180,960 @@ pub fn get_alpha(storage: &dyn Storage) -> StdResult<u64> {
 
     Ok(config.alpha)
 }
+
+pub fn get_asset_infos(storage: &dyn Storage) -> StdResult<[AssetInfo; 2]> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.asset_infos)
+}
+
+pub fn get_lending_asset_info(storage: &dyn Storage) -> StdResult<AssetInfo> {
+    let [_, lending_asset_info] = get_asset_infos(storage)?;
+
+    Ok(lending_asset_info)
+}
+
+pub fn get_target_liquidity(storage: &dyn Storage) -> StdResult<Option<Uint128>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.target_liquidity)
+}
+
+pub fn get_minimum_liquidity(storage: &dyn Storage) -> StdResult<Option<Uint128>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.minimum_liquidity)
+}
+
+pub fn get_collateral_price(storage: &dyn Storage) -> StdResult<Option<Decimal>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.collateral_price)
+}
+
+pub fn get_oracle(storage: &dyn Storage) -> StdResult<Option<Addr>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.oracle)
+}
+
+pub fn get_twap_window(storage: &dyn Storage) -> StdResult<Option<u64>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.twap_window)
+}
+
+pub fn get_max_price_age(storage: &dyn Storage) -> StdResult<Option<u64>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.max_price_age)
+}
+
+pub fn get_max_price_deviation(storage: &dyn Storage) -> StdResult<Option<Decimal>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.max_price_deviation)
+}
+
+pub fn get_buyback_spread(storage: &dyn Storage) -> StdResult<Decimal> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.buyback_spread)
+}
+
+pub fn get_max_buyback_volume(storage: &dyn Storage) -> StdResult<Option<Uint128>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.max_buyback_volume)
+}
+
+pub fn get_protocol_fee_rate(storage: &dyn Storage) -> StdResult<Decimal> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.protocol_fee_rate)
+}
+
+pub fn get_treasury(storage: &dyn Storage) -> StdResult<Option<Addr>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.treasury)
+}
+
+pub fn get_creator(storage: &dyn Storage) -> StdResult<Addr> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.creator)
+}
+
+pub fn get_predecessor(storage: &dyn Storage) -> StdResult<Option<Addr>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.predecessor)
+}
+
+pub fn get_guardian(storage: &dyn Storage) -> StdResult<Option<Addr>> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.guardian)
+}
+
+pub fn get_timelock_delay(storage: &dyn Storage) -> StdResult<u64> {
+    let config = CONFIG.load(storage)?;
+
+    Ok(config.timelock_delay)
+}
+
+/// An update of the pricing parameters, the phase schedule or the capacity limits of the market.
+/// A parameter is left as is when it is not given.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MarketUpdate {
+    pub market_phases_info: Option<MarketPhasesInfo>,
+    pub alpha: Option<u64>,
+    pub collateral_price: Option<Decimal>,
+    pub twap_window: Option<u64>,
+    pub buyback_spread: Option<Decimal>,
+    pub protocol_fee_rate: Option<Decimal>,
+    pub timelock_delay: Option<u64>,
+    pub capacity_limits: Option<CapacityLimits>,
+}
+
+impl MarketUpdate {
+    /// Whether the update changes the phase schedule or the fees, which the liquidity providers
+    /// committed their liquidity for, so they can exit before it is applied
+    pub fn lets_lps_exit(&self) -> bool {
+        self.market_phases_info.is_some()
+            || self.buyback_spread.is_some()
+            || self.protocol_fee_rate.is_some()
+    }
+}
+
+pub fn apply_market_update(storage: &mut dyn Storage, update: &MarketUpdate) -> StdResult<Config> {
+    if let Some(capacity_limits) = &update.capacity_limits {
+        set_capacity_limits(storage, capacity_limits)?;
+    }
+
+    CONFIG.update(storage, |mut config| -> StdResult<_> {
+        let update = update.clone();
+
+        if let Some(market_phases_info) = update.market_phases_info {
+            config.market_phases_info = market_phases_info;
+        }
+        if let Some(alpha) = update.alpha {
+            config.alpha = alpha;
+        }
+        if let Some(collateral_price) = update.collateral_price {
+            config.collateral_price = Some(collateral_price);
+        }
+        if let Some(twap_window) = update.twap_window {
+            config.twap_window = Some(twap_window);
+        }
+        if let Some(buyback_spread) = update.buyback_spread {
+            config.buyback_spread = buyback_spread;
+        }
+        if let Some(protocol_fee_rate) = update.protocol_fee_rate {
+            config.protocol_fee_rate = protocol_fee_rate;
+        }
+        if let Some(timelock_delay) = update.timelock_delay {
+            config.timelock_delay = timelock_delay;
+        }
+
+        Ok(config)
+    })
+}
+
+/// A market update queued by the market operator. Liquidity providers can withdraw from the pool
+/// until the update is applied or cancelled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingUpdate {
+    pub update: MarketUpdate,
+    pub queued_at: u64,
+    /// The block height the update can be applied from
+    pub executable_at: u64,
+}
+
+const PENDING_UPDATE: Item<PendingUpdate> = Item::new("pending_update");
+
+pub fn set_pending_update(
+    storage: &mut dyn Storage,
+    pending_update: &PendingUpdate,
+) -> StdResult<()> {
+    PENDING_UPDATE.save(storage, pending_update)
+}
+
+pub fn may_get_pending_update(storage: &dyn Storage) -> StdResult<Option<PendingUpdate>> {
+    PENDING_UPDATE.may_load(storage)
+}
+
+pub fn remove_pending_update(storage: &mut dyn Storage) {
+    PENDING_UPDATE.remove(storage)
+}
+
+/// An action the guardian can pause in an emergency
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PausableAction {
+    Borrow,
+    Lend,
+    /// Providing liquidity to the pool
+    Deposit,
+    /// Withdrawing liquidity from the pool and assets from the market: claiming collateral,
+    /// closing loans early and exiting positions
+    Withdraw,
+    /// Settling PUT options and repaying loans, which exercises the options of the market
+    Exercise,
+}
+
+impl fmt::Display for PausableAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Borrow => write!(f, "borrow"),
+            Self::Lend => write!(f, "lend"),
+            Self::Deposit => write!(f, "deposit"),
+            Self::Withdraw => write!(f, "withdraw"),
+            Self::Exercise => write!(f, "exercise"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PausedActions {
+    pub borrow: bool,
+    pub lend: bool,
+    pub deposit: bool,
+    pub withdraw: bool,
+    pub exercise: bool,
+}
+
+impl PausedActions {
+    pub fn is_paused(&self, action: PausableAction) -> bool {
+        match action {
+            PausableAction::Borrow => self.borrow,
+            PausableAction::Lend => self.lend,
+            PausableAction::Deposit => self.deposit,
+            PausableAction::Withdraw => self.withdraw,
+            PausableAction::Exercise => self.exercise,
+        }
+    }
+
+    pub fn set_paused(&mut self, action: PausableAction, is_paused: bool) {
+        match action {
+            PausableAction::Borrow => self.borrow = is_paused,
+            PausableAction::Lend => self.lend = is_paused,
+            PausableAction::Deposit => self.deposit = is_paused,
+            PausableAction::Withdraw => self.withdraw = is_paused,
+            PausableAction::Exercise => self.exercise = is_paused,
+        }
+    }
+}
+
+const PAUSED_ACTIONS: Item<PausedActions> = Item::new("paused_actions");
+
+pub fn set_paused_actions(
+    storage: &mut dyn Storage,
+    paused_actions: &PausedActions,
+) -> StdResult<()> {
+    PAUSED_ACTIONS.save(storage, paused_actions)
+}
+
+pub fn get_paused_actions(storage: &dyn Storage) -> StdResult<PausedActions> {
+    Ok(PAUSED_ACTIONS.may_load(storage)?.unwrap_or_default())
+}
+
+/// A role of the accounts using the market, which the allowlist mode can restrict
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowlistRole {
+    Borrower,
+    Lender,
+    /// Providing liquidity to the pool
+    LiquidityProvider,
+}
+
+impl fmt::Display for AllowlistRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Borrower => write!(f, "borrower"),
+            Self::Lender => write!(f, "lender"),
+            Self::LiquidityProvider => write!(f, "liquidity provider"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct AllowlistRoles {
+    pub borrower: bool,
+    pub lender: bool,
+    pub liquidity_provider: bool,
+}
+
+impl AllowlistRoles {
+    pub fn includes(&self, role: AllowlistRole) -> bool {
+        match role {
+            AllowlistRole::Borrower => self.borrower,
+            AllowlistRole::Lender => self.lender,
+            AllowlistRole::LiquidityProvider => self.liquidity_provider,
+        }
+    }
+
+    pub fn set(&mut self, role: AllowlistRole, is_included: bool) {
+        match role {
+            AllowlistRole::Borrower => self.borrower = is_included,
+            AllowlistRole::Lender => self.lender = is_included,
+            AllowlistRole::LiquidityProvider => self.liquidity_provider = is_included,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !(self.borrower || self.lender || self.liquidity_provider)
+    }
+}
+
+/// The allowlist mode of the market, set by the market operator. The restricted roles are open
+/// only to the accounts allowlisted for them, every other role is open to anyone.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Allowlist {
+    pub restricted_roles: AllowlistRoles,
+    /// The contract queried for the allowlisted accounts at runtime; without it, the market
+    /// operator allowlists the accounts in the market itself
+    pub allowlist_contract: Option<Addr>,
+}
+
+const ALLOWLIST: Item<Allowlist> = Item::new("allowlist");
+
+pub fn set_allowlist(storage: &mut dyn Storage, allowlist: &Allowlist) -> StdResult<()> {
+    ALLOWLIST.save(storage, allowlist)
+}
+
+pub fn get_allowlist(storage: &dyn Storage) -> StdResult<Allowlist> {
+    Ok(ALLOWLIST.may_load(storage)?.unwrap_or_default())
+}
+
+/// The roles the market operator has allowlisted each account for
+const ALLOWLISTED_ROLES: Map<&Addr, AllowlistRoles> = Map::new("allowlisted_roles");
+
+pub fn get_allowlisted_roles(storage: &dyn Storage, account: &Addr) -> StdResult<AllowlistRoles> {
+    Ok(ALLOWLISTED_ROLES
+        .may_load(storage, account)?
+        .unwrap_or_default())
+}
+
+pub fn set_allowlisted_roles(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    roles: &AllowlistRoles,
+) -> StdResult<()> {
+    if roles.is_empty() {
+        ALLOWLISTED_ROLES.remove(storage, account);
+
+        return Ok(());
+    }
+
+    ALLOWLISTED_ROLES.save(storage, account, roles)
+}
+
+/// Returns up to `limit` accounts allowlisted by the market operator in the order of their
+/// addresses, starting after `start_after`.
+pub fn get_allowlisted_accounts(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: usize,
+) -> StdResult<Vec<(Addr, AllowlistRoles)>> {
+    ALLOWLISTED_ROLES
+        .range(
+            storage,
+            start_after.map(|account| Bound::exclusive(account.as_bytes())),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (account, roles) = item?;
+
+            Ok((Addr::unchecked(String::from_utf8_lossy(&account)), roles))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MarketState {
+    /// Set when the market operator cancels the market, or when the market lacks the minimum
+    /// liquidity at the end of the LP phase
+    pub is_cancelled: bool,
+    /// Set once the minimum liquidity has been checked after the end of the LP phase
+    #[serde(default)]
+    pub is_liquidity_checked: bool,
+    /// The block time (in seconds) of the last call the market received by the end of the AMM
+    /// phase. The settlement price is taken as of this time.
+    #[serde(default)]
+    pub amm_observed_at: u64,
+    /// The latest oracle price observed by the market by the end of the AMM phase
+    #[serde(default)]
+    pub amm_oracle_price: Option<ObservedPrice>,
+    /// The number of borrowings accepted by the market
+    pub borrows_count: u64,
+    /// The premium (interest) in the lending asset earned by the pool since the market started
+    pub total_premium: Uint128,
+    /// The number of option positions opened in the market, which is also the last position id
+    pub positions_count: u64,
+    /// The amount of the collateral asset held by the pool on behalf of option holders
+    pub locked_collateral: Uint128,
+    /// The amount of the lending asset held by the pool on behalf of option holders
+    pub locked_lending: Uint128,
+    /// The number of loans recorded by the market, including the ones split off by partial
+    /// repayments, which is also the last loan id
+    pub loans_count: u64,
+    /// The amount of the collateral asset pledged for loans that have not been repaid yet
+    pub loan_collateral: Uint128,
+    /// The total lent amount of the positions bought back early
+    pub buyback_volume: Uint128,
+    /// The protocol fees in the lending asset held by the pool until they are collected
+    pub accrued_fees: Uint128,
+    /// The protocol fees in the lending asset sent to the treasury so far
+    pub collected_fees: Uint128,
+    /// The notional in the lending asset of the loans taken out and not closed early
+    pub borrowed_notional: Uint128,
+    /// The amount of the collateral asset the pool may have to deliver for PUT options which
+    /// have been neither exited early nor settled
+    pub put_collateral: Uint128,
+}
+
+impl MarketState {
+    /// Checks whether anyone has borrowed from or lent to the market.
+    pub fn has_positions(&self) -> bool {
+        self.borrows_count > 0 || self.positions_count > 0
+    }
+}
+
+const MARKET_STATE: Item<MarketState> = Item::new("market_state");
+
+pub fn set_market_state(storage: &mut dyn Storage, market_state: MarketState) -> StdResult<()> {
+    MARKET_STATE.save(storage, &market_state)
+}
+
+pub fn get_market_state(storage: &dyn Storage) -> StdResult<MarketState> {
+    MARKET_STATE.load(storage)
+}
+
+/// Initialises the state of a market released before the market kept one, taking the settlement
+/// price as of `block_time` (in seconds) until the market observes a later one. Returns whether
+/// the state was initialised.
+pub fn migrate_market_state(storage: &mut dyn Storage, block_time: u64) -> StdResult<bool> {
+    if MARKET_STATE.may_load(storage)?.is_some() {
+        return Ok(false);
+    }
+
+    MARKET_STATE.save(
+        storage,
+        &MarketState {
+            amm_observed_at: block_time,
+            ..MarketState::default()
+        },
+    )?;
+
+    Ok(true)
+}
+
+pub fn cancel_market(storage: &mut dyn Storage) -> StdResult<MarketState> {
+    MARKET_STATE.update(storage, |mut market_state| -> StdResult<_> {
+        market_state.is_cancelled = true;
+        Ok(market_state)
+    })
+}
+
+/// Records the outcome of the minimum liquidity check, which is done only once.
+pub fn record_liquidity_check(
+    storage: &mut dyn Storage,
+    has_minimum_liquidity: bool,
+) -> StdResult<MarketState> {
+    MARKET_STATE.update(storage, |mut market_state| -> StdResult<_> {
+        market_state.is_liquidity_checked = true;
+        market_state.is_cancelled |= !has_minimum_liquidity;
+        Ok(market_state)
+    })
+}
+
+/// Records the block time of a call the market received by the end of the AMM phase, along with
+/// the oracle price observed by the call. The previous oracle price is kept when the call could not
+/// observe one.
+pub fn record_amm_observation(
+    storage: &mut dyn Storage,
+    block_time: u64,
+    oracle_price: Option<ObservedPrice>,
+) -> StdResult<MarketState> {
+    MARKET_STATE.update(storage, |mut market_state| -> StdResult<_> {
+        market_state.amm_observed_at = block_time;
+        if oracle_price.is_some() {
+            market_state.amm_oracle_price = oracle_price;
+        }
+        Ok(market_state)
+    })
+}
+
+pub fn record_borrowing(
+    storage: &mut dyn Storage,
+    premium: Uint128,
+    protocol_fee: Uint128,
+) -> StdResult<MarketState> {
+    MARKET_STATE.update(storage, |mut market_state| -> StdResult<_> {
+        market_state.borrows_count += 1;
+        market_state.total_premium = market_state.total_premium.checked_add(premium)?;
+        market_state.accrued_fees = market_state.accrued_fees.checked_add(protocol_fee)?;
+        Ok(market_state)
+    })
+}
+
+/// The limits on how much liquidity positions can consume, set by the market operator. Every
+/// limit is optional.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct CapacityLimits {
+    /// The maximum size of a single loan or PUT position in the lending asset
+    pub max_position_size: Option<Uint128>,
+    /// The maximum total size of the loans and PUT positions of a single account in the lending
+    /// asset
+    pub max_account_exposure: Option<Uint128>,
+    /// The maximum share of a pool reserve which can be committed: the lending asset paid out
+    /// for loans, or the collateral asset the pool may have to deliver for PUT options
+    pub max_utilization: Option<Decimal>,
+}
+
+impl CapacityLimits {
+    /// Checks whether every limit is at least as tight as the current one, so the limits cannot
+    /// commit more of the pool than the current ones.
+    pub fn is_tighter_than(&self, current: &CapacityLimits) -> bool {
+        fn is_tighter<T: PartialOrd>(limit: Option<T>, current: Option<T>) -> bool {
+            match (limit, current) {
+                (Some(limit), Some(current)) => limit <= current,
+                (limit, current) => limit.is_some() || current.is_none(),
+            }
+        }
+
+        is_tighter(self.max_position_size, current.max_position_size)
+            && is_tighter(self.max_account_exposure, current.max_account_exposure)
+            && is_tighter(self.max_utilization, current.max_utilization)
+    }
+}
+
+const CAPACITY_LIMITS: Item<CapacityLimits> = Item::new("capacity_limits");
+
+pub fn set_capacity_limits(
+    storage: &mut dyn Storage,
+    capacity_limits: &CapacityLimits,
+) -> StdResult<()> {
+    CAPACITY_LIMITS.save(storage, capacity_limits)
+}
+
+pub fn get_capacity_limits(storage: &dyn Storage) -> StdResult<CapacityLimits> {
+    Ok(CAPACITY_LIMITS.may_load(storage)?.unwrap_or_default())
+}
+
+/// The total size in the lending asset of the loans and PUT positions of each account. Closing a
+/// loan or exiting a position early releases its size.
+const ACCOUNT_EXPOSURES: Map<&Addr, Uint128> = Map::new("account_exposures");
+
+pub fn get_account_exposure(storage: &dyn Storage, account: &Addr) -> StdResult<Uint128> {
+    Ok(ACCOUNT_EXPOSURES
+        .may_load(storage, account)?
+        .unwrap_or_default())
+}
+
+pub fn set_account_exposure(
+    storage: &mut dyn Storage,
+    account: &Addr,
+    exposure: Uint128,
+) -> StdResult<()> {
+    ACCOUNT_EXPOSURES.save(storage, account, &exposure)
+}
+
+/// A strike price of the market's strike ladder, which borrowers can select to pick the
+/// loan-to-value level of their loans.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Strike {
+    pub id: u64,
+    /// The strike price in units of the lending asset per unit of the collateral asset
+    pub price: Decimal,
+    /// The maximum total notional in the lending asset which can be borrowed at the strike
+    pub capacity: Option<Uint128>,
+    /// The total notional in the lending asset borrowed at the strike so far
+    pub borrowed: Uint128,
+}
+
+impl Strike {
+    /// Returns the notional which can still be borrowed at the strike, if the strike is capped.
+    pub fn remaining_capacity(&self) -> Option<Uint128> {
+        self.capacity
+            .map(|capacity| capacity.saturating_sub(self.borrowed))
+    }
+}
+
+const STRIKES: Item<Vec<Strike>> = Item::new("strikes");
+
+pub fn set_strikes(storage: &mut dyn Storage, strikes: &[Strike]) -> StdResult<()> {
+    STRIKES.save(storage, &strikes.to_vec())
+}
+
+pub fn get_strikes(storage: &dyn Storage) -> StdResult<Vec<Strike>> {
+    Ok(STRIKES.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn may_get_strike(storage: &dyn Storage, strike_id: u64) -> StdResult<Option<Strike>> {
+    Ok(get_strikes(storage)?
+        .into_iter()
+        .find(|strike| strike.id == strike_id))
+}
+
+pub fn set_strike(storage: &mut dyn Storage, strike: &Strike) -> StdResult<()> {
+    let mut strikes = get_strikes(storage)?;
+
+    match strikes.iter_mut().find(|existing| existing.id == strike.id) {
+        Some(existing) => *existing = strike.clone(),
+        None => strikes.push(strike.clone()),
+    }
+
+    set_strikes(storage, &strikes)
+}
+
+/// An oracle price observed by the market during a call, along with the price implied by the pool
+/// reserves at the time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ObservedPrice {
+    /// The price of one unit of the collateral asset in units of the lending asset
+    pub price: Decimal,
+    /// The time (in seconds since the UNIX epoch) the oracle published the price at
+    pub publish_time: u64,
+    /// The confidence interval around the oracle price, in units of the lending asset
+    pub confidence: Decimal,
+    /// The price implied by the pool reserves, unless the pool had no reserves yet
+    pub pool_price: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SettlementPrice {
+    /// The price of one unit of the collateral asset in units of the lending asset
+    pub price: Decimal,
+    /// Where the price was taken from
+    pub source: PriceSource,
+    /// The time (in seconds since the UNIX epoch) the oracle published the price at
+    pub publish_time: Option<u64>,
+    /// The confidence interval around the oracle price, in units of the lending asset
+    pub confidence: Option<Decimal>,
+    /// The block time (in seconds) the price is taken as of, the end of the AMM phase
+    #[serde(default)]
+    pub observed_at: u64,
+    /// The block height the price was captured at
+    pub captured_at: u64,
+}
+
+const SETTLEMENT_PRICE: Item<SettlementPrice> = Item::new("settlement_price");
+
+/// Stores the settlement price, which can only be captured once.
+pub fn set_settlement_price(
+    storage: &mut dyn Storage,
+    settlement_price: &SettlementPrice,
+) -> StdResult<()> {
+    if SETTLEMENT_PRICE.may_load(storage)?.is_some() {
+        return Err(StdError::generic_err(
+            "Settlement price has already been captured",
+        ));
+    }
+
+    SETTLEMENT_PRICE.save(storage, settlement_price)
+}
+
+pub fn get_settlement_price(storage: &dyn Storage) -> StdResult<Option<SettlementPrice>> {
+    SETTLEMENT_PRICE.may_load(storage)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionStatus {
+    /// The option has not been settled yet
+    Open,
+    /// The pool exercised the PUT option, so the lender receives the collateral asset
+    Exercised,
+    /// The pool let the PUT option expire, so the lender receives the lent asset back
+    Expired,
+    /// The pool bought the PUT option back during the AMM phase
+    Exited,
+}
+
+/// A PUT option written by a lender to the pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Position {
+    pub id: u64,
+    pub owner: Addr,
+    /// The strike price in units of the lending asset per unit of the collateral asset
+    pub strike: Decimal,
+    /// The amount of the lending asset lent to the pool
+    pub lent_amount: Uint128,
+    /// The amount of the collateral asset the lender receives if the pool exercises the option
+    pub collateral_amount: Uint128,
+    /// The premium in the lending asset paid to the lender
+    pub premium: Uint128,
+    pub status: PositionStatus,
+    /// Set when the lender has claimed the assets owed to them
+    pub is_claimed: bool,
+    /// The block height the position was opened at
+    pub created_at: u64,
+}
+
+const POSITIONS: Map<U64Key, Position> = Map::new("positions");
+
+/// Reserves the next position id.
+pub fn next_position_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let market_state = MARKET_STATE.update(storage, |mut market_state| -> StdResult<_> {
+        market_state.positions_count += 1;
+        Ok(market_state)
+    })?;
+
+    Ok(market_state.positions_count)
+}
+
+pub fn set_position(storage: &mut dyn Storage, position: &Position) -> StdResult<()> {
+    POSITIONS.save(storage, U64Key::new(position.id), position)
+}
+
+pub fn get_position(storage: &dyn Storage, position_id: u64) -> StdResult<Position> {
+    POSITIONS.load(storage, U64Key::new(position_id))
+}
+
+pub fn may_get_position(storage: &dyn Storage, position_id: u64) -> StdResult<Option<Position>> {
+    POSITIONS.may_load(storage, U64Key::new(position_id))
+}
+
+/// Returns up to `limit` positions in the order they were opened, starting after `start_after`.
+pub fn get_positions(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<Position>> {
+    POSITIONS
+        .range(
+            storage,
+            start_after.map(Bound::exclusive_int),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, position)| position))
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LoanStatus {
+    Open,
+    Repaid,
+    /// Closed by the borrower during the AMM phase
+    Closed,
+    /// Repaid during the settlement phase with the proceeds of a loan in a successor market
+    RolledOver,
+}
+
+/// A loan backed by the collateral pledged by a borrower. The borrower holds a CALL option on the
+/// collateral, which they exercise by repaying the notional during the settlement phase.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Loan {
+    pub id: u64,
+    pub owner: Addr,
+    /// The strike price in units of the lending asset per unit of the collateral asset
+    pub strike: Decimal,
+    /// The strike of the strike ladder selected by the borrower, if any
+    pub strike_id: Option<u64>,
+    /// The amount of the lending asset the borrower has to repay to reclaim the collateral
+    pub notional: Uint128,
+    /// The amount of the collateral asset pledged for the loan
+    pub collateral_amount: Uint128,
+    /// The interest in the lending asset paid to the pool
+    pub interest: Uint128,
+    pub status: LoanStatus,
+    /// The loan this loan was split off from by a partial repayment
+    pub parent_id: Option<u64>,
+    /// The block height the loan was taken out at
+    pub created_at: u64,
+}
+
+const LOANS: Map<U64Key, Loan> = Map::new("loans");
+
+/// Reserves the next loan id.
+pub fn next_loan_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let market_state = MARKET_STATE.update(storage, |mut market_state| -> StdResult<_> {
+        market_state.loans_count += 1;
+        Ok(market_state)
+    })?;
+
+    Ok(market_state.loans_count)
+}
+
+pub fn set_loan(storage: &mut dyn Storage, loan: &Loan) -> StdResult<()> {
+    LOANS.save(storage, U64Key::new(loan.id), loan)
+}
+
+pub fn get_loan(storage: &dyn Storage, loan_id: u64) -> StdResult<Loan> {
+    LOANS.load(storage, U64Key::new(loan_id))
+}
+
+/// The option token contract the CALL options of the loans are issued as tokens of, for markets
+/// instantiated with an option token code id
+const OPTION_TOKEN: Item<Addr> = Item::new("option_token");
+
+pub fn set_option_token(storage: &mut dyn Storage, option_token: &Addr) -> StdResult<()> {
+    OPTION_TOKEN.save(storage, option_token)
+}
+
+pub fn may_get_option_token(storage: &dyn Storage) -> StdResult<Option<Addr>> {
+    OPTION_TOKEN.may_load(storage)
+}
+
+/// A loan being rolled over into a successor market, kept until the successor market has paid
+/// the repayment to the market.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingLoanRollover {
+    pub loan_id: u64,
+    /// The amount of the lending asset the successor market has to pay
+    pub repayment: Uint128,
+    /// The balance of the lending asset held by the market before the rollover
+    pub balance: Uint128,
+}
+
+const PENDING_LOAN_ROLLOVER: Item<PendingLoanRollover> = Item::new("pending_loan_rollover");
+
+pub fn set_pending_loan_rollover(
+    storage: &mut dyn Storage,
+    pending_loan_rollover: &PendingLoanRollover,
+) -> StdResult<()> {
+    PENDING_LOAN_ROLLOVER.save(storage, pending_loan_rollover)
+}
+
+pub fn may_get_pending_loan_rollover(
+    storage: &dyn Storage,
+) -> StdResult<Option<PendingLoanRollover>> {
+    PENDING_LOAN_ROLLOVER.may_load(storage)
+}
+
+pub fn remove_pending_loan_rollover(storage: &mut dyn Storage) {
+    PENDING_LOAN_ROLLOVER.remove(storage)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct SettlementProgress {
+    /// The id of the last position walked by the settlement
+    pub last_position_id: Option<u64>,
+    /// The number of PUT options exercised by the pool
+    pub exercised_count: u64,
+    /// The number of PUT options the pool let expire
+    pub expired_count: u64,
+}
+
+const SETTLEMENT_PROGRESS: Item<SettlementProgress> = Item::new("settlement_progress");
+
+pub fn set_settlement_progress(
+    storage: &mut dyn Storage,
+    settlement_progress: &SettlementProgress,
+) -> StdResult<()> {
+    SETTLEMENT_PROGRESS.save(storage, settlement_progress)
+}
+
+pub fn get_settlement_progress(storage: &dyn Storage) -> StdResult<SettlementProgress> {
+    Ok(SETTLEMENT_PROGRESS.may_load(storage)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    const CONFIG_V1_JSON: &[u8] = br#"{
+        "market_operator": "operator",
+        "liquidity_pool": "pool",
+        "blocks_per_year": 4204800,
+        "alpha": 200000000000,
+        "market_phases_info": {
+            "market_started_at": 1234,
+            "lp_phase_ends_at": 2345,
+            "amm_phase_ends_at": 3456,
+            "settlement_phase_ends_at": 4567
+        }
+    }"#;
+
+    fn pool_asset_infos(liquidity_pool: &Addr) -> StdResult<[AssetInfo; 2]> {
+        assert_eq!(liquidity_pool, &Addr::unchecked("pool"));
+
+        Ok([
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ])
+    }
+
+    #[test]
+    fn migrate_config_fills_the_fields_of_a_config_v1() {
+        let mut storage = MockStorage::new();
+        storage.set(b"config", CONFIG_V1_JSON);
+
+        assert!(CONFIG.load(&storage).is_err());
+        assert_eq!(
+            migrate_config(&mut storage, 100, pool_asset_infos),
+            Ok(true)
+        );
+
+        let config = CONFIG.load(&storage).unwrap();
+        assert_eq!(config.market_operator, Addr::unchecked("operator"));
+        assert_eq!(config.creator, Addr::unchecked("operator"));
+        assert_eq!(config.liquidity_pool, Addr::unchecked("pool"));
+        assert_eq!(config.blocks_per_year, 4204800);
+        assert_eq!(config.alpha, 200000000000);
+        assert_eq!(config.market_phases_info.amm_phase_ends_at, 3456);
+        assert_eq!(
+            config.asset_infos,
+            pool_asset_infos(&config.liquidity_pool).unwrap()
+        );
+        assert_eq!(config.target_liquidity, None);
+        assert_eq!(config.minimum_liquidity, None);
+        assert_eq!(config.collateral_price, None);
+        assert_eq!(config.oracle, None);
+        assert_eq!(config.twap_window, None);
+        assert_eq!(config.max_price_age, None);
+        assert_eq!(config.max_price_deviation, None);
+        assert_eq!(config.buyback_spread, Decimal::zero());
+        assert_eq!(config.max_buyback_volume, None);
+        assert_eq!(config.protocol_fee_rate, Decimal::zero());
+        assert_eq!(config.treasury, None);
+        assert_eq!(config.guardian, None);
+        assert_eq!(config.timelock_delay, 100);
+        assert_eq!(config.predecessor, None);
+    }
+
+    #[test]
+    fn migrate_config_keeps_a_current_config() {
+        let mut storage = MockStorage::new();
+        storage.set(b"config", CONFIG_V1_JSON);
+        migrate_config(&mut storage, 100, pool_asset_infos).unwrap();
+
+        assert_eq!(
+            migrate_config(&mut storage, 200, pool_asset_infos),
+            Ok(false)
+        );
+        assert_eq!(CONFIG.load(&storage).unwrap().timelock_delay, 100);
+    }
+
+    #[test]
+    fn migrate_market_state_initialises_a_missing_state() {
+        let mut storage = MockStorage::new();
+
+        assert_eq!(migrate_market_state(&mut storage, 1_600_000_000), Ok(true));
+        assert_eq!(
+            get_market_state(&storage).unwrap(),
+            MarketState {
+                amm_observed_at: 1_600_000_000,
+                ..MarketState::default()
+            }
+        );
+
+        record_liquidity_check(&mut storage, true).unwrap();
+        assert_eq!(migrate_market_state(&mut storage, 1_600_000_100), Ok(false));
+        assert!(get_market_state(&storage).unwrap().is_liquidity_checked);
+    }
+}
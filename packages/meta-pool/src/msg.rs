@@ -0,0 +1,94 @@
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_zll_std_liquidity_pool::ap::{Asset, AssetInfo};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure describes the parameters used for creating a meta pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    /// The maintainer who allocates the liquidity of the meta pool across liquidity pools
+    pub maintainer: String,
+    /// The collateral asset (first) and the lending asset (second) of the meta pool
+    pub asset_infos: [AssetInfo; 2],
+    /// The code id of the CW20 contract used for the meta pool token
+    pub token_code_id: u64,
+    /// The price of one unit of the collateral asset in units of the lending asset
+    pub collateral_price: Option<Decimal>,
+    /// The price oracle queried for the collateral price, preferred over `collateral_price`
+    pub oracle: Option<String>,
+}
+
+/// ## Description
+/// This structure describes the execute messages available in the meta pool contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// Deposits assets in the meta pool and mints meta pool tokens worth the deposited value
+    Deposit {
+        /// The assets to deposit
+        assets: [Asset; 2],
+        /// The minimum amount of meta pool tokens the depositor accepts to receive
+        min_share_out: Option<Uint128>,
+    },
+    /// Adds a liquidity pool the maintainer can provide liquidity to. Only the maintainer can add
+    /// liquidity pools.
+    AddLiquidityPool { liquidity_pool: String },
+    /// Removes a liquidity pool the meta pool holds no LP tokens of. Only the maintainer can remove
+    /// liquidity pools.
+    RemoveLiquidityPool { liquidity_pool: String },
+    /// Provides assets held by the meta pool as liquidity to one of its liquidity pools. Only the
+    /// maintainer can provide liquidity.
+    ProvideLiquidity {
+        liquidity_pool: String,
+        assets: [Asset; 2],
+    },
+    /// Withdraws liquidity from one of the liquidity pools by burning LP tokens held by the meta
+    /// pool. Only the maintainer can withdraw liquidity.
+    WithdrawLiquidity {
+        liquidity_pool: String,
+        /// The amount of LP tokens to burn
+        amount: Uint128,
+    },
+    /// Hands the maintenance of the meta pool over. Only the maintainer can set the maintainer.
+    SetMaintainer { maintainer: String },
+}
+
+/// ## Description
+/// This structure describes the messages that can be attached to meta pool tokens sent to the meta pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Burns the received meta pool tokens and sends their share of the assets and the LP tokens
+    /// held by the meta pool
+    Withdraw {
+        /// The receiver of the withdrawn assets, defaults to the sender of the meta pool tokens
+        recipient: Option<String>,
+    },
+}
+
+/// ## Description
+/// This structure describes the query messages available in the meta pool contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns the config of the meta pool in an object of type
+    /// [`ConfigResponse`](crate::response::ConfigResponse).
+    Config {},
+    /// Returns the assets held by the meta pool and its LP tokens of every liquidity pool in an
+    /// object of type [`HoldingsResponse`](crate::response::HoldingsResponse).
+    Holdings {},
+    /// Returns the net asset value of the meta pool per meta pool token, valued in the lending
+    /// asset, in an object of type [`NavPerShareResponse`](crate::response::NavPerShareResponse).
+    NavPerShare {},
+}
+
+/// ## Description
+/// This structure describes the migrate message of the meta pool contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
@@ -0,0 +1,50 @@
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_zll_std_liquidity_pool::ap::{Asset, AssetInfo};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub use cw_zll_std_liquidity_pool::response::NavPerShareResponse;
+
+/// ## Description
+/// This structure describes the config of the meta pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    pub maintainer: Addr,
+    /// The collateral asset (first) and the lending asset (second) of the meta pool
+    pub asset_infos: [AssetInfo; 2],
+    /// The CW20 contract of the meta pool token
+    pub share_token: Addr,
+    pub collateral_price: Option<Decimal>,
+    pub oracle: Option<Addr>,
+    /// The liquidity pools the maintainer can provide liquidity to
+    pub liquidity_pools: Vec<Addr>,
+}
+
+/// ## Description
+/// This structure describes the LP tokens of a single liquidity pool held by the meta pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LiquidityPoolHolding {
+    pub liquidity_pool: Addr,
+    /// The LP token of the liquidity pool
+    pub liquidity_token: Addr,
+    /// The amount of LP tokens held
+    pub share: Uint128,
+    /// The value of the share of the reserves of the liquidity pool at the collateral price of the
+    /// meta pool
+    pub nav: Asset,
+}
+
+/// ## Description
+/// This structure describes everything the meta pool holds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HoldingsResponse {
+    /// The collateral and the lending asset held by the meta pool itself
+    pub assets: [Asset; 2],
+    /// The LP tokens held by the meta pool
+    pub liquidity_pools: Vec<LiquidityPoolHolding>,
+    /// The value of all holdings
+    pub nav: Asset,
+}
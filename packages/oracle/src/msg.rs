@@ -0,0 +1,23 @@
+use cosmwasm_std::Decimal;
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// This structure describes the query messages every price oracle used by the markets has to support.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns the price of one unit of the `base` asset in units of the `quote` asset
+    Price { base: AssetInfo, quote: AssetInfo },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PriceResponse {
+    /// The price of one unit of the base asset in units of the quote asset
+    pub price: Decimal,
+    /// The time (in seconds since the UNIX epoch) the price was published at
+    pub publish_time: u64,
+    /// The confidence interval around the price, in units of the quote asset
+    pub confidence: Decimal,
+}
@@ -0,0 +1,13 @@
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult};
+use cw_zll_std_liquidity_pool::ap::AssetInfo;
+
+use crate::msg::{PriceResponse, QueryMsg};
+
+pub fn query_price(
+    querier: &QuerierWrapper,
+    oracle: Addr,
+    base: AssetInfo,
+    quote: AssetInfo,
+) -> StdResult<PriceResponse> {
+    querier.query_wasm_smart(oracle, &QueryMsg::Price { base, quote })
+}
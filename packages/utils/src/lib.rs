@@ -1 +1,2 @@
+pub mod migration;
 pub mod reply;
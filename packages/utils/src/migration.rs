@@ -0,0 +1,122 @@
+use cosmwasm_std::{StdError, Storage};
+use cw2::get_contract_version;
+use semver::Version;
+use thiserror::Error;
+
+/// Checks that the contract kept in `storage` can be migrated to `contract_version` of
+/// `contract_name` and returns the version it is migrated from. A contract can only be migrated
+/// to the code of the same contract and never to an older version.
+pub fn assert_migration(
+    storage: &dyn Storage,
+    contract_name: &str,
+    contract_version: &str,
+) -> Result<Version, MigrationError> {
+    let previous = get_contract_version(storage)?;
+
+    if previous.contract != contract_name {
+        return Err(MigrationError::InvalidContract {
+            previous_contract: previous.contract,
+            contract: contract_name.to_string(),
+        });
+    }
+
+    let previous_version = parse_version(&previous.version)?;
+
+    if previous_version > parse_version(contract_version)? {
+        return Err(MigrationError::Downgrade {
+            previous_version: previous.version,
+            version: contract_version.to_string(),
+        });
+    }
+
+    Ok(previous_version)
+}
+
+fn parse_version(version: &str) -> Result<Version, MigrationError> {
+    Version::parse(version).map_err(|err| MigrationError::InvalidVersion {
+        version: version.to_string(),
+        err: err.to_string(),
+    })
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MigrationError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Cannot migrate contract `{previous_contract}` to `{contract}`")]
+    InvalidContract {
+        previous_contract: String,
+        contract: String,
+    },
+
+    #[error("Cannot migrate from version {previous_version} to the older version {version}")]
+    Downgrade {
+        previous_version: String,
+        version: String,
+    },
+
+    #[error("Invalid contract version `{version}`: {err}")]
+    InvalidVersion { version: String, err: String },
+}
+
+impl From<MigrationError> for StdError {
+    fn from(err: MigrationError) -> Self {
+        match err {
+            MigrationError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+    use cw2::set_contract_version;
+
+    const CONTRACT_NAME: &str = "crates.io:cw-zll-test";
+
+    #[test]
+    fn assert_migration_returns_previous_version() {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        assert_eq!(
+            assert_migration(&storage, CONTRACT_NAME, "0.1.0"),
+            Ok(Version::new(0, 1, 0))
+        );
+        assert_eq!(
+            assert_migration(&storage, CONTRACT_NAME, "0.2.0"),
+            Ok(Version::new(0, 1, 0))
+        );
+    }
+
+    #[test]
+    fn assert_migration_rejects_other_contracts() {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, "crates.io:cw20-base", "0.9.1").unwrap();
+
+        assert_eq!(
+            assert_migration(&storage, CONTRACT_NAME, "0.1.0"),
+            Err(MigrationError::InvalidContract {
+                previous_contract: "crates.io:cw20-base".to_string(),
+                contract: CONTRACT_NAME.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn assert_migration_rejects_downgrades() {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, CONTRACT_NAME, "0.2.0").unwrap();
+
+        assert_eq!(
+            assert_migration(&storage, CONTRACT_NAME, "0.1.9"),
+            Err(MigrationError::Downgrade {
+                previous_version: "0.2.0".to_string(),
+                version: "0.1.9".to_string(),
+            })
+        );
+    }
+}